@@ -0,0 +1,69 @@
+//! `CombinedNearbySearchWorkflow::search` の所要時間
+//!
+//! `cargo bench --bench combined_nearby_search` で実行する
+
+use bld_simulator::workflow::CombinedNearbySearchWorkflow;
+use bld_simulator::State;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// 各ケースを繰り返す回数
+const ITERATIONS: u32 = 5;
+
+fn main() {
+    let workflow = CombinedNearbySearchWorkflow::from_json(
+        include_str!("../resources/ufr_expanded.json"),
+        include_str!("../resources/ufr_parity.json"),
+        include_str!("../resources/ufr_twist.json"),
+        include_str!("../resources/uf_expanded.json"),
+        include_str!("../resources/uf_flip.json"),
+    )
+    .expect("Failed to create workflow");
+
+    // UF と UL が入れ替わった状態になってしまった、という想定
+    let target_state = State::new(
+        [0, 1, 2, 3, 4, 5, 6, 7],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 1, 2, 3, 4, 5, 11, 6, 8, 9, 10, 7],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    );
+
+    let cases = [
+        (
+            "corner 3-cycle + edge 3-cycle",
+            State::new(
+                [0, 1, 7, 3, 4, 5, 2, 6],
+                [0, 0, 1, 0, 0, 0, 2, 0],
+                [0, 1, 2, 3, 4, 7, 5, 6, 8, 9, 10, 11],
+                [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            ),
+        ),
+        (
+            "R U2 F' L D2 B R' U F2 D' L2 B2 U R2 F2",
+            State::new(
+                [7, 2, 6, 4, 3, 5, 1, 0],
+                [1, 2, 0, 1, 1, 0, 2, 2],
+                [4, 2, 6, 7, 3, 11, 10, 8, 0, 1, 5, 9],
+                [1, 0, 1, 0, 0, 1, 1, 0, 0, 0, 1, 1],
+            ),
+        ),
+    ];
+
+    for (name, initial_state) in &cases {
+        let mut total = Duration::ZERO;
+        for _ in 0..ITERATIONS {
+            let start = Instant::now();
+            let result = workflow
+                .search(black_box(initial_state), black_box(&target_state))
+                .expect("Failed to search");
+            total += start.elapsed();
+            black_box(result);
+        }
+        println!(
+            "{:<45} {:>10.2} ms/iter ({} iterations)",
+            name,
+            total.as_secs_f64() * 1000.0 / ITERATIONS as f64,
+            ITERATIONS
+        );
+    }
+}
@@ -9,6 +9,8 @@ fn main() {
         ("D/R' U' R", "スラッシュ記法"),
         ("R' D': U/R D R'", "ネスト: コロン + スラッシュ"),
         ("U R U': D, R' U' R", "ネスト: コロン + カンマ"),
+        ("[R D R': [U, R D' R']]", "括弧: コロン + カンマ"),
+        ("(R U R' U')3", "繰り返し"),
     ];
 
     for (input, description) in test_cases {
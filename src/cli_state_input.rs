@@ -8,8 +8,12 @@ use ratatui::{
     Frame, Terminal,
 };
 use std::io;
-use crate::cube::State;
+use crate::cube::{RubiksCube, State, StateComponent, StateValidation};
 use crate::display::{StateToDisplay, CubeNetWidget};
+use crate::inspection::{
+    CornerInspection, CornerOperation, EdgeInspection, LetterScheme, SolverConfig,
+};
+use crate::workflow::bld_workflow::AllOperations;
 
 /// Interactive state input editor for cube state components
 pub struct StateInputEditor {
@@ -18,14 +22,19 @@ pub struct StateInputEditor {
     target_co: [usize; 8],
     target_ep: [usize; 12],
     target_eo: [usize; 12],
+    facelets: String,
+    facelet_error: Option<String>,
     current_field: StateField,
     cursor_position: usize,
     is_confirmed: bool,
+    /// スクランブルの記憶する文字の組に使う割り当て
+    scheme: LetterScheme,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum StateField {
     Scramble,
+    Facelets,
     Cp,
     Co,
     Ep,
@@ -41,12 +50,21 @@ impl StateInputEditor {
             target_co: [0, 0, 0, 0, 0, 0, 0, 0],
             target_ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
             target_eo: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            facelets: State::solved().to_facelet_string(),
+            facelet_error: None,
             current_field: StateField::Scramble,
             cursor_position: 0,
             is_confirmed: false,
+            scheme: LetterScheme::speffz(),
         }
     }
 
+    /// Show the scramble's letter pairs in `scheme` (Speffz by default)
+    pub fn with_letter_scheme(mut self, scheme: LetterScheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
     /// Run the interactive editor
     pub fn run<B: Backend>(
         &mut self,
@@ -64,7 +82,19 @@ impl StateInputEditor {
                         }
                         KeyCode::Enter => {
                             // Move to next field or confirm if on last field
+                            if self.current_field == StateField::Facelets {
+                                // Load the target state only if the facelets are valid
+                                if !self.load_facelets() {
+                                    continue;
+                                }
+                            }
                             if self.current_field == StateField::Eo {
+                                // Jump to the first invalid field instead of submitting
+                                if let Some((field, position)) = self.first_invalid_field() {
+                                    self.current_field = field;
+                                    self.cursor_position = position;
+                                    continue;
+                                }
                                 self.is_confirmed = true;
                                 return Ok(Some((
                                     self.scramble.clone(),
@@ -79,7 +109,10 @@ impl StateInputEditor {
                             }
                         }
                         KeyCode::Tab => {
-                            // Move to next field
+                            // Move to next field, discarding unloaded facelet edits
+                            if self.current_field == StateField::Facelets {
+                                self.sync_facelets();
+                            }
                             self.current_field = self.next_field();
                             self.cursor_position = 0;
                         }
@@ -97,6 +130,7 @@ impl StateInputEditor {
                                         self.scramble.len() - 1
                                     }
                                 }
+                                StateField::Facelets => self.facelets.len(),
                                 StateField::Cp | StateField::Co => 7,
                                 StateField::Ep | StateField::Eo => 11,
                             };
@@ -105,32 +139,39 @@ impl StateInputEditor {
                             }
                         }
                         KeyCode::Up => {
-                            if self.current_field != StateField::Scramble {
+                            if !self.is_text_field() {
                                 self.increment_current_value();
+                                self.sync_facelets();
                             }
                         }
                         KeyCode::Down => {
-                            if self.current_field != StateField::Scramble {
+                            if !self.is_text_field() {
                                 self.decrement_current_value();
+                                self.sync_facelets();
                             }
                         }
                         KeyCode::Char(c) => {
-                            if self.current_field == StateField::Scramble {
-                                self.scramble.insert(self.cursor_position, c);
+                            let cursor_position = self.cursor_position;
+                            if let Some(text) = self.current_text_mut() {
+                                text.insert(cursor_position, c);
                                 self.cursor_position += 1;
                             }
                         }
                         KeyCode::Backspace => {
-                            if self.current_field == StateField::Scramble && self.cursor_position > 0 {
-                                self.scramble.remove(self.cursor_position - 1);
-                                self.cursor_position -= 1;
+                            let cursor_position = self.cursor_position;
+                            if cursor_position > 0 {
+                                if let Some(text) = self.current_text_mut() {
+                                    text.remove(cursor_position - 1);
+                                    self.cursor_position -= 1;
+                                }
                             }
                         }
                         KeyCode::Delete => {
-                            if self.current_field == StateField::Scramble
-                                && self.cursor_position < self.scramble.len()
-                            {
-                                self.scramble.remove(self.cursor_position);
+                            let cursor_position = self.cursor_position;
+                            if let Some(text) = self.current_text_mut() {
+                                if cursor_position < text.len() {
+                                    text.remove(cursor_position);
+                                }
                             }
                         }
                         _ => {}
@@ -140,9 +181,107 @@ impl StateInputEditor {
         }
     }
 
+    fn is_text_field(&self) -> bool {
+        matches!(self.current_field, StateField::Scramble | StateField::Facelets)
+    }
+
+    fn current_text_mut(&mut self) -> Option<&mut String> {
+        match self.current_field {
+            StateField::Scramble => Some(&mut self.scramble),
+            StateField::Facelets => Some(&mut self.facelets),
+            _ => None,
+        }
+    }
+
+    /// Parse the facelet field into the target state
+    ///
+    /// Returns false and keeps the error message if the facelets are invalid.
+    fn load_facelets(&mut self) -> bool {
+        // The target arrays have no centers, so hold the cube with its centers at home
+        let state = State::from_facelet_string(&self.facelets)
+            .map(|state| state.reoriented().unwrap_or(state));
+        match state {
+            Ok(state) => {
+                self.target_cp = state.cp.map(|x| x as usize);
+                self.target_co = state.co.map(|x| x as usize);
+                self.target_ep = state.ep.map(|x| x as usize);
+                self.target_eo = state.eo.map(|x| x as usize);
+                self.sync_facelets();
+                true
+            }
+            Err(err) => {
+                self.facelet_error = Some(err.to_string());
+                false
+            }
+        }
+    }
+
+    /// Rewrite the facelet field from the target arrays
+    fn sync_facelets(&mut self) {
+        self.facelets = self.target_state().to_facelet_string();
+        self.facelet_error = None;
+    }
+
+    /// スクランブルを UFR / UF バッファで分析した、記憶する文字の組
+    fn scramble_memo(&self) -> Result<String, String> {
+        let state = RubiksCube::new()
+            .scramble_to_state(&self.scramble)
+            .map_err(|e| e.to_string())?;
+        let state = state.reoriented().unwrap_or(state);
+
+        let config = SolverConfig::default();
+        let corners = CornerInspection::solve_corner_permutation_with_config(&state, &config);
+        let corner_swaps = corners
+            .iter()
+            .filter(|op| matches!(op, CornerOperation::Swap(_)))
+            .count();
+        let edges = EdgeInspection::solve_edge_permutation_with_config(
+            &state,
+            corner_swaps % 2 == 1,
+            &config,
+        );
+
+        let mut operations = AllOperations::new();
+        for op in edges {
+            operations.push_edge(op);
+        }
+        for op in corners {
+            operations.push_corner(op);
+        }
+        Ok(operations.letter_pairs(&self.scheme))
+    }
+
+    fn target_state(&self) -> State {
+        State::from_arrays(
+            self.target_cp,
+            self.target_co,
+            self.target_ep,
+            self.target_eo,
+        )
+    }
+
+    fn validation(&self) -> StateValidation {
+        self.target_state().validate()
+    }
+
+    /// Field and cursor position of the first problem in the target state
+    fn first_invalid_field(&self) -> Option<(StateField, usize)> {
+        self.validation().issues.iter().find_map(|issue| {
+            let field = match issue.component() {
+                StateComponent::Cp => StateField::Cp,
+                StateComponent::Co => StateField::Co,
+                StateComponent::Ep => StateField::Ep,
+                StateComponent::Eo => StateField::Eo,
+                StateComponent::Centers => return None,
+            };
+            Some((field, issue.positions().first().copied().unwrap_or(0)))
+        })
+    }
+
     fn next_field(&self) -> StateField {
         match self.current_field {
-            StateField::Scramble => StateField::Cp,
+            StateField::Scramble => StateField::Facelets,
+            StateField::Facelets => StateField::Cp,
             StateField::Cp => StateField::Co,
             StateField::Co => StateField::Ep,
             StateField::Ep => StateField::Eo,
@@ -152,7 +291,7 @@ impl StateInputEditor {
 
     fn increment_current_value(&mut self) {
         match self.current_field {
-            StateField::Scramble => {}
+            StateField::Scramble | StateField::Facelets => {}
             StateField::Cp => {
                 let max_value = 7;
                 self.target_cp[self.cursor_position] =
@@ -178,7 +317,7 @@ impl StateInputEditor {
 
     fn decrement_current_value(&mut self) {
         match self.current_field {
-            StateField::Scramble => {}
+            StateField::Scramble | StateField::Facelets => {}
             StateField::Cp => {
                 let max_value = 7;
                 self.target_cp[self.cursor_position] = if self.target_cp[self.cursor_position] == 0
@@ -219,6 +358,8 @@ impl StateInputEditor {
     }
 
     fn ui(&self, f: &mut Frame) {
+        let validation = self.validation();
+
         // Split the screen into left (input) and right (cube display)
         let main_chunks = Layout::default()
             .direction(Direction::Horizontal)
@@ -234,10 +375,12 @@ impl StateInputEditor {
             .constraints([
                 Constraint::Length(3), // Title
                 Constraint::Length(3), // Scramble field
+                Constraint::Length(3), // Facelets field
                 Constraint::Length(3), // CP field
                 Constraint::Length(3), // CO field
                 Constraint::Length(3), // EP field
                 Constraint::Length(3), // EO field
+                Constraint::Length(3), // Scramble memo
                 Constraint::Min(6),    // Instructions
             ])
             .split(main_chunks[0]);
@@ -252,50 +395,84 @@ impl StateInputEditor {
         // Render scramble field
         self.render_scramble_field(f, left_chunks[1]);
 
+        // Render facelets field
+        self.render_facelets_field(f, left_chunks[2]);
+
         // Render target state fields
         self.render_field(
             f,
-            left_chunks[2],
+            left_chunks[3],
             "Target CP (Corner Permutation)",
             &self.target_cp,
             StateField::Cp,
+            &validation,
         );
         self.render_field(
             f,
-            left_chunks[3],
+            left_chunks[4],
             "Target CO (Corner Orientation)",
             &self.target_co,
             StateField::Co,
+            &validation,
         );
         self.render_field(
             f,
-            left_chunks[4],
+            left_chunks[5],
             "Target EP (Edge Permutation)",
             &self.target_ep,
             StateField::Ep,
+            &validation,
         );
         self.render_field(
             f,
-            left_chunks[5],
+            left_chunks[6],
             "Target EO (Edge Orientation)",
             &self.target_eo,
             StateField::Eo,
+            &validation,
         );
 
+        // Scramble memo
+        let memo = match self.scramble_memo() {
+            Ok(memo) if memo.is_empty() => {
+                Span::styled("(solved)", Style::default().fg(Color::Gray))
+            }
+            Ok(memo) => Span::styled(memo, Style::default().fg(Color::White)),
+            Err(e) => Span::styled(e, Style::default().fg(Color::Red)),
+        };
+        let memo_widget = Paragraph::new(Line::from(memo)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Scramble Memo"),
+        );
+        f.render_widget(memo_widget, left_chunks[7]);
+
         // Instructions
+        let status = if validation.is_valid() {
+            Line::from(Span::styled("State: valid", Style::default().fg(Color::Green)))
+        } else {
+            Line::from(Span::styled(
+                format!("Invalid state: {}", validation.issues[0]),
+                Style::default().fg(Color::Red),
+            ))
+        };
         let instructions = vec![
-            Line::from(""),
+            status,
             Line::from(vec![
                 Span::styled("Scramble: ", Style::default().fg(Color::Yellow)),
                 Span::raw("Type moves (e.g., R U R' D)"),
             ]),
+            Line::from(vec![
+                Span::styled("Facelets: ", Style::default().fg(Color::Yellow)),
+                Span::raw("54 stickers in URFDLB order, Enter to load"),
+            ]),
             Line::from(vec![
                 Span::styled("State: ", Style::default().fg(Color::Yellow)),
                 Span::raw("← → to move, ↑ ↓ to change value"),
             ]),
             Line::from(vec![
                 Span::styled("Tab/Enter: ", Style::default().fg(Color::Yellow)),
-                Span::raw("Next field / Submit (on EO)"),
+                Span::raw("Next field / Submit (on EO, if valid)"),
             ]),
             Line::from(vec![
                 Span::styled("Esc: ", Style::default().fg(Color::Yellow)),
@@ -309,15 +486,10 @@ impl StateInputEditor {
                     .title("Instructions"),
             )
             .style(Style::default().fg(Color::Gray));
-        f.render_widget(instructions_widget, left_chunks[6]);
+        f.render_widget(instructions_widget, left_chunks[8]);
 
         // Right side: Cube display
-        let state = State::from_arrays(
-            self.target_cp,
-            self.target_co,
-            self.target_ep,
-            self.target_eo,
-        );
+        let state = self.target_state();
         let cube_display = StateToDisplay::convert(&state);
         let cube_widget = CubeNetWidget::new(&cube_display)
             .title("Target State Preview");
@@ -384,6 +556,49 @@ impl StateInputEditor {
         f.render_widget(field_widget, area);
     }
 
+    fn render_facelets_field(&self, f: &mut Frame, area: ratatui::layout::Rect) {
+        let is_active = self.current_field == StateField::Facelets;
+        let border_style = if self.facelet_error.is_some() {
+            Style::default().fg(Color::Red)
+        } else if is_active {
+            Style::default().fg(Color::Green)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+
+        let mut spans = Vec::new();
+        for (i, c) in self.facelets.chars().enumerate() {
+            if is_active && i == self.cursor_position {
+                spans.push(Span::styled(
+                    c.to_string(),
+                    Style::default().fg(Color::Black).bg(Color::Yellow),
+                ));
+            } else {
+                spans.push(Span::raw(c.to_string()));
+            }
+        }
+        if is_active && self.cursor_position >= self.facelets.len() {
+            spans.push(Span::styled(
+                " ",
+                Style::default().fg(Color::Black).bg(Color::Yellow),
+            ));
+        }
+
+        let title = match &self.facelet_error {
+            Some(err) => format!("Facelets (URFDLB) - {}", err),
+            None => "Facelets (URFDLB)".to_string(),
+        };
+        let field_widget = Paragraph::new(Line::from(spans))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .style(border_style),
+            )
+            .alignment(Alignment::Left);
+        f.render_widget(field_widget, area);
+    }
+
     fn render_field<T: std::fmt::Display>(
         &self,
         f: &mut Frame,
@@ -391,13 +606,31 @@ impl StateInputEditor {
         title: &str,
         values: &[T],
         field: StateField,
+        validation: &StateValidation,
     ) {
+        let component = match field {
+            StateField::Cp => StateComponent::Cp,
+            StateField::Co => StateComponent::Co,
+            StateField::Ep => StateComponent::Ep,
+            StateField::Eo => StateComponent::Eo,
+            StateField::Scramble | StateField::Facelets => return,
+        };
+        let issues = validation.issues_for(component);
+        let invalid_positions: Vec<usize> =
+            issues.iter().flat_map(|issue| issue.positions()).collect();
+
         let is_active = self.current_field == field;
-        let border_style = if is_active {
+        let border_style = if !issues.is_empty() {
+            Style::default().fg(Color::Red)
+        } else if is_active {
             Style::default().fg(Color::Green)
         } else {
             Style::default().fg(Color::Gray)
         };
+        let title = match issues.first() {
+            Some(issue) => format!("{} - {}", title, issue),
+            None => title.to_string(),
+        };
 
         // Create the display text with cursor
         let mut spans = vec![Span::raw("[")];
@@ -408,6 +641,11 @@ impl StateInputEditor {
                     format!("{}", value),
                     Style::default().fg(Color::Black).bg(Color::Yellow),
                 ));
+            } else if invalid_positions.contains(&i) {
+                spans.push(Span::styled(
+                    format!("{}", value),
+                    Style::default().fg(Color::Red),
+                ));
             } else {
                 spans.push(Span::styled(
                     format!("{}", value),
@@ -432,3 +670,35 @@ impl StateInputEditor {
         f.render_widget(field_widget, area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow::bld_workflow::create_test_workflow;
+
+    #[test]
+    fn test_scramble_memo() {
+        let mut editor = StateInputEditor::new();
+        assert_eq!(editor.scramble_memo().unwrap(), "");
+
+        // 解法と同じ文字の組を、指定した割り当てで表示する
+        editor.scramble = "R U R' U' R' F R2 U' R' U' R U R' F'".to_string();
+        let state = RubiksCube::new()
+            .scramble_to_state(&editor.scramble)
+            .unwrap();
+        let solution = create_test_workflow().solve(&state).unwrap();
+        let kana = LetterScheme::from_json(include_str!("../resources/kana_scheme.json")).unwrap();
+        let editor = editor.with_letter_scheme(kana.clone());
+        assert_eq!(
+            editor.scramble_memo().unwrap(),
+            solution.all_operations.letter_pairs(&kana)
+        );
+        assert!(!editor.scramble_memo().unwrap().is_empty());
+
+        let editor = StateInputEditor {
+            scramble: "R Q".to_string(),
+            ..StateInputEditor::new()
+        };
+        assert!(editor.scramble_memo().is_err());
+    }
+}
@@ -0,0 +1,360 @@
+use super::state::{State, CENTER_COUNT, CENTER_NAMES, CORNER_STICKERS, EDGE_STICKERS};
+use super::validation::StateValidation;
+
+/// Number of stickers in a facelet string
+pub const FACELET_COUNT: usize = 54;
+
+/// 面の並び（URFDLB）
+const FACE_ORDER: [char; 6] = ['U', 'R', 'F', 'D', 'L', 'B'];
+
+/// 各面のセンターの facelet 番号（State::centers の並び U, D, L, R, F, B）
+const CENTER_FACELETS: [usize; CENTER_COUNT] = [4, 31, 40, 13, 22, 49];
+
+/// コーナーの facelet 番号（CORNER_STICKERS と同じ並び）
+///
+/// U1..U9 = 0..8, R = 9..17, F = 18..26, D = 27..35, L = 36..44, B = 45..53
+const CORNER_FACELETS: [[usize; 3]; 8] = [
+    [0, 47, 36],  // UBL: U1 B3 L1
+    [2, 11, 45],  // UBR: U3 R3 B1
+    [8, 20, 9],   // UFR: U9 F3 R1
+    [6, 38, 18],  // UFL: U7 L3 F1
+    [33, 42, 53], // DBL: D7 L7 B9
+    [35, 51, 17], // DBR: D9 B7 R9
+    [29, 15, 26], // DFR: D3 R7 F9
+    [27, 24, 44], // DFL: D1 F7 L9
+];
+
+/// エッジの facelet 番号（EDGE_STICKERS と同じ並び）
+const EDGE_FACELETS: [[usize; 2]; 12] = [
+    [50, 39], // BL: B6 L4
+    [48, 14], // BR: B4 R6
+    [23, 12], // FR: F6 R4
+    [21, 41], // FL: F4 L6
+    [1, 46],  // UB: U2 B2
+    [5, 10],  // UR: U6 R2
+    [7, 19],  // UF: U8 F2
+    [3, 37],  // UL: U4 L2
+    [34, 52], // DB: D8 B8
+    [32, 16], // DR: D6 R8
+    [28, 25], // DF: D2 F8
+    [30, 43], // DL: D4 L8
+];
+
+/// Facelet string を読み込めない理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaceletError {
+    /// 54 文字でない
+    WrongLength(usize),
+    /// URFDLB 以外の文字
+    InvalidCharacter { index: usize, character: char },
+    /// ある色のステッカーが 9 枚でない
+    WrongColorCount { color: char, count: usize },
+    /// 同じ色のセンターが複数ある
+    DuplicateCenter { color: char },
+    /// 存在しない色の組み合わせのコーナー
+    UnknownCorner { position: usize, colors: String },
+    /// 存在しない色の組み合わせのエッジ
+    UnknownEdge { position: usize, colors: String },
+    /// ピースは読み取れたが、実際のキューブでは作れない
+    IllegalState(StateValidation),
+}
+
+impl std::fmt::Display for FaceletError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaceletError::WrongLength(length) => write!(
+                f,
+                "facelet string must have {} stickers, got {}",
+                FACELET_COUNT, length
+            ),
+            FaceletError::InvalidCharacter { index, character } => write!(
+                f,
+                "invalid sticker '{}' at {} ({}): expected one of URFDLB",
+                character,
+                index,
+                facelet_name(*index)
+            ),
+            FaceletError::WrongColorCount { color, count } => {
+                write!(f, "color {} appears {} times (expected 9)", color, count)
+            }
+            FaceletError::DuplicateCenter { color } => {
+                write!(f, "more than one center has color {}", color)
+            }
+            FaceletError::UnknownCorner { position, colors } => write!(
+                f,
+                "corner at {} has colors {} which is not a corner of the cube",
+                CORNER_STICKERS[*position][0], colors
+            ),
+            FaceletError::UnknownEdge { position, colors } => write!(
+                f,
+                "edge at {} has colors {} which is not an edge of the cube",
+                EDGE_STICKERS[*position][0], colors
+            ),
+            FaceletError::IllegalState(validation) => {
+                write!(f, "impossible cube state: {}", validation)
+            }
+        }
+    }
+}
+
+/// facelet 番号を "U1" のような名前にする
+fn facelet_name(index: usize) -> String {
+    let face = FACE_ORDER.get(index / 9).copied().unwrap_or('?');
+    format!("{}{}", face, index % 9 + 1)
+}
+
+/// ステッカーの色（= 揃った状態でその面の文字）
+fn sticker_color(name: &str) -> char {
+    name.chars().next().unwrap()
+}
+
+impl State {
+    /// Convert to a 54-character facelet string in URFDLB order
+    ///
+    /// Each face is read row by row as in the usual net: U and D with F
+    /// below/above them, the side faces with U on top. A solved cube is
+    /// `UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB`.
+    pub fn to_facelet_string(&self) -> String {
+        let mut facelets = ['?'; FACELET_COUNT];
+
+        for (i, &facelet) in CENTER_FACELETS.iter().enumerate() {
+            facelets[facelet] = sticker_color(CENTER_NAMES[self.centers[i] as usize]);
+        }
+
+        // 位置 i の k 番目のステッカーには、ピースの (co[i] + k) 番目のステッカーが来る
+        for (i, stickers) in CORNER_FACELETS.iter().enumerate() {
+            let piece = self.cp[i] as usize;
+            for (k, &facelet) in stickers.iter().enumerate() {
+                let sticker = (self.co[i] as usize + k) % 3;
+                facelets[facelet] = sticker_color(CORNER_STICKERS[piece][sticker]);
+            }
+        }
+
+        for (i, stickers) in EDGE_FACELETS.iter().enumerate() {
+            let piece = self.ep[i] as usize;
+            for (k, &facelet) in stickers.iter().enumerate() {
+                let sticker = (self.eo[i] as usize + k) % 2;
+                facelets[facelet] = sticker_color(EDGE_STICKERS[piece][sticker]);
+            }
+        }
+
+        facelets.iter().collect()
+    }
+
+    /// Read a 54-character facelet string in URFDLB order
+    ///
+    /// Whitespace is ignored so that strings split per face can be pasted.
+    /// The result is checked with `State::validate`, so a colouring that
+    /// needs a twisted corner or a swapped pair of pieces is rejected.
+    pub fn from_facelet_string(input: &str) -> Result<State, FaceletError> {
+        let facelets: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+        if facelets.len() != FACELET_COUNT {
+            return Err(FaceletError::WrongLength(facelets.len()));
+        }
+
+        for (index, &character) in facelets.iter().enumerate() {
+            if !FACE_ORDER.contains(&character) {
+                return Err(FaceletError::InvalidCharacter { index, character });
+            }
+        }
+
+        for &color in FACE_ORDER.iter() {
+            let count = facelets.iter().filter(|&&c| c == color).count();
+            if count != 9 {
+                return Err(FaceletError::WrongColorCount { color, count });
+            }
+        }
+
+        // センター
+        let mut centers = [0u8; CENTER_COUNT];
+        for (i, &facelet) in CENTER_FACELETS.iter().enumerate() {
+            let color = facelets[facelet];
+            let center = CENTER_NAMES
+                .iter()
+                .position(|name| sticker_color(name) == color)
+                .unwrap();
+            if centers[..i].contains(&(center as u8)) {
+                return Err(FaceletError::DuplicateCenter { color });
+            }
+            centers[i] = center as u8;
+        }
+
+        // コーナー: 色の並びが一致するピースと向きを探す
+        let mut cp = [0u8; 8];
+        let mut co = [0u8; 8];
+        for (i, stickers) in CORNER_FACELETS.iter().enumerate() {
+            let colors: Vec<char> = stickers.iter().map(|&facelet| facelets[facelet]).collect();
+            let found = (0..8).find_map(|piece| {
+                (0..3)
+                    .find(|&ori| {
+                        (0..3).all(|k| {
+                            sticker_color(CORNER_STICKERS[piece][(ori + k) % 3]) == colors[k]
+                        })
+                    })
+                    .map(|ori| (piece, ori))
+            });
+            match found {
+                Some((piece, ori)) => {
+                    cp[i] = piece as u8;
+                    co[i] = ori as u8;
+                }
+                None => {
+                    return Err(FaceletError::UnknownCorner {
+                        position: i,
+                        colors: colors.iter().collect(),
+                    })
+                }
+            }
+        }
+
+        // エッジ
+        let mut ep = [0u8; 12];
+        let mut eo = [0u8; 12];
+        for (i, stickers) in EDGE_FACELETS.iter().enumerate() {
+            let colors: Vec<char> = stickers.iter().map(|&facelet| facelets[facelet]).collect();
+            let found = (0..12).find_map(|piece| {
+                (0..2)
+                    .find(|&ori| {
+                        (0..2).all(|k| {
+                            sticker_color(EDGE_STICKERS[piece][(ori + k) % 2]) == colors[k]
+                        })
+                    })
+                    .map(|ori| (piece, ori))
+            });
+            match found {
+                Some((piece, ori)) => {
+                    ep[i] = piece as u8;
+                    eo[i] = ori as u8;
+                }
+                None => {
+                    return Err(FaceletError::UnknownEdge {
+                        position: i,
+                        colors: colors.iter().collect(),
+                    })
+                }
+            }
+        }
+
+        let state = State::with_centers(cp, co, ep, eo, centers);
+        let validation = state.validate();
+        if !validation.is_valid() {
+            return Err(FaceletError::IllegalState(validation));
+        }
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::operations::RubiksCube;
+
+    const SOLVED: &str = "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB";
+
+    #[test]
+    fn test_solved_facelets() {
+        assert_eq!(State::solved().to_facelet_string(), SOLVED);
+        assert_eq!(State::from_facelet_string(SOLVED), Ok(State::solved()));
+    }
+
+    #[test]
+    fn test_single_moves_match_standard_facelets() {
+        let cube = RubiksCube::new();
+
+        assert_eq!(
+            cube.scramble_to_state("R").unwrap().to_facelet_string(),
+            "UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB"
+        );
+        assert_eq!(
+            cube.scramble_to_state("U").unwrap().to_facelet_string(),
+            "UUUUUUUUUBBBRRRRRRRRRFFFFFFDDDDDDDDDFFFLLLLLLLLLBBBBBB"
+        );
+        assert_eq!(
+            cube.scramble_to_state("F").unwrap().to_facelet_string(),
+            "UUUUUULLLURRURRURRFFFFFFFFFRRRDDDDDDLLDLLDLLDBBBBBBBBB"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let cube = RubiksCube::new();
+        for scramble in [
+            "R U R' U'",
+            "D2 F' L B2 R' U2 F D' L2 B",
+            "r U x' M' E2 S",
+            "y z'",
+        ] {
+            let state = cube.scramble_to_state(scramble).unwrap();
+            let facelets = state.to_facelet_string();
+            assert_eq!(
+                State::from_facelet_string(&facelets),
+                Ok(state),
+                "{}",
+                scramble
+            );
+        }
+    }
+
+    #[test]
+    fn test_whitespace_is_ignored() {
+        let spaced = "UUUUUUUUU RRRRRRRRR FFFFFFFFF DDDDDDDDD LLLLLLLLL BBBBBBBBB";
+        assert_eq!(State::from_facelet_string(spaced), Ok(State::solved()));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            State::from_facelet_string("UUU"),
+            Err(FaceletError::WrongLength(3))
+        );
+
+        let mut invalid = SOLVED.to_string();
+        invalid.replace_range(10..11, "X");
+        assert_eq!(
+            State::from_facelet_string(&invalid),
+            Err(FaceletError::InvalidCharacter {
+                index: 10,
+                character: 'X'
+            })
+        );
+
+        let mut wrong_count = SOLVED.to_string();
+        wrong_count.replace_range(0..1, "R");
+        assert_eq!(
+            State::from_facelet_string(&wrong_count),
+            Err(FaceletError::WrongColorCount {
+                color: 'U',
+                count: 8
+            })
+        );
+
+        // UFR の U と R を入れ替えると鏡像のコーナーになる
+        let mut mirrored = SOLVED.to_string();
+        mirrored.replace_range(8..9, "R");
+        mirrored.replace_range(9..10, "U");
+        assert!(matches!(
+            State::from_facelet_string(&mirrored),
+            Err(FaceletError::UnknownCorner { position: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_illegal_colourings() {
+        // UF を反転
+        let mut flipped = SOLVED.to_string();
+        flipped.replace_range(7..8, "F");
+        flipped.replace_range(19..20, "U");
+        let err = State::from_facelet_string(&flipped).unwrap_err();
+        assert!(matches!(err, FaceletError::IllegalState(_)));
+        assert!(err.to_string().contains("eo"));
+
+        // UFR を 1 回ねじる
+        let mut state = State::solved();
+        state.co[2] = 1;
+        let twisted = state.to_facelet_string();
+        assert!(matches!(
+            State::from_facelet_string(&twisted),
+            Err(FaceletError::IllegalState(_))
+        ));
+    }
+}
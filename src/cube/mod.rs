@@ -1,7 +1,17 @@
 pub mod state;
 pub mod operations;
+pub mod move_table;
 pub mod solver;
+pub mod two_phase;
+pub mod validation;
+pub mod facelet;
+pub mod random_state;
 
-pub use state::{State, PartialStatePattern};
+pub use state::{Cycles, PartialStatePattern, PieceCycle, State};
 pub use operations::RubiksCube;
-pub use solver::{SolutionSearcher, Move};
\ No newline at end of file
+pub use move_table::PackedState;
+pub use solver::{MoveSet, SolutionSearcher};
+pub use two_phase::TwoPhaseSolver;
+pub use facelet::FaceletError;
+pub use random_state::{InspectionSummary, RandomStateGenerator, ScrambleConstraint, SeededRng};
+pub use validation::{StateComponent, StateIssue, StateValidation};
\ No newline at end of file
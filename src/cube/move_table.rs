@@ -0,0 +1,227 @@
+use super::operations::RubiksCube;
+use super::state::{State, CENTER_COUNT};
+use crate::parser::move_parser::NotationMove;
+use std::sync::OnceLock;
+
+/// NotationMove の種類の数（Noop を含む）
+pub const NOTATION_MOVE_COUNT: usize = NotationMove::Noop as usize + 1;
+
+/// `NotationMove` で直接引ける操作の表
+///
+/// 一度だけ `RubiksCube::new()` から作り、以降は文字列や HashMap を介さずに参照する
+fn move_table() -> &'static [State; NOTATION_MOVE_COUNT] {
+    static TABLE: OnceLock<[State; NOTATION_MOVE_COUNT]> = OnceLock::new();
+
+    TABLE.get_or_init(|| {
+        let cube = RubiksCube::new();
+        // Noop を含め、未登録の操作は恒等変換のまま
+        let mut table: [State; NOTATION_MOVE_COUNT] = std::array::from_fn(|_| State::solved());
+
+        for name in cube.get_move_names() {
+            let mv = NotationMove::from_str(&name)
+                .unwrap_or_else(|_| panic!("move table: unknown move '{}'", name));
+            table[mv.index()] = cube
+                .apply_move(&State::solved(), &name)
+                .expect("move listed by get_move_names");
+        }
+
+        table
+    })
+}
+
+/// `mv` に対応する操作を State として返す（Noop は完成状態）
+pub fn notation_move_state(mv: &NotationMove) -> &'static State {
+    &move_table()[mv.index()]
+}
+
+/// Compact copy of `State` for hot loops
+///
+/// Each corner is stored as `piece * 3 + orientation` and each edge as
+/// `piece * 2 + orientation`, so applying a move is one table lookup and a
+/// single pass over 26 bytes with no allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedState {
+    corners: [u8; 8],
+    edges: [u8; 12],
+    centers: [u8; CENTER_COUNT],
+}
+
+impl PackedState {
+    pub fn solved() -> Self {
+        PackedState::from(&State::solved())
+    }
+
+    pub fn to_state(&self) -> State {
+        State::with_centers(
+            self.corners.map(|c| c / 3),
+            self.corners.map(|c| c % 3),
+            self.edges.map(|e| e / 2),
+            self.edges.map(|e| e % 2),
+            self.centers,
+        )
+    }
+
+    /// NotationMove をその場で適用する
+    pub fn apply_notation_move(&mut self, mv: &NotationMove) {
+        if matches!(mv, NotationMove::Noop) {
+            return;
+        }
+        self.apply_move(notation_move_state(mv));
+    }
+
+    /// `State::apply_move` と同じ規則で操作をその場で適用する
+    pub fn apply_move(&mut self, move_state: &State) {
+        let corners = self.corners;
+        for i in 0..8 {
+            let c = corners[move_state.cp[i] as usize];
+            self.corners[i] = twist(c, move_state.co[i]);
+        }
+
+        let edges = self.edges;
+        for i in 0..12 {
+            let e = edges[move_state.ep[i] as usize];
+            self.edges[i] = e ^ move_state.eo[i];
+        }
+
+        let centers = self.centers;
+        for i in 0..CENTER_COUNT {
+            self.centers[i] = centers[move_state.centers[i] as usize];
+        }
+    }
+
+    /// 手順を順に適用する
+    pub fn apply_sequence<'a>(&mut self, moves: impl IntoIterator<Item = &'a NotationMove>) {
+        for mv in moves {
+            self.apply_notation_move(mv);
+        }
+    }
+
+    /// `CornerSwapOperation::apply` と同じ規則で2つのコーナーを交換する
+    pub fn swap_corners(&mut self, target1: usize, target2: usize, orientation: u8) {
+        let first = self.corners[target1];
+        let second = self.corners[target2];
+        self.corners[target1] = twist(second, orientation);
+        self.corners[target2] = twist(first, 3 - orientation);
+    }
+
+    /// `CornerTwistOperation::apply` と同じ規則でコーナーをねじる
+    pub fn twist_corner(&mut self, target: usize, orientation: u8) {
+        self.corners[target] = twist(self.corners[target], 3 - orientation);
+    }
+
+    /// `EdgeSwapOperation::apply` と同じ規則で2つのエッジを交換する
+    pub fn swap_edges(&mut self, target1: usize, target2: usize, orientation: u8) {
+        let first = self.edges[target1];
+        let second = self.edges[target2];
+        self.edges[target1] = second ^ orientation;
+        self.edges[target2] = first ^ orientation;
+    }
+
+    /// エッジを反転する
+    pub fn flip_edge(&mut self, target: usize) {
+        self.edges[target] ^= 1;
+    }
+}
+
+impl From<&State> for PackedState {
+    fn from(state: &State) -> Self {
+        PackedState {
+            corners: std::array::from_fn(|i| state.cp[i] * 3 + state.co[i] % 3),
+            edges: std::array::from_fn(|i| state.ep[i] * 2 + state.eo[i] % 2),
+            centers: state.centers,
+        }
+    }
+}
+
+impl From<PackedState> for State {
+    fn from(packed: PackedState) -> Self {
+        packed.to_state()
+    }
+}
+
+/// 向きを `amount` だけ進めたコーナーを返す
+fn twist(corner: u8, amount: u8) -> u8 {
+    let orientation = corner % 3;
+    corner - orientation + (orientation + amount) % 3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::operations::apply_notation_move;
+    use crate::inspection::{
+        CornerSwapOperation, CornerTwistOperation, EdgeFlipOperation, EdgeSwapOperation,
+    };
+    use crate::parser::parse_sequence;
+
+    #[test]
+    fn test_table_matches_rubiks_cube() {
+        let cube = RubiksCube::new();
+        for name in cube.get_move_names() {
+            let mv = NotationMove::from_str(&name).unwrap();
+            assert_eq!(
+                Some(notation_move_state(&mv).clone()),
+                cube.apply_move(&State::solved(), &name),
+                "{}",
+                name
+            );
+        }
+        assert_eq!(notation_move_state(&NotationMove::Noop), &State::solved());
+    }
+
+    #[test]
+    fn test_packed_round_trip() {
+        let cube = RubiksCube::new();
+        let state = cube
+            .scramble_to_state("R U F' L2 D B' M E' S x y'")
+            .unwrap();
+        assert_eq!(PackedState::from(&state).to_state(), state);
+        assert_eq!(PackedState::solved().to_state(), State::solved());
+    }
+
+    #[test]
+    fn test_packed_moves_match_state() {
+        let cube = RubiksCube::new();
+        let scramble = "R U R' u2 D' l F2 b' M S2 E x' y z2 B L' d' f r2";
+        let sequence = parse_sequence(scramble).unwrap();
+
+        let mut packed = PackedState::solved();
+        packed.apply_sequence(&sequence);
+        assert_eq!(packed.to_state(), cube.scramble_to_state(scramble).unwrap());
+
+        let mut state = State::solved();
+        for mv in &sequence {
+            state = apply_notation_move(&state, mv);
+        }
+        assert_eq!(state, cube.scramble_to_state(scramble).unwrap());
+    }
+
+    #[test]
+    fn test_packed_operations_match_inspection() {
+        let cube = RubiksCube::new();
+        let state = cube.scramble_to_state("R U2 F' D L B2").unwrap();
+
+        for orientation in 0..3 {
+            let mut packed = PackedState::from(&state);
+            packed.swap_corners(2, 5, orientation);
+            let expected = CornerSwapOperation::new(2, 5, orientation).apply(&state);
+            assert_eq!(packed.to_state(), expected);
+
+            let mut packed = PackedState::from(&state);
+            packed.twist_corner(4, orientation);
+            let expected = CornerTwistOperation::new(4, orientation).apply(&state);
+            assert_eq!(packed.to_state(), expected);
+        }
+
+        for orientation in 0..2 {
+            let mut packed = PackedState::from(&state);
+            packed.swap_edges(6, 9, orientation);
+            let expected = EdgeSwapOperation::new(6, 9, orientation).apply(&state);
+            assert_eq!(packed.to_state(), expected);
+        }
+
+        let mut packed = PackedState::from(&state);
+        packed.flip_edge(3);
+        assert_eq!(packed.to_state(), EdgeFlipOperation::new(3).apply(&state));
+    }
+}
@@ -1,6 +1,9 @@
+use super::move_table::notation_move_state;
 use super::state::State;
-use crate::parser::move_parser::NotationMove;
+use crate::parser::move_parser::{parse_sequence, NotationMove};
+use crate::parser::ParseError;
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
 pub struct RubiksCube {
     moves: HashMap<String, State>,
@@ -11,6 +14,7 @@ impl RubiksCube {
         let mut moves = HashMap::new();
 
         // 6つの基本操作とその2層回し（90度時計回り）を定義
+        // スライスと2層回しはセンターも動かす
         moves.insert(
             "U".to_string(),
             State::new(
@@ -23,11 +27,12 @@ impl RubiksCube {
 
         moves.insert(
             "u".to_string(),
-            State::new(
+            State::with_centers(
                 [3, 0, 1, 2, 4, 5, 6, 7],
                 [0, 0, 0, 0, 0, 0, 0, 0],
                 [3, 0, 1, 2, 7, 4, 5, 6, 8, 9, 10, 11],
                 [1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 1, 4, 5, 3, 2],
             ),
         );
 
@@ -43,11 +48,12 @@ impl RubiksCube {
 
         moves.insert(
             "d".to_string(),
-            State::new(
+            State::with_centers(
                 [0, 1, 2, 3, 5, 6, 7, 4],
                 [0, 0, 0, 0, 0, 0, 0, 0],
                 [1, 2, 3, 0, 4, 5, 6, 7, 9, 10, 11, 8],
                 [1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 1, 5, 4, 2, 3],
             ),
         );
 
@@ -63,11 +69,12 @@ impl RubiksCube {
 
         moves.insert(
             "l".to_string(),
-            State::new(
+            State::with_centers(
                 [4, 1, 2, 0, 7, 5, 6, 3],
                 [2, 0, 0, 1, 1, 0, 0, 2],
                 [11, 1, 2, 7, 8, 5, 4, 0, 10, 9, 6, 3],
                 [0, 0, 0, 0, 1, 0, 1, 0, 1, 0, 1, 0],
+                [5, 4, 2, 3, 0, 1],
             ),
         );
 
@@ -83,11 +90,12 @@ impl RubiksCube {
 
         moves.insert(
             "r".to_string(),
-            State::new(
+            State::with_centers(
                 [0, 2, 6, 3, 4, 1, 5, 7],
                 [0, 1, 2, 0, 0, 2, 1, 0],
                 [0, 5, 9, 3, 6, 2, 10, 7, 4, 1, 8, 11],
                 [0, 0, 0, 0, 1, 0, 1, 0, 1, 0, 1, 0],
+                [4, 5, 2, 3, 1, 0],
             ),
         );
 
@@ -103,11 +111,12 @@ impl RubiksCube {
 
         moves.insert(
             "f".to_string(),
-            State::new(
+            State::with_centers(
                 [0, 1, 3, 7, 4, 5, 2, 6],
                 [0, 0, 1, 2, 0, 0, 2, 1],
                 [0, 1, 6, 10, 4, 7, 3, 11, 8, 5, 2, 9],
                 [0, 0, 1, 1, 0, 1, 1, 1, 0, 1, 1, 1],
+                [2, 3, 1, 0, 4, 5],
             ),
         );
 
@@ -123,41 +132,45 @@ impl RubiksCube {
 
         moves.insert(
             "b".to_string(),
-            State::new(
+            State::with_centers(
                 [1, 5, 2, 3, 0, 4, 6, 7],
                 [1, 2, 0, 0, 2, 1, 0, 0],
                 [4, 8, 2, 3, 1, 9, 6, 5, 0, 11, 10, 7],
                 [1, 1, 0, 0, 1, 1, 0, 1, 1, 1, 0, 1],
+                [3, 2, 0, 1, 4, 5],
             ),
         );
 
         moves.insert(
             "M".to_string(),
-            State::new(
+            State::with_centers(
                 [0, 1, 2, 3, 4, 5, 6, 7],
                 [0, 0, 0, 0, 0, 0, 0, 0],
                 [0, 1, 2, 3, 8, 5, 4, 7, 10, 9, 6, 11],
                 [0, 0, 0, 0, 1, 0, 1, 0, 1, 0, 1, 0],
+                [5, 4, 2, 3, 0, 1],
             ),
         );
 
         moves.insert(
             "S".to_string(),
-            State::new(
+            State::with_centers(
                 [0, 1, 2, 3, 4, 5, 6, 7],
                 [0, 0, 0, 0, 0, 0, 0, 0],
-                [0, 1, 2, 9, 4, 7, 6, 3, 8, 5, 10, 11],
-                [0, 0, 0, 1, 0, 1, 0, 1, 0, 1, 0, 0],
+                [0, 1, 2, 3, 4, 7, 6, 11, 8, 5, 10, 9],
+                [0, 0, 0, 0, 0, 1, 0, 1, 0, 1, 0, 1],
+                [2, 3, 1, 0, 4, 5],
             ),
         );
 
         moves.insert(
             "E".to_string(),
-            State::new(
+            State::with_centers(
                 [0, 1, 2, 3, 4, 5, 6, 7],
                 [0, 0, 0, 0, 0, 0, 0, 0],
                 [1, 2, 3, 0, 4, 5, 6, 7, 8, 9, 10, 11],
                 [1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 1, 5, 4, 2, 3],
             ),
         );
 
@@ -179,21 +192,56 @@ impl RubiksCube {
             cube.moves.insert(format!("{}'", face), counter_move);
         }
 
+        // 持ち替え（x, y, z）を定義
+        // x = r L', y = u D', z = f B'
+        let rotations = [("x", "r", "L'"), ("y", "u", "D'"), ("z", "f", "B'")];
+        for (rotation, wide, opposite) in &rotations {
+            let base_move = cube.moves[*wide].apply_move(&cube.moves[*opposite]);
+
+            let double_move = base_move.apply_move(&base_move);
+            let counter_move = double_move.apply_move(&base_move);
+
+            cube.moves.insert(rotation.to_string(), base_move);
+            cube.moves.insert(format!("{}2", rotation), double_move);
+            cube.moves.insert(format!("{}'", rotation), counter_move);
+        }
+
         cube
     }
 
-    pub fn scramble_to_state(&self, scramble: &str) -> State {
-        let mut state = State::solved();
-
-        for move_name in scramble.split_whitespace() {
-            if let Some(move_state) = self.moves.get(move_name) {
-                state = state.apply_move(move_state);
-            } else {
-                eprintln!("警告: 未知の操作 '{}'", move_name);
+    /// 持ち替えのみで到達できる24通りの状態を返す
+    ///
+    /// 完成状態をこれらのいずれかに一致させられる場合、キューブは
+    /// センターに対して揃っているとみなせる
+    pub fn whole_cube_rotations() -> &'static [State] {
+        static ROTATIONS: OnceLock<Vec<State>> = OnceLock::new();
+
+        ROTATIONS.get_or_init(|| {
+            let cube = RubiksCube::new();
+            let mut rotations = vec![State::solved()];
+            let mut i = 0;
+
+            // x と y の組み合わせで全ての向きを列挙
+            while i < rotations.len() {
+                for name in ["x", "y"] {
+                    let next = rotations[i].apply_move(&cube.moves[name]);
+                    if !rotations.contains(&next) {
+                        rotations.push(next);
+                    }
+                }
+                i += 1;
             }
-        }
 
-        state
+            rotations
+        })
+    }
+
+    /// スクランブルを適用した状態（WCA / SiGN の表記も受け付け、読めない手はエラー）
+    pub fn scramble_to_state(&self, scramble: &str) -> Result<State, ParseError> {
+        let sequence = parse_sequence(scramble)?;
+        Ok(sequence
+            .iter()
+            .fold(State::solved(), |state, mv| apply_notation_move(&state, mv)))
     }
 
     pub fn get_move_names(&self) -> Vec<String> {
@@ -220,12 +268,7 @@ pub fn apply_notation_move(state: &State, mv: &NotationMove) -> State {
         return state.clone();
     }
 
-    let cube = RubiksCube::new();
-    let move_name = mv.to_string();
-    cube.apply_move(state, &move_name).unwrap_or_else(|| {
-        eprintln!("Warning: Unknown move '{}'", move_name);
-        state.clone()
-    })
+    state.apply_move(notation_move_state(mv))
 }
 
 // test
@@ -243,14 +286,112 @@ mod tests {
             [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         );
 
-        let result_state = cube.scramble_to_state(scramble);
+        let result_state = cube.scramble_to_state(scramble).unwrap();
         assert_eq!(result_state, desired_state);
     }
 
+    #[test]
+    fn test_scramble_accepts_wca_spellings() {
+        let cube = RubiksCube::new();
+
+        assert_eq!(
+            cube.scramble_to_state("F2 D L' B2 U' R2 F Rw Uw'").unwrap(),
+            cube.scramble_to_state("F2 D L' B2 U' R2 F r u'").unwrap()
+        );
+        assert_eq!(
+            cube.scramble_to_state("R2'").unwrap(),
+            cube.scramble_to_state("R2").unwrap()
+        );
+        assert!(cube.scramble_to_state("R Q U").is_err());
+    }
+
+    #[test]
+    fn test_wide_moves_match_face_and_slice() {
+        let cube = RubiksCube::new();
+
+        assert_eq!(
+            cube.scramble_to_state("r").unwrap(),
+            cube.scramble_to_state("R M'").unwrap()
+        );
+        assert_eq!(
+            cube.scramble_to_state("l").unwrap(),
+            cube.scramble_to_state("L M").unwrap()
+        );
+        assert_eq!(
+            cube.scramble_to_state("u").unwrap(),
+            cube.scramble_to_state("U E'").unwrap()
+        );
+        assert_eq!(
+            cube.scramble_to_state("d").unwrap(),
+            cube.scramble_to_state("D E").unwrap()
+        );
+        assert_eq!(
+            cube.scramble_to_state("f").unwrap(),
+            cube.scramble_to_state("F S").unwrap()
+        );
+        assert_eq!(
+            cube.scramble_to_state("b").unwrap(),
+            cube.scramble_to_state("B S'").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rotations() {
+        let cube = RubiksCube::new();
+
+        assert_eq!(
+            cube.scramble_to_state("x").unwrap(),
+            cube.scramble_to_state("R M' L'").unwrap()
+        );
+        assert_eq!(
+            cube.scramble_to_state("y").unwrap(),
+            cube.scramble_to_state("U E' D'").unwrap()
+        );
+        assert_eq!(
+            cube.scramble_to_state("z").unwrap(),
+            cube.scramble_to_state("F S B'").unwrap()
+        );
+        assert_eq!(
+            cube.scramble_to_state("x2").unwrap(),
+            cube.scramble_to_state("x x").unwrap()
+        );
+        assert!(cube.scramble_to_state("x x'").unwrap().is_solved_in_place());
+        assert!(cube
+            .scramble_to_state("y y y y")
+            .unwrap()
+            .is_solved_in_place());
+
+        // x で F のセンターが U に来る
+        let state = cube.scramble_to_state("x").unwrap();
+        assert_eq!(state.centers[0], 4);
+    }
+
+    #[test]
+    fn test_whole_cube_rotations() {
+        let rotations = RubiksCube::whole_cube_rotations();
+        assert_eq!(rotations.len(), 24);
+        assert!(rotations.iter().all(|rotation| rotation.is_solved()));
+    }
+
+    #[test]
+    fn test_is_solved_respects_centers() {
+        let cube = RubiksCube::new();
+
+        // 持ち替えだけなら揃っている
+        assert!(cube.scramble_to_state("x y2 z'").unwrap().is_solved());
+        assert!(cube.scramble_to_state("r L'").unwrap().is_solved());
+
+        // センターだけがずれている状態は揃っていない
+        let offset = cube.scramble_to_state("M' E M E'").unwrap();
+        assert_eq!(offset.cp, State::solved().cp);
+        assert!(!offset.centers_solved());
+        assert!(!offset.is_solved());
+    }
+
     #[test]
     fn test_rubiks_cube_scramble_a() {
         let cube = RubiksCube::new();
         let scramble = "D R U R' D' R U' R' U2 M' U2 M M' U2 M U2 R2 U R' D R U' R' D'";
-        println!("state: {:?}", cube.scramble_to_state(scramble));
+        println!("state: {:?}", cube.scramble_to_state(scramble).unwrap());
     }
 }
@@ -0,0 +1,399 @@
+use super::state::State;
+use super::two_phase::TwoPhaseSolver;
+use super::validation::permutation_is_odd;
+use crate::inspection::{
+    CornerInspection, CornerOperation, EdgeInspection, EdgeOperation, SolverConfig,
+};
+use crate::parser::move_parser::Sequence;
+
+/// 条件を満たす状態を探す試行回数の既定値
+const DEFAULT_MAX_ATTEMPTS: usize = 100_000;
+
+/// Small seeded random number generator (SplitMix64)
+///
+/// The same seed always gives the same sequence, so practice sets can be
+/// reproduced on any platform.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// 0 以上 `n` 未満の一様な乱数
+    pub fn below(&mut self, n: usize) -> usize {
+        // 偏りをなくすため、n の倍数に収まらない値は捨てる
+        let n = n as u64;
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return (value % n) as usize;
+            }
+        }
+    }
+
+    /// Fisher-Yates シャッフル
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// A filter on the BLD memo of a random state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScrambleConstraint {
+    /// コーナーの交換数が偶数（パリティなし）
+    NoParity,
+    /// コーナーの交換数が奇数（パリティあり）
+    Parity,
+    /// コーナーのねじれ（Twist 操作）がちょうど N 個
+    CornerTwists(usize),
+    /// エッジの反転（Flip 操作）がちょうど N 個
+    EdgeFlips(usize),
+    /// コーナーは揃っていて、エッジだけが崩れている
+    EdgesOnly,
+    /// エッジは揃っていて、コーナーだけが崩れている
+    CornersOnly,
+    /// コーナーとエッジを合わせて N 回以上のループ（cycle break）がある
+    MinCycleBreaks(usize),
+    /// コーナーのバッファが最初から揃っている
+    CornerBufferSolved,
+    /// エッジのバッファが最初から揃っている
+    EdgeBufferSolved,
+}
+
+/// Counts taken from the `CornerInspection`/`EdgeInspection` memo of a state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InspectionSummary {
+    pub corner_swaps: usize,
+    pub corner_twists: usize,
+    pub corner_cycle_breaks: usize,
+    pub edge_swaps: usize,
+    pub edge_flips: usize,
+    pub edge_cycle_breaks: usize,
+    /// コーナーの交換数が奇数（エッジは交換分析モードで解く）
+    pub parity: bool,
+    pub corner_buffer_solved: bool,
+    pub edge_buffer_solved: bool,
+}
+
+impl InspectionSummary {
+    /// BldWorkflow と同じ手順で分析し、操作の数を数える
+    pub fn from_state(state: &State) -> Self {
+        Self::from_state_with_config(state, &SolverConfig::default())
+    }
+
+    /// `config` のバッファで分析し、操作の数を数える
+    pub fn from_state_with_config(state: &State, config: &SolverConfig) -> Self {
+        let (corner_buffer, edge_buffer) = (config.corner_buffer, config.edge_buffer);
+        let corner_operations =
+            CornerInspection::solve_corner_permutation_with_config(state, config);
+
+        // 交換の前にバッファに自分自身が入っていれば、その交換は新しいループの開始
+        let mut current = state.clone();
+        let mut corner_swaps = 0;
+        let mut corner_twists = 0;
+        let mut corner_cycle_breaks = 0;
+        for op in &corner_operations {
+            match op {
+                CornerOperation::Swap(_) => {
+                    corner_swaps += 1;
+                    if current.cp[corner_buffer] as usize == corner_buffer {
+                        corner_cycle_breaks += 1;
+                    }
+                }
+                CornerOperation::Twist(_) => corner_twists += 1,
+            }
+            current = op.apply(&current);
+        }
+
+        let parity = corner_swaps % 2 == 1;
+        let edge_operations =
+            EdgeInspection::solve_edge_permutation_with_config(state, parity, config);
+
+        let mut current = state.clone();
+        let mut edge_swaps = 0;
+        let mut edge_flips = 0;
+        let mut edge_cycle_breaks = 0;
+        for op in &edge_operations {
+            match op {
+                EdgeOperation::Swap(_) => {
+                    edge_swaps += 1;
+                    let buffer_value =
+                        config.convert_edge_for_swap_inspection(current.ep[edge_buffer], parity);
+                    if buffer_value as usize == edge_buffer {
+                        edge_cycle_breaks += 1;
+                    }
+                }
+                EdgeOperation::Flip(_) => edge_flips += 1,
+            }
+            current = op.apply(&current);
+        }
+
+        InspectionSummary {
+            corner_swaps,
+            corner_twists,
+            corner_cycle_breaks,
+            edge_swaps,
+            edge_flips,
+            edge_cycle_breaks,
+            parity,
+            corner_buffer_solved: state.cp[corner_buffer] as usize == corner_buffer
+                && state.co[corner_buffer] == 0,
+            edge_buffer_solved: state.ep[edge_buffer] as usize == edge_buffer
+                && state.eo[edge_buffer] == 0,
+        }
+    }
+
+    /// 条件を満たすかどうか
+    pub fn satisfies(&self, constraint: &ScrambleConstraint) -> bool {
+        match constraint {
+            ScrambleConstraint::NoParity => !self.parity,
+            ScrambleConstraint::Parity => self.parity,
+            ScrambleConstraint::CornerTwists(n) => self.corner_twists == *n,
+            ScrambleConstraint::EdgeFlips(n) => self.edge_flips == *n,
+            ScrambleConstraint::EdgesOnly => self.corner_swaps == 0 && self.corner_twists == 0,
+            ScrambleConstraint::CornersOnly => self.edge_swaps == 0 && self.edge_flips == 0,
+            ScrambleConstraint::MinCycleBreaks(n) => {
+                self.corner_cycle_breaks + self.edge_cycle_breaks >= *n
+            }
+            ScrambleConstraint::CornerBufferSolved => self.corner_buffer_solved,
+            ScrambleConstraint::EdgeBufferSolved => self.edge_buffer_solved,
+        }
+    }
+}
+
+/// Seeded generator of random legal states with BLD constraints
+///
+/// States are drawn uniformly from all legal states (or from the states
+/// with one piece type solved for `EdgesOnly`/`CornersOnly`) and rejected
+/// until every constraint holds.
+pub struct RandomStateGenerator {
+    rng: SeededRng,
+    constraints: Vec<ScrambleConstraint>,
+    max_attempts: usize,
+    config: SolverConfig,
+}
+
+impl RandomStateGenerator {
+    pub fn new(seed: u64) -> Self {
+        RandomStateGenerator {
+            rng: SeededRng::new(seed),
+            constraints: Vec::new(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            config: SolverConfig::default(),
+        }
+    }
+
+    /// Add a constraint; all constraints must hold
+    pub fn with_constraint(mut self, constraint: ScrambleConstraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Give up after this many rejected states
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Check the constraints against these buffers instead of UFR/UF
+    pub fn with_config(mut self, config: SolverConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Draw the next random state that satisfies every constraint
+    pub fn generate(&mut self) -> Result<State, String> {
+        let scramble_corners = !self.constraints.contains(&ScrambleConstraint::EdgesOnly);
+        let scramble_edges = !self.constraints.contains(&ScrambleConstraint::CornersOnly);
+
+        for _ in 0..self.max_attempts {
+            let state = self.random_state(scramble_corners, scramble_edges);
+            let summary = InspectionSummary::from_state_with_config(&state, &self.config);
+            if self.constraints.iter().all(|c| summary.satisfies(c)) {
+                return Ok(state);
+            }
+        }
+
+        Err(format!(
+            "No state satisfying {:?} found in {} attempts",
+            self.constraints, self.max_attempts
+        ))
+    }
+
+    /// Draw the next state and a scramble that produces it from solved
+    pub fn generate_scramble(&mut self) -> Result<(State, Sequence), String> {
+        let state = self.generate()?;
+        let scramble = TwoPhaseSolver::new().scramble(&state)?;
+        Ok((state, scramble))
+    }
+
+    /// 一様な乱数で合法な State を作る
+    fn random_state(&mut self, scramble_corners: bool, scramble_edges: bool) -> State {
+        let mut state = State::solved();
+
+        if scramble_corners {
+            self.rng.shuffle(&mut state.cp);
+            for i in 0..7 {
+                state.co[i] = self.rng.below(3) as u8;
+            }
+            let twist: u8 = state.co[..7].iter().sum();
+            state.co[7] = (3 - twist % 3) % 3;
+        }
+
+        if scramble_edges {
+            self.rng.shuffle(&mut state.ep);
+            for i in 0..11 {
+                state.eo[i] = self.rng.below(2) as u8;
+            }
+            state.eo[11] = state.eo[..11].iter().sum::<u8>() % 2;
+        }
+
+        // コーナーとエッジの置換の偶奇を合わせる
+        if permutation_is_odd(&state.cp) != permutation_is_odd(&state.ep) {
+            if scramble_edges {
+                state.ep.swap(0, 1);
+            } else {
+                state.cp.swap(0, 1);
+            }
+        }
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::RubiksCube;
+
+    #[test]
+    fn test_same_seed_same_states() {
+        let mut a = RandomStateGenerator::new(42);
+        let mut b = RandomStateGenerator::new(42);
+        let mut c = RandomStateGenerator::new(43);
+
+        let first = a.generate().unwrap();
+        assert_eq!(first, b.generate().unwrap());
+        assert_ne!(first, c.generate().unwrap());
+        assert_eq!(a.generate().unwrap(), b.generate().unwrap());
+    }
+
+    #[test]
+    fn test_random_states_are_legal() {
+        let mut generator = RandomStateGenerator::new(1);
+        for _ in 0..50 {
+            let state = generator.generate().unwrap();
+            assert!(state.validate().is_valid(), "{}", state);
+        }
+    }
+
+    #[test]
+    fn test_constraints() {
+        let constraints = [
+            ScrambleConstraint::NoParity,
+            ScrambleConstraint::Parity,
+            ScrambleConstraint::CornerTwists(2),
+            ScrambleConstraint::EdgeFlips(0),
+            ScrambleConstraint::EdgesOnly,
+            ScrambleConstraint::CornersOnly,
+            ScrambleConstraint::MinCycleBreaks(2),
+            ScrambleConstraint::CornerBufferSolved,
+            ScrambleConstraint::EdgeBufferSolved,
+        ];
+
+        for constraint in constraints {
+            let mut generator = RandomStateGenerator::new(7).with_constraint(constraint.clone());
+            for _ in 0..5 {
+                let state = generator.generate().unwrap();
+                assert!(state.validate().is_valid());
+                assert!(
+                    InspectionSummary::from_state(&state).satisfies(&constraint),
+                    "{:?}",
+                    constraint
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_constraints_use_config_buffers() {
+        // UBL / DF バッファ
+        let config = SolverConfig::new(0, 10, [10, 8]).unwrap();
+        let mut generator = RandomStateGenerator::new(11)
+            .with_config(config.clone())
+            .with_constraint(ScrambleConstraint::CornerBufferSolved)
+            .with_constraint(ScrambleConstraint::EdgeBufferSolved);
+        for _ in 0..5 {
+            let state = generator.generate().unwrap();
+            assert_eq!((state.cp[0], state.co[0]), (0, 0));
+            assert_eq!((state.ep[10], state.eo[10]), (10, 0));
+            let summary = InspectionSummary::from_state_with_config(&state, &config);
+            assert!(summary.corner_buffer_solved && summary.edge_buffer_solved);
+        }
+    }
+
+    #[test]
+    fn test_edges_only_keeps_corners_solved() {
+        let mut generator = RandomStateGenerator::new(3)
+            .with_constraint(ScrambleConstraint::EdgesOnly)
+            .with_constraint(ScrambleConstraint::NoParity);
+        let state = generator.generate().unwrap();
+
+        assert_eq!(state.cp, State::solved().cp);
+        assert_eq!(state.co, State::solved().co);
+    }
+
+    #[test]
+    fn test_impossible_constraints() {
+        let mut generator = RandomStateGenerator::new(0)
+            .with_constraint(ScrambleConstraint::EdgesOnly)
+            .with_constraint(ScrambleConstraint::Parity)
+            .with_max_attempts(100);
+        assert!(generator.generate().is_err());
+    }
+
+    #[test]
+    fn test_summary_counts() {
+        let cube = RubiksCube::new();
+        // T パーム: UFR と UBR の交換 + UL と UR の交換
+        let state = cube
+            .scramble_to_state("R U R' U' R' F R2 U' R' U' R U R' F'")
+            .unwrap();
+        let summary = InspectionSummary::from_state(&state);
+
+        assert!(summary.parity);
+        assert_eq!(summary.corner_swaps, 1);
+        assert_eq!(summary.corner_twists, 0);
+        assert!(!summary.corner_buffer_solved);
+    }
+
+    #[test]
+    fn test_generate_scramble() {
+        let cube = RubiksCube::new();
+        let mut generator = RandomStateGenerator::new(2024);
+        let (state, scramble) = generator.generate_scramble().unwrap();
+
+        let scramble = scramble
+            .iter()
+            .map(|m| m.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(cube.scramble_to_state(&scramble).unwrap(), state);
+    }
+}
@@ -1,174 +1,468 @@
-use super::operations::RubiksCube;
+use super::move_table::notation_move_state;
 use super::state::{PartialStatePattern, State};
+use crate::parser::move_parser::{NotationMove, Sequence};
+use crate::parser::{decompose, kind_move};
 use std::collections::VecDeque;
 
-/// Represents a single move on the Rubik's cube
+/// 1 つの枝刈り表が追跡するピースの最大数
+const MAX_TRACKED_PIECES: usize = 4;
+
+/// 枝刈り表の未到達マーク
+const UNVISITED: u8 = u8::MAX;
+
+/// The moves a `SolutionSearcher` may use
 #[derive(Debug, Clone, PartialEq)]
-pub enum Move {
-    U,
-    D,
-    R,
-    L,
-    F,
-    B, // Clockwise moves
-    UPrime,
-    DPrime,
-    RPrime,
-    LPrime,
-    FPrime,
-    BPrime, // Counter-clockwise moves
+pub enum MoveSet {
+    /// 18 face turns (half turn metric)
+    Htm,
+    /// 12 face quarter turns
+    QuarterTurns,
+    /// <R, U, M>
+    Rum,
+    /// <R, U, D>
+    Rud,
+    /// 18 face turns and 18 wide turns
+    Wide,
+    /// Any list of moves, searched in the given order
+    Custom(Vec<NotationMove>),
 }
 
-impl Move {
-    /// Get all possible moves
-    pub fn all_moves() -> Vec<Move> {
-        vec![
-            Move::U,
-            Move::D,
-            Move::R,
-            Move::L,
-            Move::F,
-            Move::B,
-            Move::UPrime,
-            Move::DPrime,
-            Move::RPrime,
-            Move::LPrime,
-            Move::FPrime,
-            Move::BPrime,
-        ]
-    }
-
-    /// Convert move to string notation
-    pub fn to_string(&self) -> String {
+impl MoveSet {
+    /// 探索に使う手の一覧
+    ///
+    /// `Htm` の順番（90 度, 逆回転, 180 度の順に U D R L F B）は
+    /// `TwoPhaseSolver` の手の番号として使われる
+    pub fn moves(&self) -> Sequence {
         match self {
-            Move::U => "U".to_string(),
-            Move::D => "D".to_string(),
-            Move::R => "R".to_string(),
-            Move::L => "L".to_string(),
-            Move::F => "F".to_string(),
-            Move::B => "B".to_string(),
-            Move::UPrime => "U'".to_string(),
-            Move::DPrime => "D'".to_string(),
-            Move::RPrime => "R'".to_string(),
-            Move::LPrime => "L'".to_string(),
-            Move::FPrime => "F'".to_string(),
-            Move::BPrime => "B'".to_string(),
-        }
-    }
-
-    /// Get the move state for applying this move using RubiksCube
-    pub fn apply_to_state(&self, state: &State, cube: &RubiksCube) -> Option<State> {
-        let move_name = self.to_string();
-        cube.apply_move(state, &move_name)
+            MoveSet::Htm => {
+                let mut moves = MoveSet::QuarterTurns.moves();
+                moves.extend([
+                    NotationMove::U2,
+                    NotationMove::D2,
+                    NotationMove::R2,
+                    NotationMove::L2,
+                    NotationMove::F2,
+                    NotationMove::B2,
+                ]);
+                moves
+            }
+            MoveSet::QuarterTurns => vec![
+                NotationMove::U,
+                NotationMove::D,
+                NotationMove::R,
+                NotationMove::L,
+                NotationMove::F,
+                NotationMove::B,
+                NotationMove::UPrime,
+                NotationMove::DPrime,
+                NotationMove::RPrime,
+                NotationMove::LPrime,
+                NotationMove::FPrime,
+                NotationMove::BPrime,
+            ],
+            MoveSet::Rum => with_turns(&[NotationMove::R, NotationMove::U, NotationMove::M]),
+            MoveSet::Rud => with_turns(&[NotationMove::R, NotationMove::U, NotationMove::D]),
+            MoveSet::Wide => {
+                let mut moves = MoveSet::Htm.moves();
+                moves.extend(with_turns(&[
+                    NotationMove::UWide,
+                    NotationMove::DWide,
+                    NotationMove::RWide,
+                    NotationMove::LWide,
+                    NotationMove::FWide,
+                    NotationMove::BWide,
+                ]));
+                moves
+            }
+            MoveSet::Custom(moves) => moves.clone(),
+        }
+    }
+}
+
+/// 各手の 90 度, 逆回転, 180 度を並べる
+fn with_turns(moves: &[NotationMove]) -> Sequence {
+    moves
+        .iter()
+        .flat_map(|m| [m.clone(), m.reversed(), m.doubled()])
+        .collect()
+}
+
+/// 手の回転軸と、軸の中での層の番号
+///
+/// 同じ軸の手は互いに可換なので、探索では層の番号が増える順にだけ並べる
+fn axis_and_layer(mv: &NotationMove) -> Option<(usize, usize)> {
+    use NotationMove::*;
+    match mv {
+        U | U2 | UPrime => Some((0, 0)),
+        E | E2 | EPrime => Some((0, 1)),
+        D | D2 | DPrime => Some((0, 2)),
+        UWide | UWide2 | UWidePrime => Some((0, 3)),
+        DWide | DWide2 | DWidePrime => Some((0, 4)),
+        Y | Y2 | YPrime => Some((0, 5)),
+        R | R2 | RPrime => Some((1, 0)),
+        M | M2 | MPrime => Some((1, 1)),
+        L | L2 | LPrime => Some((1, 2)),
+        RWide | RWide2 | RWidePrime => Some((1, 3)),
+        LWide | LWide2 | LWidePrime => Some((1, 4)),
+        X | X2 | XPrime => Some((1, 5)),
+        F | F2 | FPrime => Some((2, 0)),
+        S | S2 | SPrime => Some((2, 1)),
+        B | B2 | BPrime => Some((2, 2)),
+        FWide | FWide2 | FWidePrime => Some((2, 3)),
+        BWide | BWide2 | BWidePrime => Some((2, 4)),
+        Z | Z2 | ZPrime => Some((2, 5)),
+        Noop => None,
+    }
+}
+
+/// `previous` の直後に `mv` を探索する必要があるか
+///
+/// 同じ軸の可換な手の入れ替えだけの重複と、同じ層の連続のうち `moves` の1手
+/// （または何もしない）で済むものを除く。`moves` に R2 が無ければ R R は残す。
+/// `previous` は `last_turn` で同じ層の連続をまとめた手
+pub(crate) fn follows(
+    mv: &NotationMove,
+    previous: Option<&NotationMove>,
+    moves: &[NotationMove],
+) -> bool {
+    let Some((axis, layer)) = axis_and_layer(mv) else {
+        return false;
+    };
+    let Some(previous) = previous else {
+        return true;
+    };
+    match axis_and_layer(previous) {
+        Some(same) if same == (axis, layer) => {
+            let combined = combined_turn(mv, previous);
+            combined != NotationMove::Noop && !moves.contains(&combined)
+        }
+        Some((previous_axis, previous_layer)) => axis != previous_axis || layer > previous_layer,
+        None => true,
+    }
+}
+
+/// 探索中の手順の最後にある同じ層の連続を1手にまとめたもの
+///
+/// `follows` の `previous` に渡すと、R R R のように2手前以前とまとめれば
+/// `moves` の1手で済む連続も除ける
+pub(crate) fn last_turn<'a>(
+    path: impl DoubleEndedIterator<Item = &'a NotationMove>,
+) -> Option<NotationMove> {
+    let mut path = path.rev();
+    let last = path.next()?;
+    let layer = axis_and_layer(last);
+    Some(
+        path.take_while(|mv| axis_and_layer(mv) == layer)
+            .fold(last.clone(), |turn, mv| combined_turn(&turn, mv)),
+    )
+}
+
+/// 同じ層の2手をまとめた1手（打ち消し合えば Noop）
+fn combined_turn(mv: &NotationMove, previous: &NotationMove) -> NotationMove {
+    match (decompose(mv), decompose(previous)) {
+        (Some((axis, kind, amount)), Some((_, _, previous_amount))) => {
+            kind_move(axis, kind, amount + previous_amount)
+        }
+        _ => NotationMove::Noop,
     }
 }
 
-/// Solution searcher that performs breadth-first search
+/// Solution searcher that performs IDA* search with pruning tables
+///
+/// The goal is a `PartialStatePattern`. For every group of at most four
+/// pieces in the pattern a pruning table holds the exact number of moves
+/// needed to solve that group alone; the largest of these is used as the
+/// admissible heuristic, so the solutions found are optimal in the move set.
+///
+/// When the move set contains slice, wide or rotation moves, the centers
+/// must also end up in their home positions.
 pub struct SolutionSearcher {
     start_state: State,
     desired_pattern: PartialStatePattern,
     max_depth: usize,
-    cube: RubiksCube,
-    solutions_found: Vec<Vec<Move>>,
+    moves: Sequence,
+    max_solutions: Option<usize>,
+    solutions_found: Vec<Sequence>,
+    /// センターを動かす手を含むとき、センターの完成も条件にする
+    check_centers: bool,
+}
+
+/// ピースの部分集合の位置と向きだけを見た距離表
+struct PruningTable {
+    /// 追跡するピース（元の位置の番号）
+    pieces: Vec<usize>,
+    /// 位置の数（コーナー 8, エッジ 12）
+    slots: usize,
+    /// 向きの数（コーナー 3, エッジ 2）
+    orientations: usize,
+    /// 完成までの最短手数
+    distances: Vec<u8>,
 }
 
-/// Represents a search node containing state, move sequence, and depth
-#[derive(Debug, Clone)]
-struct SearchNode {
-    state: State,
-    moves: Vec<Move>,
-    depth: usize,
+impl PruningTable {
+    /// 完成状態から幅優先探索で距離表を作る
+    ///
+    /// `destination[m][p]` は手 m で位置 p のピースが移る先、
+    /// `twist[m][q]` はその時に加わる向き
+    fn build(
+        pieces: Vec<usize>,
+        slots: usize,
+        orientations: usize,
+        destination: &[Vec<usize>],
+        twist: &[Vec<u8>],
+    ) -> Self {
+        let base = slots * orientations;
+        let size = base.pow(pieces.len() as u32);
+        let mut table = PruningTable {
+            pieces,
+            slots,
+            orientations,
+            distances: vec![UNVISITED; size],
+        };
+
+        let solved: Vec<(usize, u8)> = table.pieces.iter().map(|&p| (p, 0)).collect();
+        let mut queue = VecDeque::new();
+        let start = table.index(&solved);
+        table.distances[start] = 0;
+        queue.push_back(solved);
+
+        while let Some(current) = queue.pop_front() {
+            let distance = table.distances[table.index(&current)];
+            for (dest, tw) in destination.iter().zip(twist) {
+                let next: Vec<(usize, u8)> = current
+                    .iter()
+                    .map(|&(position, ori)| {
+                        let q = dest[position];
+                        (q, (ori + tw[q]) % table.orientations as u8)
+                    })
+                    .collect();
+                let index = table.index(&next);
+                if table.distances[index] == UNVISITED {
+                    table.distances[index] = distance + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        table
+    }
+
+    fn index(&self, pieces: &[(usize, u8)]) -> usize {
+        pieces.iter().fold(0, |acc, &(position, ori)| {
+            acc * self.slots * self.orientations + position * self.orientations + ori as usize
+        })
+    }
+
+    /// State から追跡ピースの距離を引く
+    fn lookup(&self, permutation: &[u8], orientation: &[u8]) -> u8 {
+        let mut current = [(0usize, 0u8); MAX_TRACKED_PIECES];
+        for (k, &piece) in self.pieces.iter().enumerate() {
+            let position = permutation
+                .iter()
+                .position(|&p| p as usize == piece)
+                .unwrap();
+            current[k] = (position, orientation[position]);
+        }
+        self.distances[self.index(&current[..self.pieces.len()])]
+    }
 }
 
 impl SolutionSearcher {
-    /// Create a new solution searcher
+    /// Create a new solution searcher using all face turns including half turns
     pub fn new(start_state: State, desired_pattern: PartialStatePattern, max_depth: usize) -> Self {
         SolutionSearcher {
             start_state,
             desired_pattern,
             max_depth,
-            cube: RubiksCube::new(),
+            moves: MoveSet::Htm.moves(),
+            max_solutions: None,
             solutions_found: Vec::new(),
+            check_centers: false,
         }
     }
 
     /// Create searcher with the fixed bottom layer pattern from the docs
+    ///
+    /// The D layer cross needs at most 8 moves.
     pub fn with_bottom_layer_pattern(start_state: State) -> Self {
         let desired_pattern = PartialStatePattern::new(
             [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1], // Bottom layer edges
             [0, 0, 0, 0, 0, 0, 0, 0],             // No corners required
         );
-        SolutionSearcher::new(start_state, desired_pattern, 3)
+        SolutionSearcher::new(start_state, desired_pattern, 8)
     }
 
-    /// Search for a solution using breadth-first search
-    pub fn search(&mut self) -> Option<Vec<Vec<Move>>> {
-        let mut queue = VecDeque::new();
-        let mut visited = std::collections::HashSet::new();
+    /// Search with one of the predefined move sets (e.g. `MoveSet::Rum`)
+    pub fn with_move_set(self, move_set: MoveSet) -> Self {
+        self.with_moves(move_set.moves())
+    }
 
-        // Start with the initial state
-        let start_node = SearchNode {
-            state: self.start_state.clone(),
-            moves: Vec::new(),
-            depth: 0,
-        };
+    /// Search with an explicit list of moves
+    pub fn with_moves(mut self, moves: Sequence) -> Self {
+        self.moves = moves;
+        self
+    }
+
+    /// Stop after this many optimal solutions have been found
+    pub fn with_max_solutions(mut self, max_solutions: usize) -> Self {
+        self.max_solutions = Some(max_solutions);
+        self
+    }
+
+    /// Search for optimal solutions using IDA*
+    ///
+    /// Returns every solution of the shortest length (up to `max_solutions`),
+    /// or `None` if there is none within `max_depth`.
+    pub fn search(&mut self) -> Option<Vec<Sequence>> {
+        self.solutions_found.clear();
+
+        let move_states: Vec<State> = self
+            .moves
+            .iter()
+            .map(|m| notation_move_state(m).clone())
+            .collect();
+        self.check_centers = move_states.iter().any(|m| !m.centers_solved());
+
+        if self.is_goal(&self.start_state) {
+            return Some(vec![Vec::new()]); // Already solved
+        }
+
+        let tables = self.build_pruning_tables(&move_states);
+
+        let start = self.start_state.clone();
+        let mut bound = Self::heuristic(&tables, &start) as usize;
+        let mut path = Vec::new();
+
+        while bound <= self.max_depth {
+            self.search_depth(&start, 0, bound, &move_states, &tables, &mut path);
+            if !self.solutions_found.is_empty() {
+                return Some(self.solutions_found.clone());
+            }
+            bound += 1;
+        }
 
-        // Check if start state already matches
+        None // No solution found within max depth
+    }
+
+    /// 深さ `bound` までの反復深化の 1 回分
+    fn search_depth(
+        &mut self,
+        state: &State,
+        depth: usize,
+        bound: usize,
+        move_states: &[State],
+        tables: &[PruningTable],
+        path: &mut Vec<usize>,
+    ) {
         if self
-            .start_state
-            .matches_partial_pattern(&self.desired_pattern)
+            .max_solutions
+            .is_some_and(|max| self.solutions_found.len() >= max)
         {
-            return Some(Vec::new()); // Already solved
+            return;
         }
 
-        queue.push_back(start_node);
-        visited.insert(self.start_state.clone());
+        if depth == bound {
+            if self.is_goal(state) {
+                self.solutions_found
+                    .push(path.iter().map(|&i| self.moves[i].clone()).collect());
+            }
+            return;
+        }
 
-        while let Some(current) = queue.pop_front() {
-            // Don't search beyond max depth
-            if current.depth >= self.max_depth {
+        let previous = last_turn(path.iter().map(|&j| &self.moves[j]));
+        for i in 0..self.moves.len() {
+            if !follows(&self.moves[i], previous.as_ref(), &self.moves) {
                 continue;
             }
 
-            // Try all possible moves
-            for move_option in Move::all_moves() {
-                if let Some(new_state) = move_option.apply_to_state(&current.state, &self.cube) {
-                    // Skip if we've seen this state before
-                    if visited.contains(&new_state) {
-                        continue;
-                    }
-
-                    visited.insert(new_state.clone());
-
-                    let mut new_moves = current.moves.clone();
-                    new_moves.push(move_option);
-
-                    // Check if this state matches our desired pattern
-                    if new_state.matches_partial_pattern(&self.desired_pattern) {
-                        self.solutions_found.push(new_moves);
-                        continue;
-                    }
-
-                    // Add to queue for further exploration
-                    let new_node = SearchNode {
-                        state: new_state,
-                        moves: new_moves,
-                        depth: current.depth + 1,
-                    };
-                    queue.push_back(new_node);
-                }
+            let next = state.apply_move(&move_states[i]);
+            if depth + 1 + Self::heuristic(tables, &next) as usize > bound {
+                continue;
             }
+
+            path.push(i);
+            self.search_depth(&next, depth + 1, bound, move_states, tables, path);
+            path.pop();
         }
+    }
+
+    fn is_goal(&self, state: &State) -> bool {
+        state.matches_partial_pattern(&self.desired_pattern)
+            && (!self.check_centers || state.centers_solved())
+    }
+
+    /// 全ての枝刈り表の最大値（許容的な下界）
+    fn heuristic(tables: &[PruningTable], state: &State) -> u8 {
+        tables
+            .iter()
+            .map(|table| {
+                if table.slots == 8 {
+                    table.lookup(&state.cp, &state.co)
+                } else {
+                    table.lookup(&state.ep, &state.eo)
+                }
+            })
+            .max()
+            .unwrap_or(0)
+    }
 
-        // Return all of solution found, if any
-        if !self.solutions_found.is_empty() {
-            return Some(self.solutions_found.clone());
+    /// パターンで指定されたピースを 4 個ずつに分けて枝刈り表を作る
+    fn build_pruning_tables(&self, move_states: &[State]) -> Vec<PruningTable> {
+        // 手 m で位置 p のピースが移る先 q（move.cp[q] == p）と向きの変化
+        let corner_destination: Vec<Vec<usize>> = move_states
+            .iter()
+            .map(|m| {
+                let mut dest = vec![0; 8];
+                for (q, &p) in m.cp.iter().enumerate() {
+                    dest[p as usize] = q;
+                }
+                dest
+            })
+            .collect();
+        let corner_twist: Vec<Vec<u8>> = move_states.iter().map(|m| m.co.to_vec()).collect();
+        let edge_destination: Vec<Vec<usize>> = move_states
+            .iter()
+            .map(|m| {
+                let mut dest = vec![0; 12];
+                for (q, &p) in m.ep.iter().enumerate() {
+                    dest[p as usize] = q;
+                }
+                dest
+            })
+            .collect();
+        let edge_twist: Vec<Vec<u8>> = move_states.iter().map(|m| m.eo.to_vec()).collect();
+
+        let corners: Vec<usize> = (0..8)
+            .filter(|&i| self.desired_pattern.desired_corner[i] == 1)
+            .collect();
+        let edges: Vec<usize> = (0..12)
+            .filter(|&i| self.desired_pattern.desired_edge[i] == 1)
+            .collect();
+
+        let mut tables = Vec::new();
+        for group in corners.chunks(MAX_TRACKED_PIECES) {
+            tables.push(PruningTable::build(
+                group.to_vec(),
+                8,
+                3,
+                &corner_destination,
+                &corner_twist,
+            ));
         }
-        None // No solution found within max depth
+        for group in edges.chunks(MAX_TRACKED_PIECES) {
+            tables.push(PruningTable::build(
+                group.to_vec(),
+                12,
+                2,
+                &edge_destination,
+                &edge_twist,
+            ));
+        }
+        tables
     }
 
     /// Format solution as a string
-    pub fn format_solution(moves: &[Move]) -> String {
+    pub fn format_solution(moves: &[NotationMove]) -> String {
         if moves.is_empty() {
             "Already solved!".to_string()
         } else {
@@ -184,30 +478,104 @@ impl SolutionSearcher {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cube::operations::{apply_notation_move, RubiksCube};
+    use crate::parser::{parse_sequence, sequence_to_string};
 
     #[test]
-    fn test_move_to_string() {
-        assert_eq!(Move::U.to_string(), "U");
-        assert_eq!(Move::UPrime.to_string(), "U'");
-        assert_eq!(Move::R.to_string(), "R");
-        assert_eq!(Move::RPrime.to_string(), "R'");
+    fn test_move_set_counts() {
+        assert_eq!(MoveSet::Htm.moves().len(), 18);
+        assert_eq!(MoveSet::QuarterTurns.moves().len(), 12);
+        assert_eq!(MoveSet::Wide.moves().len(), 36);
+        assert_eq!(
+            sequence_to_string(&MoveSet::Rum.moves()),
+            "R R' R2 U U' U2 M M' M2"
+        );
+        assert_eq!(
+            sequence_to_string(&MoveSet::Rud.moves()),
+            "R R' R2 U U' U2 D D' D2"
+        );
     }
 
     #[test]
-    fn test_all_moves_count() {
-        assert_eq!(Move::all_moves().len(), 12);
+    fn test_follows() {
+        let htm = MoveSet::Htm.moves();
+        assert!(follows(&NotationMove::R, None, &htm));
+        assert!(!follows(&NotationMove::R2, Some(&NotationMove::R), &htm));
+        assert!(!follows(&NotationMove::R, Some(&NotationMove::R), &htm));
+        // 同じ軸の可換な手は層の順にだけ並べる
+        assert!(follows(&NotationMove::M, Some(&NotationMove::R), &htm));
+        assert!(!follows(&NotationMove::R, Some(&NotationMove::M), &htm));
+        assert!(follows(&NotationMove::U, Some(&NotationMove::M), &htm));
+        assert!(!follows(&NotationMove::Noop, None, &htm));
+
+        // R2 が無ければ R R は1手にまとめられない
+        let quarter_turns = MoveSet::QuarterTurns.moves();
+        assert!(follows(
+            &NotationMove::R,
+            Some(&NotationMove::R),
+            &quarter_turns
+        ));
+        assert!(!follows(
+            &NotationMove::RPrime,
+            Some(&NotationMove::R),
+            &quarter_turns
+        ));
+    }
+
+    #[test]
+    fn test_follows_same_layer_run() {
+        // R R R は R'、R R R R は何もしないので、QuarterTurns でも探索しない
+        let quarter_turns = MoveSet::QuarterTurns.moves();
+        let path = [NotationMove::U, NotationMove::R, NotationMove::R];
+        let previous = last_turn(path.iter());
+        assert_eq!(previous, Some(NotationMove::R2));
+        assert!(!follows(
+            &NotationMove::R,
+            previous.as_ref(),
+            &quarter_turns
+        ));
+        assert!(!follows(
+            &NotationMove::RPrime,
+            previous.as_ref(),
+            &quarter_turns
+        ));
+        assert!(follows(&NotationMove::L, previous.as_ref(), &quarter_turns));
+
+        let rum = MoveSet::Rum.moves();
+        assert_eq!(last_turn([].iter()), None);
+        assert_eq!(
+            last_turn([NotationMove::R2, NotationMove::R].iter()),
+            Some(NotationMove::RPrime)
+        );
+        assert!(!follows(
+            &NotationMove::R,
+            last_turn([NotationMove::R].iter()).as_ref(),
+            &rum
+        ));
+    }
+
+    #[test]
+    fn test_solution_search_quarter_turns() {
+        let cube = RubiksCube::new();
+        let state = cube.scramble_to_state("R2").unwrap();
+
+        let mut searcher =
+            SolutionSearcher::new(state, PartialStatePattern::new([1; 12], [1; 8]), 4)
+                .with_move_set(MoveSet::QuarterTurns);
+        let solutions = searcher.search().unwrap();
+        assert_eq!(SolutionSearcher::format_solution(&solutions[0]), "R R");
     }
 
     #[test]
     fn test_solution_searcher_creation() {
         let state = State::solved();
         let searcher = SolutionSearcher::with_bottom_layer_pattern(state);
-        assert_eq!(searcher.max_depth, 3);
+        assert_eq!(searcher.max_depth, 8);
     }
 
     #[test]
     fn test_format_solution() {
-        let moves = vec![Move::U, Move::RPrime, Move::F];
+        let moves = vec![NotationMove::U, NotationMove::RPrime, NotationMove::F];
         assert_eq!(SolutionSearcher::format_solution(&moves), "U R' F");
 
         let empty_moves = vec![];
@@ -246,7 +614,152 @@ mod tests {
             );
         }
 
-        // solution の数は7つであること
-        assert_eq!(solutions.len(), 7);
+        // 最短の 2 手の解は F' R' のみ
+        assert_eq!(solutions.len(), 1);
+    }
+
+    fn apply_solution(state: &State, moves: &[NotationMove]) -> State {
+        moves
+            .iter()
+            .fold(state.clone(), |s, m| apply_notation_move(&s, m))
+    }
+
+    #[test]
+    fn test_solution_search_half_turns() {
+        let cube = RubiksCube::new();
+        let state = cube.scramble_to_state("R2 F2").unwrap();
+
+        let mut searcher = SolutionSearcher::with_bottom_layer_pattern(state.clone());
+        let solutions: Vec<String> = searcher
+            .search()
+            .unwrap()
+            .iter()
+            .map(|s| SolutionSearcher::format_solution(s))
+            .collect();
+        assert!(solutions.contains(&"F2 R2".to_string()));
+        assert!(solutions.iter().all(|s| s.split(' ').count() == 2));
+
+        // 90 度回しだけでは 4 手かかる
+        let mut searcher =
+            SolutionSearcher::with_bottom_layer_pattern(state).with_move_set(MoveSet::QuarterTurns);
+        let solutions = searcher.search().unwrap();
+        assert_eq!(solutions[0].len(), 4);
+    }
+
+    #[test]
+    fn test_already_solved() {
+        let mut searcher = SolutionSearcher::with_bottom_layer_pattern(State::solved());
+        assert_eq!(searcher.search(), Some(vec![Vec::new()]));
+    }
+
+    #[test]
+    fn test_cross_search() {
+        let cube = RubiksCube::new();
+        let state = cube
+            .scramble_to_state("D2 F' L B2 R' U2 F D' L2 B U R2 F' D")
+            .unwrap();
+        let pattern = PartialStatePattern::new(
+            [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+        );
+
+        let mut searcher =
+            SolutionSearcher::new(state.clone(), pattern.clone(), 8).with_max_solutions(3);
+        let solutions = searcher
+            .search()
+            .expect("cross is always solvable in 8 moves");
+
+        assert!(!solutions.is_empty() && solutions.len() <= 3);
+        for solution in &solutions {
+            assert!(apply_solution(&state, solution).matches_partial_pattern(&pattern));
+            assert_eq!(solution.len(), solutions[0].len());
+        }
+    }
+
+    #[test]
+    fn test_f2l_pair_search() {
+        let cube = RubiksCube::new();
+        // クロス + DFR/FR のペアが揃った状態からペアだけを崩す
+        let state = cube
+            .scramble_to_state("R U R' U' R U2 R' U R U' R'")
+            .unwrap();
+        let pattern = PartialStatePattern::new(
+            [0, 0, 1, 0, 0, 0, 0, 0, 1, 1, 1, 1],
+            [0, 0, 0, 0, 0, 0, 1, 0],
+        );
+
+        let mut searcher = SolutionSearcher::new(state.clone(), pattern.clone(), 11);
+        let solutions = searcher.search().unwrap();
+
+        assert!(solutions[0].len() <= 11);
+        assert!(apply_solution(&state, &solutions[0]).matches_partial_pattern(&pattern));
+    }
+
+    /// 全てのピースの完成を目標にする
+    fn full_pattern() -> PartialStatePattern {
+        PartialStatePattern::new([1; 12], [1; 8])
+    }
+
+    #[test]
+    fn test_rum_search() {
+        let cube = RubiksCube::new();
+        let state = cube.scramble_to_state("M' U2 M U2").unwrap();
+
+        let mut searcher = SolutionSearcher::new(state.clone(), full_pattern(), 4)
+            .with_move_set(MoveSet::Rum)
+            .with_max_solutions(1);
+        let solution = &searcher.search().unwrap()[0];
+
+        assert_eq!(solution.len(), 4);
+        let solved = apply_solution(&state, solution);
+        assert!(solved.matches_partial_pattern(&full_pattern()) && solved.centers_solved());
+    }
+
+    #[test]
+    fn test_rud_search() {
+        let cube = RubiksCube::new();
+        let state = cube.scramble_to_state("R U D' R'").unwrap();
+
+        let mut searcher =
+            SolutionSearcher::new(state, full_pattern(), 4).with_move_set(MoveSet::Rud);
+        let solutions = searcher.search().unwrap();
+
+        assert_eq!(
+            solutions[0],
+            parse_sequence("R U' D R'").unwrap(),
+            "U' と D は可換なので層の順に並ぶ"
+        );
+    }
+
+    #[test]
+    fn test_wide_search_keeps_centers() {
+        let cube = RubiksCube::new();
+        let state = cube.scramble_to_state("r U r'").unwrap();
+
+        let mut wide =
+            SolutionSearcher::new(state.clone(), full_pattern(), 3).with_move_set(MoveSet::Wide);
+        let solutions = wide.search().unwrap();
+
+        // r U' r' も L F' L' も 3 手で、どちらもセンターまで揃う
+        let solutions: Vec<String> = solutions.iter().map(sequence_to_string).collect();
+        assert!(solutions.contains(&"r U' r'".to_string()));
+        assert!(solutions.contains(&"L F' L'".to_string()));
+        for solution in &solutions {
+            let moves = parse_sequence(solution).unwrap();
+            assert!(apply_solution(&state, &moves).is_solved_in_place());
+        }
+    }
+
+    #[test]
+    fn test_rotation_search() {
+        let cube = RubiksCube::new();
+        let state = cube.scramble_to_state("x y").unwrap();
+
+        let moves = parse_sequence("x x' x2 y y' y2 z z' z2").unwrap();
+        let mut searcher =
+            SolutionSearcher::new(state, full_pattern(), 2).with_move_set(MoveSet::Custom(moves));
+        let solutions = searcher.search().unwrap();
+
+        assert!(solutions.contains(&parse_sequence("y' x'").unwrap()));
     }
 }
@@ -1,11 +1,61 @@
+use super::operations::RubiksCube;
+
+/// Number of center pieces (U, D, L, R, F, B)
+pub const CENTER_COUNT: usize = 6;
+
+/// Corner piece names, indexed like `State::cp`
+pub const CORNER_NAMES: [&str; 8] = ["UBL", "UBR", "UFR", "UFL", "DBL", "DBR", "DFR", "DFL"];
+
+/// Edge piece names, indexed like `State::ep`
+pub const EDGE_NAMES: [&str; 12] = [
+    "BL", "BR", "FR", "FL", "UB", "UR", "UF", "UL", "DB", "DR", "DF", "DL",
+];
+
+/// Corner stickers, indexed as `[corner][orientation]`
+///
+/// Orientation 0 is the U/D sticker. A corner with `co[i] == o` shows
+/// sticker `o` of its piece on the U/D face of position `i`.
+pub const CORNER_STICKERS: [[&str; 3]; 8] = [
+    ["UBL", "BUL", "LUB"], // 0
+    ["UBR", "RUB", "BUR"], // 1
+    ["UFR", "FUR", "RUF"], // 2
+    ["UFL", "LUF", "FUL"], // 3
+    ["DBL", "LDB", "BDL"], // 4
+    ["DBR", "BDR", "RDB"], // 5
+    ["DFR", "RDF", "FDR"], // 6
+    ["DFL", "FDL", "LDF"], // 7
+];
+
+/// Edge stickers, indexed as `[edge][orientation]`
+pub const EDGE_STICKERS: [[&str; 2]; 12] = [
+    ["BL", "LB"], // 0
+    ["BR", "RB"], // 1
+    ["FR", "RF"], // 2
+    ["FL", "LF"], // 3
+    ["UB", "BU"], // 4
+    ["UR", "RU"], // 5
+    ["UF", "FU"], // 6
+    ["UL", "LU"], // 7
+    ["DB", "BD"], // 8
+    ["DR", "RD"], // 9
+    ["DF", "FD"], // 10
+    ["DL", "LD"], // 11
+];
+
+/// Center face names, indexed like `State::centers`
+pub const CENTER_NAMES: [&str; CENTER_COUNT] = ["U", "D", "L", "R", "F", "B"];
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct State {
-    pub cp: [u8; 8],  // Corner Permutation
-    pub co: [u8; 8],  // Corner Orientation
-    pub ep: [u8; 12], // Edge Permutation
-    pub eo: [u8; 12], // Edge Orientation
+    pub cp: [u8; 8],                 // Corner Permutation
+    pub co: [u8; 8],                 // Corner Orientation
+    pub ep: [u8; 12],                // Edge Permutation
+    pub eo: [u8; 12],                // Edge Orientation
+    pub centers: [u8; CENTER_COUNT], // Center Permutation (U, D, L, R, F, B)
 }
 
+const SOLVED_CENTERS: [u8; CENTER_COUNT] = [0, 1, 2, 3, 4, 5];
+
 /// Represents a partial state pattern for comparison
 /// Each array element: 0 = don't care, 1 = must match original position
 #[derive(Debug, Clone, PartialEq)]
@@ -15,8 +65,26 @@ pub struct PartialStatePattern {
 }
 
 impl State {
+    /// Create a state whose centers are in their home positions
     pub fn new(cp: [u8; 8], co: [u8; 8], ep: [u8; 12], eo: [u8; 12]) -> Self {
-        State { cp, co, ep, eo }
+        State::with_centers(cp, co, ep, eo, SOLVED_CENTERS)
+    }
+
+    /// Create a state with an explicit center permutation
+    pub fn with_centers(
+        cp: [u8; 8],
+        co: [u8; 8],
+        ep: [u8; 12],
+        eo: [u8; 12],
+        centers: [u8; CENTER_COUNT],
+    ) -> Self {
+        State {
+            cp,
+            co,
+            ep,
+            eo,
+            centers,
+        }
     }
 
     pub fn solved() -> Self {
@@ -25,6 +93,7 @@ impl State {
             co: [0, 0, 0, 0, 0, 0, 0, 0],
             ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
             eo: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            centers: SOLVED_CENTERS,
         }
     }
 
@@ -34,7 +103,7 @@ impl State {
         let co: [u8; 8] = co.iter().map(|&x| x as u8).collect::<Vec<u8>>().try_into().unwrap();
         let ep: [u8; 12] = ep.iter().map(|&x| x as u8).collect::<Vec<u8>>().try_into().unwrap();
         let eo: [u8; 12] = eo.iter().map(|&x| x as u8).collect::<Vec<u8>>().try_into().unwrap();
-        State { cp, co, ep, eo }
+        State::new(cp, co, ep, eo)
     }
 
     pub fn apply_move(&self, move_state: &State) -> State {
@@ -42,6 +111,7 @@ impl State {
         let mut new_co = [0u8; 8];
         let mut new_ep = [0u8; 12];
         let mut new_eo = [0u8; 12];
+        let mut new_centers = [0u8; CENTER_COUNT];
 
         // Corner permutation と orientation の計算
         for (i, &p) in move_state.cp.iter().enumerate() {
@@ -55,13 +125,113 @@ impl State {
             new_eo[i] = (self.eo[p as usize] + move_state.eo[i]) % 2;
         }
 
-        State::new(new_cp, new_co, new_ep, new_eo)
+        // Center permutation の計算
+        for (i, &p) in move_state.centers.iter().enumerate() {
+            new_centers[i] = self.centers[p as usize];
+        }
+
+        State::with_centers(new_cp, new_co, new_ep, new_eo, new_centers)
+    }
+
+    /// Inverse permutation: `s.apply_move(&s.inverse())` is solved
+    pub fn inverse(&self) -> State {
+        let mut cp = [0u8; 8];
+        let mut co = [0u8; 8];
+        let mut ep = [0u8; 12];
+        let mut eo = [0u8; 12];
+        let mut centers = [0u8; CENTER_COUNT];
+
+        for (i, &p) in self.cp.iter().enumerate() {
+            cp[p as usize] = i as u8;
+            co[p as usize] = (3 - self.co[i]) % 3;
+        }
+        for (i, &p) in self.ep.iter().enumerate() {
+            ep[p as usize] = i as u8;
+            eo[p as usize] = (2 - self.eo[i]) % 2;
+        }
+        for (i, &p) in self.centers.iter().enumerate() {
+            centers[p as usize] = i as u8;
+        }
+
+        State::with_centers(cp, co, ep, eo, centers)
+    }
+
+    /// `self` の後に `other` を行った状態（`apply_move` と同じ）
+    pub fn compose(&self, other: &State) -> State {
+        self.apply_move(other)
+    }
+
+    /// Conjugate `self` by `setup`: setup, then self, then setup undone
+    ///
+    /// This is the state of the conjugate `[setup: self]`.
+    pub fn conjugate(&self, setup: &State) -> State {
+        setup.compose(self).compose(&setup.inverse())
+    }
+
+    /// Hold the cube so that its centers are back in their home positions
+    ///
+    /// Returns `None` if the centers are not a whole-cube rotation.
+    pub fn reoriented(&self) -> Option<State> {
+        RubiksCube::whole_cube_rotations()
+            .iter()
+            .find(|rotation| rotation.centers == self.centers)
+            .map(|rotation| self.compose(&rotation.inverse()))
+    }
+
+    /// Disjoint piece cycles of this state, with their orientation changes
+    pub fn cycles(&self) -> Cycles {
+        let corners = piece_cycles(&self.cp, &self.co, 3);
+        let edges = piece_cycles(&self.ep, &self.eo, 2);
+
+        let mut centers = Vec::new();
+        let mut visited = [false; CENTER_COUNT];
+        for start in 0..CENTER_COUNT {
+            if visited[start] || self.centers[start] as usize == start {
+                continue;
+            }
+            // センターの行き先を順にたどる
+            let mut cycle = Vec::new();
+            let mut position = start;
+            while !visited[position] {
+                visited[position] = true;
+                cycle.push(position);
+                position = self
+                    .centers
+                    .iter()
+                    .position(|&c| c as usize == position)
+                    .unwrap();
+            }
+            centers.push(cycle);
+        }
+
+        Cycles {
+            corners,
+            edges,
+            centers,
+        }
     }
 
+    /// Check if the cube is solved relative to its centers
+    ///
+    /// A cube that has only been turned as a whole (x, y, z) is still solved,
+    /// whereas a cube whose pieces are home but whose centers were moved by
+    /// slice or wide moves is not.
     pub fn is_solved(&self) -> bool {
+        RubiksCube::whole_cube_rotations()
+            .iter()
+            .any(|rotation| rotation == self)
+    }
+
+    /// Check if every piece, including the centers, is in its home position
+    pub fn is_solved_in_place(&self) -> bool {
         *self == State::solved()
     }
 
+    /// Check if the centers are in their home positions
+    pub fn centers_solved(&self) -> bool {
+        self.centers == SOLVED_CENTERS
+    }
+
     /// Check if this state matches the given partial pattern
     /// For edges and corners marked with 1 in the pattern, they must be in their solved position
     pub fn matches_partial_pattern(&self, pattern: &PartialStatePattern) -> bool {
@@ -99,6 +269,132 @@ impl State {
     }
 }
 
+/// One disjoint cycle of corner or edge stickers
+///
+/// `stickers[k]` is `(position, orientation)` of a sticker; the sticker at
+/// `stickers[k]` moves to `stickers[k + 1]`, and the last one moves back to
+/// the first position rotated by `twist`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PieceCycle {
+    pub stickers: Vec<(usize, u8)>,
+    pub twist: u8,
+}
+
+/// Cycle decomposition of a state
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Cycles {
+    pub corners: Vec<PieceCycle>,
+    pub edges: Vec<PieceCycle>,
+    /// Center positions, each moving to the next one
+    pub centers: Vec<Vec<usize>>,
+}
+
+impl Cycles {
+    /// 全てのピースが元の位置・向きにあれば true
+    pub fn is_empty(&self) -> bool {
+        self.corners.is_empty() && self.edges.is_empty() && self.centers.is_empty()
+    }
+}
+
+/// 置換をステッカー単位の巡回に分解する
+///
+/// ピース p が位置 x に向き o で入っているとき、p のステッカー j は
+/// x のステッカー (j - o) mod n の場所にある。
+fn piece_cycles(permutation: &[u8], orientation: &[u8], modulus: u8) -> Vec<PieceCycle> {
+    let mut destination = vec![0usize; permutation.len()];
+    for (position, &piece) in permutation.iter().enumerate() {
+        destination[piece as usize] = position;
+    }
+
+    let mut cycles = Vec::new();
+    let mut visited = vec![false; permutation.len()];
+    for start in 0..permutation.len() {
+        if visited[start] {
+            continue;
+        }
+
+        let mut stickers = Vec::new();
+        let mut position = start;
+        let mut sticker = 0u8;
+        loop {
+            visited[position] = true;
+            stickers.push((position, sticker));
+            let next = destination[position];
+            sticker = (sticker + modulus - orientation[next]) % modulus;
+            position = next;
+            if position == start {
+                break;
+            }
+        }
+
+        // 元の位置・向きのままのピースは出力しない
+        if stickers.len() > 1 || sticker != 0 {
+            cycles.push(PieceCycle {
+                stickers,
+                twist: sticker,
+            });
+        }
+    }
+    cycles
+}
+
+fn format_cycle<const N: usize>(
+    f: &mut std::fmt::Formatter<'_>,
+    cycle: &PieceCycle,
+    names: &[[&str; N]],
+    orientation_names: &[&str],
+) -> std::fmt::Result {
+    let stickers: Vec<&str> = cycle
+        .stickers
+        .iter()
+        .map(|&(position, ori)| names[position][ori as usize])
+        .collect();
+
+    if stickers.len() == 1 {
+        write!(f, "{}", stickers[0])?;
+    } else {
+        write!(f, "({})", stickers.join(" "))?;
+    }
+    if cycle.twist != 0 {
+        write!(f, " {}", orientation_names[cycle.twist as usize])?;
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for Cycles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // twist はステッカーの行き先の向き。U/D ステッカーが向き 2 の面に移る = 時計回り
+        const CORNER_TWISTS: [&str; 3] = ["", "twisted counter-clockwise", "twisted clockwise"];
+        const EDGE_FLIPS: [&str; 2] = ["", "flipped"];
+
+        if self.is_empty() {
+            return write!(f, "solved");
+        }
+
+        let mut first = true;
+        let mut separator = |f: &mut std::fmt::Formatter<'_>| {
+            let result = if first { Ok(()) } else { write!(f, " + ") };
+            first = false;
+            result
+        };
+
+        for cycle in &self.corners {
+            separator(f)?;
+            format_cycle(f, cycle, &CORNER_STICKERS, &CORNER_TWISTS)?;
+        }
+        for cycle in &self.edges {
+            separator(f)?;
+            format_cycle(f, cycle, &EDGE_STICKERS, &EDGE_FLIPS)?;
+        }
+        for cycle in &self.centers {
+            separator(f)?;
+            let names: Vec<&str> = cycle.iter().map(|&c| CENTER_NAMES[c]).collect();
+            write!(f, "({})", names.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
 impl PartialStatePattern {
     /// Create a new partial state pattern
     pub fn new(desired_edge: [u8; 12], desired_corner: [u8; 8]) -> Self {
@@ -140,7 +436,8 @@ impl std::fmt::Display for State {
         writeln!(f, "Corner Permutation: {:?}", self.cp)?;
         writeln!(f, "Corner Orientation: {:?}", self.co)?;
         writeln!(f, "Edge Permutation:   {:?}", self.ep)?;
-        write!(f, "Edge Orientation:   {:?}", self.eo)
+        writeln!(f, "Edge Orientation:   {:?}", self.eo)?;
+        write!(f, "Centers:            {:?}", self.centers)
     }
 }
 
@@ -218,6 +515,110 @@ mod tests {
         assert!(test_state.matches_partial_pattern(&pattern));
     }
 
+    #[test]
+    fn test_center_permutation() {
+        let solved = State::solved();
+        assert!(solved.centers_solved());
+        assert!(solved.is_solved());
+        assert!(solved.is_solved_in_place());
+
+        // ピースは揃っているがセンターが入れ替わっている
+        let swapped_centers = State::with_centers(
+            solved.cp,
+            solved.co,
+            solved.ep,
+            solved.eo,
+            [1, 0, 2, 3, 4, 5],
+        );
+        assert!(!swapped_centers.centers_solved());
+        assert!(!swapped_centers.is_solved());
+
+        // apply_move でセンターも合成される
+        let moved = solved.apply_move(&swapped_centers);
+        assert_eq!(moved.centers, [1, 0, 2, 3, 4, 5]);
+        assert!(moved.apply_move(&swapped_centers).centers_solved());
+    }
+
+    #[test]
+    fn test_inverse_and_compose() {
+        let cube = RubiksCube::new();
+        let alg = cube.scramble_to_state("R U R' U' r x' S2").unwrap();
+
+        assert!(alg.compose(&alg.inverse()).is_solved_in_place());
+        assert!(alg.inverse().compose(&alg).is_solved_in_place());
+        assert_eq!(
+            alg.inverse(),
+            cube.scramble_to_state("S2 x r' U R U' R'").unwrap()
+        );
+        assert_eq!(
+            cube.scramble_to_state("R")
+                .unwrap()
+                .compose(&cube.scramble_to_state("U").unwrap()),
+            cube.scramble_to_state("R U").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_conjugate() {
+        let cube = RubiksCube::new();
+        let setup = cube.scramble_to_state("R D'").unwrap();
+        let interchange = cube.scramble_to_state("U2").unwrap();
+
+        assert_eq!(
+            interchange.conjugate(&setup),
+            cube.scramble_to_state("R D' U2 D R'").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reoriented() {
+        let cube = RubiksCube::new();
+        let state = cube.scramble_to_state("R U x y'").unwrap();
+
+        let reoriented = state.reoriented().unwrap();
+        assert!(reoriented.centers_solved());
+        assert_eq!(reoriented, cube.scramble_to_state("R U").unwrap());
+    }
+
+    #[test]
+    fn test_cycles() {
+        let cube = RubiksCube::new();
+
+        assert!(State::solved().cycles().is_empty());
+        assert_eq!(State::solved().cycles().to_string(), "solved");
+
+        // A-perm: コーナーの 3-cycle
+        let a_perm = cube.scramble_to_state("R' F R' B2 R F' R' B2 R2").unwrap();
+        assert_eq!(a_perm.cycles().to_string(), "(UBL UBR UFR)");
+
+        let h_perm = cube.scramble_to_state("M2 U M2 U2 M2 U M2").unwrap();
+        assert_eq!(h_perm.cycles().to_string(), "(UB UF) + (UR UL)");
+
+        let flip = cube
+            .scramble_to_state("M' U M' U M' U M' U2 M' U M' U M' U M'")
+            .unwrap();
+        assert_eq!(flip.cycles().to_string(), "UB flipped + UF flipped");
+
+        // 逆操作は巡回の向きが逆になる
+        let inverse = a_perm.inverse().cycles();
+        assert_eq!(inverse.corners.len(), 1);
+        assert_eq!(inverse.corners[0].stickers, vec![(0, 0), (2, 0), (1, 0)]);
+
+        let rotation = cube.scramble_to_state("y").unwrap();
+        assert_eq!(rotation.cycles().centers, vec![vec![2, 5, 3, 4]]);
+    }
+
+    #[test]
+    fn test_cycle_twist() {
+        let mut state = State::solved();
+        state.co[2] = 1;
+        state.co[6] = 2;
+        assert_eq!(
+            state.cycles().to_string(),
+            "UFR twisted clockwise + DFR twisted counter-clockwise"
+        );
+    }
+
     #[test]
     fn test_non_matching_patterns() {
         // Create a state where top corners are scrambled
@@ -0,0 +1,626 @@
+use super::move_table::notation_move_state;
+use super::solver::{follows, last_turn, MoveSet};
+use super::state::State;
+use crate::parser::move_parser::{reversed_sequence, Sequence};
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+/// 4 つの E スライスエッジ（BL, BR, FR, FL）の位置の組み合わせ数 C(12, 4)
+const SLICE_COUNT: usize = 495;
+/// コーナーの向きの座標数 3^7
+const TWIST_COUNT: usize = 2187;
+/// エッジの向きの座標数 2^11
+const FLIP_COUNT: usize = 2048;
+/// コーナー / U・D エッジの置換の座標数 8!
+const PERM8_COUNT: usize = 40320;
+/// E スライスエッジの置換の座標数 4!
+const SLICE_PERM_COUNT: usize = 24;
+
+/// Phase 1 の最大手数
+const MAX_PHASE1_DEPTH: usize = 12;
+/// Phase 2 の最大手数（<U, D, R2, L2, F2, B2> の直径）
+const MAX_PHASE2_DEPTH: usize = 18;
+
+/// Phase 2 で使う手の `MoveSet::Htm` 内の番号（U, D, U', D', U2, D2, R2, L2, F2, B2）
+const PHASE2_MOVES: [usize; 10] = [0, 1, 6, 7, 12, 13, 14, 15, 16, 17];
+
+/// Two-phase (Kociemba) solver for the whole cube
+///
+/// Phase 1 brings the cube into the subgroup <U, D, R2, L2, F2, B2>
+/// (all orientations solved, E-slice edges in the E slice); phase 2 solves
+/// it with moves of that subgroup. Both phases are IDA* searches on
+/// coordinates with pruning tables. The search keeps improving the total
+/// length until it is at most `max_length`.
+pub struct TwoPhaseSolver {
+    max_length: usize,
+}
+
+/// 座標の遷移表と枝刈り表（初回使用時に 1 度だけ作る）
+struct Tables {
+    moves: Sequence,
+    move_states: Vec<State>,
+    twist_move: Vec<[u16; 18]>,
+    flip_move: Vec<[u16; 18]>,
+    slice_move: Vec<[u16; 18]>,
+    corner_perm_move: Vec<[u16; 10]>,
+    edge_perm_move: Vec<[u16; 10]>,
+    slice_perm_move: Vec<[u8; 10]>,
+    twist_slice_prune: Vec<u8>,
+    flip_slice_prune: Vec<u8>,
+    corner_slice_prune: Vec<u8>,
+    edge_slice_prune: Vec<u8>,
+    /// 4 ビットが立った 12 ビットのマスク → 組み合わせ番号
+    slice_index: Vec<u16>,
+    /// 組み合わせ番号 → マスク
+    slice_masks: Vec<u16>,
+}
+
+/// 探索中の状態
+struct Search<'a> {
+    tables: &'a Tables,
+    start: State,
+    max_length: usize,
+    path: Vec<usize>,
+    best: Option<Vec<usize>>,
+}
+
+impl TwoPhaseSolver {
+    /// Create a solver that stops at the first solution of at most 21 moves
+    pub fn new() -> Self {
+        TwoPhaseSolver { max_length: 21 }
+    }
+
+    /// Set the length at which the search stops improving the solution
+    ///
+    /// Lower values give shorter solutions but take longer.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Find a sequence of face turns that solves `state`
+    ///
+    /// The state must be legal. If its centers are turned, the solution is
+    /// for the cube held with its centers back in their home positions.
+    pub fn solve(&self, state: &State) -> Result<Sequence, String> {
+        state.validate().into_result()?;
+        let state = state.reoriented().unwrap_or_else(|| state.clone());
+
+        let tables = Tables::get();
+        let mut search = Search {
+            tables,
+            start: state.clone(),
+            max_length: self.max_length,
+            path: Vec::new(),
+            best: None,
+        };
+
+        let twist = twist_coordinate(&state);
+        let flip = flip_coordinate(&state);
+        let slice = tables.slice_coordinate(&state);
+
+        for depth in 0..=MAX_PHASE1_DEPTH {
+            // Phase 1 だけで見つかっている解の長さに届くなら、それより短い解はない
+            if search.is_done() || search.best.as_ref().is_some_and(|best| depth >= best.len()) {
+                break;
+            }
+            search.phase1(twist, flip, slice, depth);
+        }
+
+        search
+            .best
+            .map(|path| path.iter().map(|&i| tables.moves[i].clone()).collect())
+            .ok_or_else(|| "No solution found".to_string())
+    }
+
+    /// Generate a scramble that takes the solved cube to `state`
+    ///
+    /// This is the inverse of the solution, so applying it to a solved cube
+    /// (held with U on top and F in front) reproduces `state`.
+    pub fn scramble(&self, state: &State) -> Result<Sequence, String> {
+        let solution = self.solve(state)?;
+        Ok(reversed_sequence(&solution))
+    }
+}
+
+impl Default for TwoPhaseSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Search<'_> {
+    fn is_done(&self) -> bool {
+        self.best
+            .as_ref()
+            .is_some_and(|best| best.len() <= self.max_length)
+    }
+
+    /// Phase 1: 向きとスライスを揃える手順を深さ `remaining` で探す
+    fn phase1(&mut self, twist: usize, flip: usize, slice: usize, remaining: usize) {
+        if self.is_done() {
+            return;
+        }
+
+        if remaining == 0 {
+            if twist == 0 && flip == 0 && slice == 0 {
+                // 最後が Phase 2 の手なら、より短い Phase 1 の解で既に試している
+                let redundant = self
+                    .path
+                    .last()
+                    .is_some_and(|last| PHASE2_MOVES.contains(last));
+                if !redundant {
+                    self.phase2_start();
+                }
+            }
+            return;
+        }
+
+        let tables = self.tables;
+        let previous = last_turn(self.path.iter().map(|&i| &tables.moves[i]));
+        for m in 0..tables.moves.len() {
+            if !follows(&tables.moves[m], previous.as_ref(), &tables.moves) {
+                continue;
+            }
+
+            let next_twist = tables.twist_move[twist][m] as usize;
+            let next_flip = tables.flip_move[flip][m] as usize;
+            let next_slice = tables.slice_move[slice][m] as usize;
+            if tables.phase1_distance(next_twist, next_flip, next_slice) as usize >= remaining {
+                continue;
+            }
+
+            self.path.push(m);
+            self.phase1(next_twist, next_flip, next_slice, remaining - 1);
+            self.path.pop();
+        }
+    }
+
+    /// Phase 1 の解に続けて Phase 2 を探索する
+    fn phase2_start(&mut self) {
+        let tables = self.tables;
+        let state = self.path.iter().fold(self.start.clone(), |s, &m| {
+            s.apply_move(&tables.move_states[m])
+        });
+
+        let corner_perm = corner_perm_coordinate(&state);
+        let edge_perm = edge_perm_coordinate(&state);
+        let slice_perm = slice_perm_coordinate(&state);
+
+        // 既に見つかっている解より短い場合だけ探す（Phase 1 だけで届かなければ探さない）
+        let limit = match &self.best {
+            Some(best) => best.len(),
+            None => usize::MAX,
+        };
+        if self.path.len() >= limit {
+            return;
+        }
+        let max_depth = MAX_PHASE2_DEPTH.min(limit - self.path.len() - 1);
+
+        let phase1_length = self.path.len();
+        for depth in 0..=max_depth {
+            if self.phase2(corner_perm, edge_perm, slice_perm, depth) {
+                self.best = Some(self.path.clone());
+                self.path.truncate(phase1_length);
+                return;
+            }
+        }
+    }
+
+    /// Phase 2: <U, D, R2, L2, F2, B2> の手だけで揃える
+    fn phase2(
+        &mut self,
+        corner_perm: usize,
+        edge_perm: usize,
+        slice_perm: usize,
+        remaining: usize,
+    ) -> bool {
+        if remaining == 0 {
+            return corner_perm == 0 && edge_perm == 0 && slice_perm == 0;
+        }
+
+        let tables = self.tables;
+        let previous = last_turn(self.path.iter().map(|&i| &tables.moves[i]));
+        for (k, &m) in PHASE2_MOVES.iter().enumerate() {
+            if !follows(&tables.moves[m], previous.as_ref(), &tables.moves) {
+                continue;
+            }
+
+            let next_corner = tables.corner_perm_move[corner_perm][k] as usize;
+            let next_edge = tables.edge_perm_move[edge_perm][k] as usize;
+            let next_slice = tables.slice_perm_move[slice_perm][k] as usize;
+            if tables.phase2_distance(next_corner, next_edge, next_slice) as usize >= remaining {
+                continue;
+            }
+
+            self.path.push(m);
+            if self.phase2(next_corner, next_edge, next_slice, remaining - 1) {
+                return true;
+            }
+            self.path.pop();
+        }
+        false
+    }
+}
+
+impl Tables {
+    fn get() -> &'static Tables {
+        static TABLES: OnceLock<Tables> = OnceLock::new();
+        TABLES.get_or_init(Tables::build)
+    }
+
+    fn build() -> Tables {
+        let moves = MoveSet::Htm.moves();
+        let move_states: Vec<State> = moves
+            .iter()
+            .map(|m| notation_move_state(m).clone())
+            .collect();
+
+        let mut slice_index = vec![u16::MAX; 1 << 12];
+        let mut slice_masks = Vec::with_capacity(SLICE_COUNT);
+        for mask in 0..(1u16 << 12) {
+            if mask.count_ones() == 4 {
+                slice_index[mask as usize] = slice_masks.len() as u16;
+                slice_masks.push(mask);
+            }
+        }
+
+        let mut tables = Tables {
+            moves,
+            move_states,
+            twist_move: Vec::new(),
+            flip_move: Vec::new(),
+            slice_move: Vec::new(),
+            corner_perm_move: Vec::new(),
+            edge_perm_move: Vec::new(),
+            slice_perm_move: Vec::new(),
+            twist_slice_prune: Vec::new(),
+            flip_slice_prune: Vec::new(),
+            corner_slice_prune: Vec::new(),
+            edge_slice_prune: Vec::new(),
+            slice_index,
+            slice_masks,
+        };
+
+        // 座標ごとに代表の State を作り、手を適用して遷移表を作る
+        tables.twist_move = (0..TWIST_COUNT)
+            .map(|c| tables.phase1_row(&twist_state(c), |_, s| twist_coordinate(s)))
+            .collect();
+        tables.flip_move = (0..FLIP_COUNT)
+            .map(|c| tables.phase1_row(&flip_state(c), |_, s| flip_coordinate(s)))
+            .collect();
+        tables.slice_move = (0..SLICE_COUNT)
+            .map(|c| {
+                let state = tables.slice_state(c);
+                tables.phase1_row(&state, |t, s| t.slice_coordinate(s))
+            })
+            .collect();
+        tables.corner_perm_move = (0..PERM8_COUNT)
+            .map(|c| tables.phase2_row(&corner_perm_state(c), corner_perm_coordinate))
+            .collect();
+        tables.edge_perm_move = (0..PERM8_COUNT)
+            .map(|c| tables.phase2_row(&edge_perm_state(c), edge_perm_coordinate))
+            .collect();
+        tables.slice_perm_move = (0..SLICE_PERM_COUNT)
+            .map(|c| {
+                let row = tables.phase2_row(&slice_perm_state(c), slice_perm_coordinate);
+                row.map(|x| x as u8)
+            })
+            .collect();
+
+        tables.twist_slice_prune = prune_table(TWIST_COUNT, SLICE_COUNT, 18, |a, b, m| {
+            (
+                tables.twist_move[a][m] as usize,
+                tables.slice_move[b][m] as usize,
+            )
+        });
+        tables.flip_slice_prune = prune_table(FLIP_COUNT, SLICE_COUNT, 18, |a, b, m| {
+            (
+                tables.flip_move[a][m] as usize,
+                tables.slice_move[b][m] as usize,
+            )
+        });
+        tables.corner_slice_prune = prune_table(PERM8_COUNT, SLICE_PERM_COUNT, 10, |a, b, m| {
+            (
+                tables.corner_perm_move[a][m] as usize,
+                tables.slice_perm_move[b][m] as usize,
+            )
+        });
+        tables.edge_slice_prune = prune_table(PERM8_COUNT, SLICE_PERM_COUNT, 10, |a, b, m| {
+            (
+                tables.edge_perm_move[a][m] as usize,
+                tables.slice_perm_move[b][m] as usize,
+            )
+        });
+
+        tables
+    }
+
+    fn phase1_row(
+        &self,
+        state: &State,
+        coordinate: impl Fn(&Tables, &State) -> usize,
+    ) -> [u16; 18] {
+        let mut row = [0u16; 18];
+        for (m, move_state) in self.move_states.iter().enumerate() {
+            row[m] = coordinate(self, &state.apply_move(move_state)) as u16;
+        }
+        row
+    }
+
+    fn phase2_row(&self, state: &State, coordinate: impl Fn(&State) -> usize) -> [u16; 10] {
+        let mut row = [0u16; 10];
+        for (k, &m) in PHASE2_MOVES.iter().enumerate() {
+            row[k] = coordinate(&state.apply_move(&self.move_states[m])) as u16;
+        }
+        row
+    }
+
+    fn phase1_distance(&self, twist: usize, flip: usize, slice: usize) -> u8 {
+        self.twist_slice_prune[twist * SLICE_COUNT + slice]
+            .max(self.flip_slice_prune[flip * SLICE_COUNT + slice])
+    }
+
+    fn phase2_distance(&self, corner_perm: usize, edge_perm: usize, slice_perm: usize) -> u8 {
+        self.corner_slice_prune[corner_perm * SLICE_PERM_COUNT + slice_perm]
+            .max(self.edge_slice_prune[edge_perm * SLICE_PERM_COUNT + slice_perm])
+    }
+
+    /// E スライスエッジ（ピース 0..4）がある位置の組み合わせ。スライス内なら 0
+    fn slice_coordinate(&self, state: &State) -> usize {
+        let mask = state
+            .ep
+            .iter()
+            .enumerate()
+            .filter(|&(_, &piece)| piece < 4)
+            .fold(0usize, |mask, (position, _)| mask | (1 << position));
+        self.slice_index[mask] as usize
+    }
+
+    fn slice_state(&self, coordinate: usize) -> State {
+        let mask = self.slice_masks[coordinate];
+        let mut ep = [0u8; 12];
+        let (mut slice_piece, mut other_piece) = (0u8, 4u8);
+        for (position, piece) in ep.iter_mut().enumerate() {
+            if mask & (1 << position) != 0 {
+                *piece = slice_piece;
+                slice_piece += 1;
+            } else {
+                *piece = other_piece;
+                other_piece += 1;
+            }
+        }
+        State::new(State::solved().cp, [0; 8], ep, [0; 12])
+    }
+}
+
+/// 2 つの座標の組に対する最短手数表を幅優先探索で作る
+fn prune_table(
+    size_a: usize,
+    size_b: usize,
+    move_count: usize,
+    apply: impl Fn(usize, usize, usize) -> (usize, usize),
+) -> Vec<u8> {
+    let mut distances = vec![u8::MAX; size_a * size_b];
+    let mut queue = VecDeque::new();
+    distances[0] = 0;
+    queue.push_back(0u32);
+
+    while let Some(index) = queue.pop_front() {
+        let index = index as usize;
+        let (a, b) = (index / size_b, index % size_b);
+        let distance = distances[index];
+        for m in 0..move_count {
+            let (next_a, next_b) = apply(a, b, m);
+            let next = next_a * size_b + next_b;
+            if distances[next] == u8::MAX {
+                distances[next] = distance + 1;
+                queue.push_back(next as u32);
+            }
+        }
+    }
+    distances
+}
+
+fn twist_coordinate(state: &State) -> usize {
+    state.co[..7].iter().fold(0, |acc, &o| acc * 3 + o as usize)
+}
+
+fn twist_state(coordinate: usize) -> State {
+    let mut co = [0u8; 8];
+    let mut rest = coordinate;
+    for i in (0..7).rev() {
+        co[i] = (rest % 3) as u8;
+        rest /= 3;
+    }
+    co[7] = ((3 - co[..7].iter().map(|&o| o as usize).sum::<usize>() % 3) % 3) as u8;
+    let solved = State::solved();
+    State::new(solved.cp, co, solved.ep, solved.eo)
+}
+
+fn flip_coordinate(state: &State) -> usize {
+    state.eo[..11]
+        .iter()
+        .fold(0, |acc, &o| acc * 2 + o as usize)
+}
+
+fn flip_state(coordinate: usize) -> State {
+    let mut eo = [0u8; 12];
+    let mut rest = coordinate;
+    for i in (0..11).rev() {
+        eo[i] = (rest % 2) as u8;
+        rest /= 2;
+    }
+    eo[11] = eo[..11].iter().sum::<u8>() % 2;
+    let solved = State::solved();
+    State::new(solved.cp, solved.co, solved.ep, eo)
+}
+
+/// 置換の辞書順の番号
+fn permutation_index(permutation: &[u8]) -> usize {
+    let n = permutation.len();
+    let mut index = 0;
+    for i in 0..n {
+        let smaller = permutation[i + 1..]
+            .iter()
+            .filter(|&&p| p < permutation[i])
+            .count();
+        index = index * (n - i) + smaller;
+    }
+    index
+}
+
+fn permutation_from_index(mut index: usize, n: usize) -> Vec<u8> {
+    let mut digits = vec![0usize; n];
+    for i in (0..n).rev() {
+        digits[i] = index % (n - i);
+        index /= n - i;
+    }
+    let mut remaining: Vec<u8> = (0..n as u8).collect();
+    digits.iter().map(|&d| remaining.remove(d)).collect()
+}
+
+fn corner_perm_coordinate(state: &State) -> usize {
+    permutation_index(&state.cp)
+}
+
+fn corner_perm_state(coordinate: usize) -> State {
+    let cp: [u8; 8] = permutation_from_index(coordinate, 8).try_into().unwrap();
+    let solved = State::solved();
+    State::new(cp, solved.co, solved.ep, solved.eo)
+}
+
+/// U・D 層のエッジ（位置 4..12）の置換
+fn edge_perm_coordinate(state: &State) -> usize {
+    let permutation: Vec<u8> = state.ep[4..].iter().map(|&p| p.wrapping_sub(4)).collect();
+    permutation_index(&permutation)
+}
+
+fn edge_perm_state(coordinate: usize) -> State {
+    let mut ep = State::solved().ep;
+    for (i, p) in permutation_from_index(coordinate, 8)
+        .into_iter()
+        .enumerate()
+    {
+        ep[4 + i] = p + 4;
+    }
+    let solved = State::solved();
+    State::new(solved.cp, solved.co, ep, solved.eo)
+}
+
+/// E スライス（位置 0..4）のエッジの置換
+fn slice_perm_coordinate(state: &State) -> usize {
+    permutation_index(&state.ep[..4])
+}
+
+fn slice_perm_state(coordinate: usize) -> State {
+    let mut ep = State::solved().ep;
+    for (i, p) in permutation_from_index(coordinate, 4)
+        .into_iter()
+        .enumerate()
+    {
+        ep[i] = p;
+    }
+    let solved = State::solved();
+    State::new(solved.cp, solved.co, ep, solved.eo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::operations::{apply_notation_move, RubiksCube};
+    use crate::cube::SolutionSearcher;
+    use crate::parser::NotationMove;
+
+    fn apply_moves(state: &State, moves: &[NotationMove]) -> State {
+        moves
+            .iter()
+            .fold(state.clone(), |s, m| apply_notation_move(&s, m))
+    }
+
+    #[test]
+    fn test_permutation_index_round_trip() {
+        for index in [0, 1, 23, 5039, 40319] {
+            let n = if index < 24 { 4 } else { 8 };
+            assert_eq!(permutation_index(&permutation_from_index(index, n)), index);
+        }
+    }
+
+    #[test]
+    fn test_solve_random_scrambles() {
+        let cube = RubiksCube::new();
+        let solver = TwoPhaseSolver::new();
+
+        for scramble in [
+            "D2 F' L B2 R' U2 F D' L2 B U R2 F' D L' B2 U' R F2 D'",
+            "R U R' U' R' F R2 U' R' U' R U R' F'",
+            "F R' B2 L U' D2 F' R2 L' B U2 R D' F2 L B' U R' D",
+        ] {
+            let state = cube.scramble_to_state(scramble).unwrap();
+            let solution = solver.solve(&state).unwrap();
+
+            assert!(
+                solution.len() <= 21,
+                "{}: {} moves",
+                scramble,
+                solution.len()
+            );
+            assert!(apply_moves(&state, &solution).is_solved_in_place());
+        }
+    }
+
+    #[test]
+    fn test_solve_solved_and_short() {
+        let cube = RubiksCube::new();
+        let solver = TwoPhaseSolver::new();
+
+        assert!(solver.solve(&State::solved()).unwrap().is_empty());
+
+        // 最初の解で止めずに、3 手以下になるまで探索を続ける
+        let state = cube.scramble_to_state("R U2 F'").unwrap();
+        let solution = solver.with_max_length(3).solve(&state).unwrap();
+        assert_eq!(SolutionSearcher::format_solution(&solution), "F U2 R'");
+    }
+
+    #[test]
+    fn test_unreachable_max_length_keeps_shortest() {
+        let cube = RubiksCube::new();
+
+        // 届かない max_length で探索を続けても、見つけた解より長い解に置き換えない
+        for scramble in ["R U", "F R U L"] {
+            let state = cube.scramble_to_state(scramble).unwrap();
+            let first = TwoPhaseSolver::new().solve(&state).unwrap();
+            let solution = TwoPhaseSolver::new()
+                .with_max_length(1)
+                .solve(&state)
+                .unwrap();
+
+            assert!(
+                solution.len() <= first.len(),
+                "{}: {} moves, {} without max_length",
+                scramble,
+                solution.len(),
+                first.len()
+            );
+            assert!(apply_moves(&state, &solution).is_solved_in_place());
+        }
+    }
+
+    #[test]
+    fn test_scramble_reproduces_state() {
+        let cube = RubiksCube::new();
+        let state = cube
+            .scramble_to_state("L2 B' D R2 F U' B2 R D2 L' F2 U")
+            .unwrap();
+        let scramble = TwoPhaseSolver::new().scramble(&state).unwrap();
+
+        assert_eq!(apply_moves(&State::solved(), &scramble), state);
+    }
+
+    #[test]
+    fn test_reject_illegal_state() {
+        let mut state = State::solved();
+        state.co[0] = 1;
+        assert!(TwoPhaseSolver::new().solve(&state).is_err());
+    }
+}
@@ -0,0 +1,427 @@
+use super::operations::RubiksCube;
+use super::state::{State, CORNER_NAMES, EDGE_NAMES};
+use serde::{Deserialize, Serialize};
+
+/// State の各配列を表す（エディタでのハイライト用）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StateComponent {
+    Cp,
+    Co,
+    Ep,
+    Eo,
+    Centers,
+}
+
+/// State が実際のキューブとして成立しない理由
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StateIssue {
+    /// cp[position] が 0..8 の範囲外
+    CornerOutOfRange { position: usize, value: u8 },
+    /// 同じコーナーが複数の位置にある
+    DuplicateCorner { piece: u8, positions: Vec<usize> },
+    /// どの位置にも存在しないコーナー
+    MissingCorner { piece: u8 },
+    /// ep[position] が 0..12 の範囲外
+    EdgeOutOfRange { position: usize, value: u8 },
+    /// 同じエッジが複数の位置にある
+    DuplicateEdge { piece: u8, positions: Vec<usize> },
+    /// どの位置にも存在しないエッジ
+    MissingEdge { piece: u8 },
+    /// co[position] が 0..3 の範囲外
+    CornerOrientationOutOfRange { position: usize, value: u8 },
+    /// eo[position] が 0..2 の範囲外
+    EdgeOrientationOutOfRange { position: usize, value: u8 },
+    /// co の合計が 3 の倍数でない
+    CornerTwistSum { sum: u32 },
+    /// eo の合計が偶数でない
+    EdgeFlipSum { sum: u32 },
+    /// コーナー・エッジ（・センター）の置換の偶奇が一致しない
+    ParityMismatch {
+        corner_parity_odd: bool,
+        edge_parity_odd: bool,
+        center_parity_odd: bool,
+    },
+    /// センターの配置が持ち替えで作れない
+    InvalidCenters { centers: [u8; 6] },
+}
+
+impl StateIssue {
+    /// 問題のある配列
+    pub fn component(&self) -> StateComponent {
+        match self {
+            StateIssue::CornerOutOfRange { .. }
+            | StateIssue::DuplicateCorner { .. }
+            | StateIssue::MissingCorner { .. } => StateComponent::Cp,
+            StateIssue::CornerOrientationOutOfRange { .. } | StateIssue::CornerTwistSum { .. } => {
+                StateComponent::Co
+            }
+            StateIssue::EdgeOutOfRange { .. }
+            | StateIssue::DuplicateEdge { .. }
+            | StateIssue::MissingEdge { .. }
+            | StateIssue::ParityMismatch { .. } => StateComponent::Ep,
+            StateIssue::EdgeOrientationOutOfRange { .. } | StateIssue::EdgeFlipSum { .. } => {
+                StateComponent::Eo
+            }
+            StateIssue::InvalidCenters { .. } => StateComponent::Centers,
+        }
+    }
+
+    /// 問題のある配列内の位置（特定できる場合）
+    pub fn positions(&self) -> Vec<usize> {
+        match self {
+            StateIssue::CornerOutOfRange { position, .. }
+            | StateIssue::EdgeOutOfRange { position, .. }
+            | StateIssue::CornerOrientationOutOfRange { position, .. }
+            | StateIssue::EdgeOrientationOutOfRange { position, .. } => vec![*position],
+            StateIssue::DuplicateCorner { positions, .. }
+            | StateIssue::DuplicateEdge { positions, .. } => positions.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for StateIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateIssue::CornerOutOfRange { position, value } => {
+                write!(f, "cp[{}] = {} is out of range (0-7)", position, value)
+            }
+            StateIssue::DuplicateCorner { piece, positions } => write!(
+                f,
+                "corner {} ({}) appears at cp{:?}",
+                piece, CORNER_NAMES[*piece as usize], positions
+            ),
+            StateIssue::MissingCorner { piece } => write!(
+                f,
+                "corner {} ({}) is missing from cp",
+                piece, CORNER_NAMES[*piece as usize]
+            ),
+            StateIssue::EdgeOutOfRange { position, value } => {
+                write!(f, "ep[{}] = {} is out of range (0-11)", position, value)
+            }
+            StateIssue::DuplicateEdge { piece, positions } => write!(
+                f,
+                "edge {} ({}) appears at ep{:?}",
+                piece, EDGE_NAMES[*piece as usize], positions
+            ),
+            StateIssue::MissingEdge { piece } => write!(
+                f,
+                "edge {} ({}) is missing from ep",
+                piece, EDGE_NAMES[*piece as usize]
+            ),
+            StateIssue::CornerOrientationOutOfRange { position, value } => {
+                write!(f, "co[{}] = {} is out of range (0-2)", position, value)
+            }
+            StateIssue::EdgeOrientationOutOfRange { position, value } => {
+                write!(f, "eo[{}] = {} is out of range (0-1)", position, value)
+            }
+            StateIssue::CornerTwistSum { sum } => {
+                write!(f, "sum of co is {} (must be a multiple of 3)", sum)
+            }
+            StateIssue::EdgeFlipSum { sum } => {
+                write!(f, "sum of eo is {} (must be even)", sum)
+            }
+            StateIssue::ParityMismatch {
+                corner_parity_odd,
+                edge_parity_odd,
+                center_parity_odd,
+            } => {
+                let parity = |odd: &bool| if *odd { "odd" } else { "even" };
+                write!(
+                    f,
+                    "permutation parity mismatch (corners: {}, edges: {}, centers: {})",
+                    parity(corner_parity_odd),
+                    parity(edge_parity_odd),
+                    parity(center_parity_odd)
+                )
+            }
+            StateIssue::InvalidCenters { centers } => write!(
+                f,
+                "centers {:?} cannot be reached by whole-cube rotations",
+                centers
+            ),
+        }
+    }
+}
+
+/// State::validate の結果
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct StateValidation {
+    pub issues: Vec<StateIssue>,
+}
+
+impl StateValidation {
+    /// 問題がなければ true
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// 指定した配列に関する問題を取得
+    pub fn issues_for(&self, component: StateComponent) -> Vec<&StateIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.component() == component)
+            .collect()
+    }
+
+    /// 問題があれば Err に変換
+    pub fn into_result(self) -> Result<(), String> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(format!("Invalid state: {}", self))
+        }
+    }
+}
+
+impl std::fmt::Display for StateValidation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.issues.is_empty() {
+            return write!(f, "valid");
+        }
+        let messages: Vec<String> = self.issues.iter().map(|issue| issue.to_string()).collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+/// 置換の各要素の出現位置を調べ、範囲外・重複・欠落を報告する
+///
+/// 戻り値は置換として正しい場合の偶奇（奇置換なら true）
+fn check_permutation(
+    values: &[u8],
+    out_of_range: impl Fn(usize, u8) -> StateIssue,
+    duplicate: impl Fn(u8, Vec<usize>) -> StateIssue,
+    missing: impl Fn(u8) -> StateIssue,
+    issues: &mut Vec<StateIssue>,
+) -> Option<bool> {
+    let n = values.len();
+    let mut positions: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut valid = true;
+
+    for (i, &value) in values.iter().enumerate() {
+        if (value as usize) < n {
+            positions[value as usize].push(i);
+        } else {
+            issues.push(out_of_range(i, value));
+            valid = false;
+        }
+    }
+
+    for (piece, found) in positions.into_iter().enumerate() {
+        match found.len() {
+            0 => {
+                issues.push(missing(piece as u8));
+                valid = false;
+            }
+            1 => {}
+            _ => {
+                issues.push(duplicate(piece as u8, found));
+                valid = false;
+            }
+        }
+    }
+
+    if valid {
+        Some(permutation_is_odd(values))
+    } else {
+        None
+    }
+}
+
+/// 置換が奇置換かどうか（巡回分解して判定）
+pub(crate) fn permutation_is_odd(values: &[u8]) -> bool {
+    let mut visited = vec![false; values.len()];
+    let mut transpositions = 0;
+
+    for start in 0..values.len() {
+        if visited[start] {
+            continue;
+        }
+        let mut length = 0;
+        let mut current = start;
+        while !visited[current] {
+            visited[current] = true;
+            current = values[current] as usize;
+            length += 1;
+        }
+        transpositions += length - 1;
+    }
+
+    transpositions % 2 == 1
+}
+
+impl State {
+    /// Check that this state can be reached from the solved cube
+    ///
+    /// Reports out-of-range, duplicate and missing pieces, orientation sums
+    /// and permutation parity. An empty report means the state is legal.
+    pub fn validate(&self) -> StateValidation {
+        let mut issues = Vec::new();
+
+        let corner_parity = check_permutation(
+            &self.cp,
+            |position, value| StateIssue::CornerOutOfRange { position, value },
+            |piece, positions| StateIssue::DuplicateCorner { piece, positions },
+            |piece| StateIssue::MissingCorner { piece },
+            &mut issues,
+        );
+        let edge_parity = check_permutation(
+            &self.ep,
+            |position, value| StateIssue::EdgeOutOfRange { position, value },
+            |piece, positions| StateIssue::DuplicateEdge { piece, positions },
+            |piece| StateIssue::MissingEdge { piece },
+            &mut issues,
+        );
+
+        // Orientation の範囲と合計
+        let mut co_in_range = true;
+        for (position, &value) in self.co.iter().enumerate() {
+            if value > 2 {
+                issues.push(StateIssue::CornerOrientationOutOfRange { position, value });
+                co_in_range = false;
+            }
+        }
+        if co_in_range {
+            let sum: u32 = self.co.iter().map(|&v| v as u32).sum();
+            if !sum.is_multiple_of(3) {
+                issues.push(StateIssue::CornerTwistSum { sum });
+            }
+        }
+
+        let mut eo_in_range = true;
+        for (position, &value) in self.eo.iter().enumerate() {
+            if value > 1 {
+                issues.push(StateIssue::EdgeOrientationOutOfRange { position, value });
+                eo_in_range = false;
+            }
+        }
+        if eo_in_range {
+            let sum: u32 = self.eo.iter().map(|&v| v as u32).sum();
+            if !sum.is_multiple_of(2) {
+                issues.push(StateIssue::EdgeFlipSum { sum });
+            }
+        }
+
+        // センターは 24 通りの持ち替えのいずれかでなければならない
+        let centers_valid = RubiksCube::whole_cube_rotations()
+            .iter()
+            .any(|rotation| rotation.centers == self.centers);
+        if !centers_valid {
+            issues.push(StateIssue::InvalidCenters {
+                centers: self.centers,
+            });
+        }
+
+        // スライスはエッジとセンターを同時に 4-cycle させるので、三者の偶奇の和で判定する
+        if let (Some(corner_parity_odd), Some(edge_parity_odd), true) =
+            (corner_parity, edge_parity, centers_valid)
+        {
+            let center_parity_odd = permutation_is_odd(&self.centers);
+            if corner_parity_odd ^ edge_parity_odd ^ center_parity_odd {
+                issues.push(StateIssue::ParityMismatch {
+                    corner_parity_odd,
+                    edge_parity_odd,
+                    center_parity_odd,
+                });
+            }
+        }
+
+        StateValidation { issues }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrambled_states_are_valid() {
+        let cube = RubiksCube::new();
+        for scramble in ["", "R U R' U'", "R", "M", "r U2 x' S E2", "F2 B' L D' y z2"] {
+            let validation = cube.scramble_to_state(scramble).unwrap().validate();
+            assert!(validation.is_valid(), "{}: {}", scramble, validation);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_and_missing_pieces() {
+        let state = State::new(
+            [0, 0, 2, 3, 4, 5, 6, 7],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 12],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+        let validation = state.validate();
+
+        assert!(validation.issues.contains(&StateIssue::DuplicateCorner {
+            piece: 0,
+            positions: vec![0, 1]
+        }));
+        assert!(validation
+            .issues
+            .contains(&StateIssue::MissingCorner { piece: 1 }));
+        assert!(validation.issues.contains(&StateIssue::EdgeOutOfRange {
+            position: 11,
+            value: 12
+        }));
+        assert!(validation
+            .issues
+            .contains(&StateIssue::MissingEdge { piece: 11 }));
+        // ピースが壊れている場合は偶奇を判定しない
+        assert!(!validation
+            .issues
+            .iter()
+            .any(|issue| matches!(issue, StateIssue::ParityMismatch { .. })));
+    }
+
+    #[test]
+    fn test_orientation_sums() {
+        let mut state = State::solved();
+        state.co[2] = 1;
+        state.eo[6] = 1;
+        let validation = state.validate();
+
+        assert_eq!(
+            validation.issues,
+            vec![
+                StateIssue::CornerTwistSum { sum: 1 },
+                StateIssue::EdgeFlipSum { sum: 1 },
+            ]
+        );
+        assert_eq!(validation.issues[0].component(), StateComponent::Co);
+        assert_eq!(validation.issues[1].component(), StateComponent::Eo);
+    }
+
+    #[test]
+    fn test_parity_mismatch() {
+        // UFR と UBR だけを交換
+        let mut state = State::solved();
+        state.cp.swap(1, 2);
+        let validation = state.validate();
+
+        assert_eq!(
+            validation.issues,
+            vec![StateIssue::ParityMismatch {
+                corner_parity_odd: true,
+                edge_parity_odd: false,
+                center_parity_odd: false,
+            }]
+        );
+
+        // UF と UR も交換すれば合法
+        state.ep.swap(5, 6);
+        assert!(state.validate().is_valid());
+        assert!(state.validate().into_result().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_centers() {
+        let mut state = State::solved();
+        state.centers = [1, 0, 2, 3, 4, 5];
+        let validation = state.validate();
+
+        assert_eq!(validation.issues.len(), 1);
+        assert_eq!(validation.issues[0].component(), StateComponent::Centers);
+        assert!(validation.into_result().is_err());
+    }
+}
@@ -35,7 +35,7 @@ impl StateToDisplay {
         let mut cube_stickers = CubeStickers::new_void();
         
         // Phase 2: Paint center stickers
-        Self::paint_centers(&mut cube_stickers);
+        Self::paint_centers(&mut cube_stickers, state);
         
         // Phase 3: Paint corner stickers
         Self::paint_corners(&mut cube_stickers, state);
@@ -48,22 +48,38 @@ impl StateToDisplay {
     }
     
     /// Phase 2: センターステッカーをペイント
-    fn paint_centers(cube_stickers: &mut CubeStickers) {
-        let center_colors = [
-            (Face::Up, CubeColor::White),
-            (Face::Down, CubeColor::Yellow),
-            (Face::Left, CubeColor::Orange),
-            (Face::Right, CubeColor::Red),
-            (Face::Front, CubeColor::Green),
-            (Face::Back, CubeColor::Blue),
+    ///
+    /// state.centers[i] は面 i の位置にあるセンターを表す（U, D, L, R, F, B の順）
+    fn paint_centers(cube_stickers: &mut CubeStickers, state: &State) {
+        const CENTER_FACES: [Face; 6] = [
+            Face::Up,
+            Face::Down,
+            Face::Left,
+            Face::Right,
+            Face::Front,
+            Face::Back,
         ];
-        
-        for (face, color) in center_colors {
-            if let Some(face_stickers) = cube_stickers.get_face_mut(&face) {
+
+        for (i, face) in CENTER_FACES.iter().enumerate() {
+            let color = Self::get_center_base_color(state.centers[i] as usize);
+            if let Some(face_stickers) = cube_stickers.get_face_mut(face) {
                 face_stickers.center = color;
             }
         }
     }
+
+    /// センターの基本色
+    fn get_center_base_color(center: usize) -> CubeColor {
+        match center {
+            0 => CubeColor::White,  // U
+            1 => CubeColor::Yellow, // D
+            2 => CubeColor::Orange, // L
+            3 => CubeColor::Red,    // R
+            4 => CubeColor::Green,  // F
+            5 => CubeColor::Blue,   // B
+            _ => CubeColor::Void,
+        }
+    }
     
     /// Phase 3: コーナーステッカーをペイント
     fn paint_corners(cube_stickers: &mut CubeStickers, state: &State) {
@@ -278,7 +294,7 @@ impl StateToDisplay {
         let mut cube_stickers = CubeStickers::new_void();
         
         // Phase 2: Paint center stickers
-        Self::paint_centers(&mut cube_stickers);
+        Self::paint_centers(&mut cube_stickers, state);
         
         // Phase 3: Paint corner stickers
         Self::paint_corners(&mut cube_stickers, state);
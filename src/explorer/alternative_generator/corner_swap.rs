@@ -1,7 +1,14 @@
 use crate::explorer::mixed_nearby_search::{AlternativeGenerator, MixedOperation};
+use crate::inspection::SolverConfig;
 
 /// Corner Swap用のAlternative Generator
-pub struct CornerSwapAlternativeGenerator;
+///
+/// 代替案は元の交換と同じバッファからの交換（フローティングバッファ以外なら
+/// `config.corner_buffer`）
+#[derive(Default)]
+pub struct CornerSwapAlternativeGenerator {
+    config: SolverConfig,
+}
 
 impl AlternativeGenerator<MixedOperation> for CornerSwapAlternativeGenerator {
     fn generate_alternatives(&self, operation: &MixedOperation) -> Vec<MixedOperation> {
@@ -18,21 +25,29 @@ impl AlternativeGenerator<MixedOperation> for CornerSwapAlternativeGenerator {
 }
 
 impl CornerSwapAlternativeGenerator {
+    pub fn new(config: SolverConfig) -> Self {
+        Self { config }
+    }
+
     fn generate_corner_swap_alternatives(&self, original: &crate::inspection::CornerSwapOperation) -> Vec<crate::inspection::CornerSwapOperation> {
         use crate::inspection::CornerSwapOperation;
         let mut alternatives = Vec::new();
 
-        const CORNER_BUFFER: usize = 2; // UFR
+        let corner_buffer = if self.config.corner_buffers().any(|b| b == original.target1) {
+            original.target1
+        } else {
+            self.config.corner_buffer
+        };
 
         // 各コーナー位置について
         for target in 0..8 {
-            if target == CORNER_BUFFER {
+            if target == corner_buffer {
                 continue;
             }
 
             // 各方向について
             for orientation in 0..3 {
-                let candidate = CornerSwapOperation::new(CORNER_BUFFER, target, orientation);
+                let candidate = CornerSwapOperation::new(corner_buffer, target, orientation);
                 
                 // 元の操作と異なる場合のみ追加
                 if candidate != *original {
@@ -52,7 +67,7 @@ mod tests {
 
     #[test]
     fn test_does_not_include_original() {
-        let generator = CornerSwapAlternativeGenerator;
+        let generator = CornerSwapAlternativeGenerator::default();
         let original = CornerSwapOperation::new(2, 1, 0);
         let original_mixed = MixedOperation::CornerSwap(original.clone());
         
@@ -72,7 +87,7 @@ mod tests {
 
     #[test]
     fn test_different_operations_included() {
-        let generator = CornerSwapAlternativeGenerator;
+        let generator = CornerSwapAlternativeGenerator::default();
         let original = CornerSwapOperation::new(2, 1, 0);
         let original_mixed = MixedOperation::CornerSwap(original);
         
@@ -87,4 +102,38 @@ mod tests {
             }
         }));
     }
+
+    #[test]
+    fn test_configured_buffer() {
+        let config = SolverConfig::from_names("UBL", "UF", ["UB", "UL"]).unwrap();
+        let generator = CornerSwapAlternativeGenerator::new(config);
+        let original = MixedOperation::CornerSwap(CornerSwapOperation::new(0, 1, 0));
+
+        let alternatives = generator.generate_alternatives(&original);
+
+        // UBL 以外の 7 targets × 3 orientations - 1 original = 20
+        assert_eq!(alternatives.len(), 20);
+        assert!(alternatives.iter().all(|alt| match alt {
+            MixedOperation::CornerSwap(op) => op.target1 == 0 && op.target2 != 0,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn test_floating_buffer() {
+        let config = SolverConfig::default()
+            .with_floating_corner_buffers(&[3])
+            .unwrap();
+        let generator = CornerSwapAlternativeGenerator::new(config);
+        let original = MixedOperation::CornerSwap(CornerSwapOperation::new(3, 4, 0));
+
+        let alternatives = generator.generate_alternatives(&original);
+
+        // UFL から交換したものは UFL からの交換に置き換える
+        assert_eq!(alternatives.len(), 20);
+        assert!(alternatives.iter().all(|alt| match alt {
+            MixedOperation::CornerSwap(op) => op.target1 == 3 && op.target2 != 3,
+            _ => false,
+        }));
+    }
 }
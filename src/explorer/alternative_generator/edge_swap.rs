@@ -1,7 +1,14 @@
 use crate::explorer::mixed_nearby_search::{AlternativeGenerator, MixedOperation};
+use crate::inspection::SolverConfig;
 
 /// Edge Swap用のAlternative Generator
-pub struct EdgeSwapAlternativeGenerator;
+///
+/// 代替案は元の交換と同じバッファからの交換（フローティングバッファ以外なら
+/// `config.edge_buffer`）
+#[derive(Default)]
+pub struct EdgeSwapAlternativeGenerator {
+    config: SolverConfig,
+}
 
 impl AlternativeGenerator<MixedOperation> for EdgeSwapAlternativeGenerator {
     fn generate_alternatives(&self, operation: &MixedOperation) -> Vec<MixedOperation> {
@@ -18,21 +25,29 @@ impl AlternativeGenerator<MixedOperation> for EdgeSwapAlternativeGenerator {
 }
 
 impl EdgeSwapAlternativeGenerator {
+    pub fn new(config: SolverConfig) -> Self {
+        Self { config }
+    }
+
     fn generate_edge_swap_alternatives(&self, original: &crate::inspection::EdgeSwapOperation) -> Vec<crate::inspection::EdgeSwapOperation> {
         use crate::inspection::EdgeSwapOperation;
         let mut alternatives = Vec::new();
 
-        const EDGE_BUFFER: usize = 6; // UF
+        let edge_buffer = if self.config.edge_buffers().any(|b| b == original.target1) {
+            original.target1
+        } else {
+            self.config.edge_buffer
+        };
 
         // 各エッジ位置について
         for target in 0..12 {
-            if target == EDGE_BUFFER {
+            if target == edge_buffer {
                 continue;
             }
 
             // 各方向について
             for orientation in 0..2 {
-                let candidate = EdgeSwapOperation::new(EDGE_BUFFER, target, orientation);
+                let candidate = EdgeSwapOperation::new(edge_buffer, target, orientation);
                 
                 // 元の操作と異なる場合のみ追加
                 if candidate != *original {
@@ -52,7 +67,7 @@ mod tests {
 
     #[test]
     fn test_does_not_include_original() {
-        let generator = EdgeSwapAlternativeGenerator;
+        let generator = EdgeSwapAlternativeGenerator::default();
         let original = EdgeSwapOperation::new(6, 0, 0);
         let original_mixed = MixedOperation::EdgeSwap(original.clone());
         
@@ -72,7 +87,7 @@ mod tests {
 
     #[test]
     fn test_different_operations_included() {
-        let generator = EdgeSwapAlternativeGenerator;
+        let generator = EdgeSwapAlternativeGenerator::default();
         let original = EdgeSwapOperation::new(6, 0, 0);
         let original_mixed = MixedOperation::EdgeSwap(original);
         
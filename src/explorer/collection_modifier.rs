@@ -1,6 +1,6 @@
 use crate::parser::move_parser::Sequence;
 use crate::explorer::ModifiedMoveSequence;
-use crate::cube::State;
+use crate::cube::{PackedState, State};
 use std::fmt;
 
 /// MoveSequenceCollectionの変更を表す
@@ -85,16 +85,19 @@ impl ModifiedMoveSequenceCollection {
 
     /// 変更されたCollectionを状態に適用
     pub fn apply_to_state(&self, initial_state: &State) -> State {
-        let collection = self.get_collection();
-        let mut state = initial_state.clone();
-        
-        for sequence in collection {
-            for mv in sequence {
-                state = crate::cube::operations::apply_notation_move(&state, &mv);
+        let mut state = PackedState::from(initial_state);
+        self.apply_to_packed(&mut state);
+        state.to_state()
+    }
+
+    /// 変更されたCollectionを PackedState にその場で適用（Collection を組み立てない）
+    pub fn apply_to_packed(&self, state: &mut PackedState) {
+        for (i, sequence) in self.original_collection.iter().enumerate() {
+            match self.modifiers.iter().find(|m| m.sequence_index() == i) {
+                Some(modifier) => modifier.modified_sequence().apply_to_packed(state),
+                None => state.apply_sequence(sequence),
             }
         }
-        
-        state
     }
 
     /// 指定したSequenceインデックスが変更されているか確認
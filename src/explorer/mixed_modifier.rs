@@ -1,4 +1,4 @@
-use crate::cube::State;
+use crate::cube::{PackedState, State};
 use super::mixed_nearby_search::{MixedOperation, ApplyableToState};
 use std::fmt;
 
@@ -90,14 +90,20 @@ impl ModifiedMixedSequence {
 
     /// 変更された操作列を状態に適用
     pub fn apply_to_state(&self, initial_state: &State) -> State {
-        let operations = self.get_sequence();
-        let mut state = initial_state.clone();
-        
-        for operation in operations {
-            state = operation.apply_to_state(&state);
+        let mut state = PackedState::from(initial_state);
+        self.apply_to_packed(&mut state);
+        state.to_state()
+    }
+
+    /// 変更された操作列を PackedState にその場で適用（操作列を組み立てない）
+    pub fn apply_to_packed(&self, state: &mut PackedState) {
+        for (i, operation) in self.original_sequence.iter().enumerate() {
+            // get_sequence と同じく、同じステップへの変更は後のものが優先
+            match self.modifiers.iter().rev().find(|m| m.step() == i) {
+                Some(modifier) => modifier.operation().apply_to_packed(state),
+                None => operation.apply_to_packed(state),
+            }
         }
-        
-        state
     }
 
     /// 指定したステップが変更されているか確認
@@ -1,5 +1,5 @@
 use super::mixed_modifier::{MixedModifier, ModifiedMixedSequence};
-use crate::cube::State;
+use crate::cube::{PackedState, State};
 
 /// 混合操作（Corner + Edge）の列挙型
 /// 各バリアントは CubeOperation を実装する型のみ
@@ -45,6 +45,15 @@ impl ApplyableToState for MixedOperation {
             MixedOperation::EdgeFlip(op) => op.apply(state),
         }
     }
+
+    fn apply_to_packed(&self, state: &mut PackedState) {
+        match self {
+            MixedOperation::CornerSwap(op) => state.swap_corners(op.target1, op.target2, op.orientation),
+            MixedOperation::CornerTwist(op) => state.twist_corner(op.target, op.orientation),
+            MixedOperation::EdgeSwap(op) => state.swap_edges(op.target1, op.target2, op.orientation),
+            MixedOperation::EdgeFlip(op) => state.flip_edge(op.target),
+        }
+    }
 }
 
 /// Alternative generator trait for generating alternative operations
@@ -55,6 +64,11 @@ pub trait AlternativeGenerator<T> {
 /// Trait for operations that can be applied to a State
 pub trait ApplyableToState {
     fn apply_to_state(&self, state: &State) -> State;
+
+    /// Apply in place to a packed state; override when a direct form exists
+    fn apply_to_packed(&self, state: &mut PackedState) {
+        *state = PackedState::from(&self.apply_to_state(&state.to_state()));
+    }
 }
 
 /// 混合操作列の近傍を探索する構造体
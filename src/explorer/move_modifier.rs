@@ -1,5 +1,5 @@
 use crate::parser::move_parser::{NotationMove, Sequence};
-use crate::cube::State;
+use crate::cube::{PackedState, State};
 use std::fmt;
 
 /// NotationMoveの変更を表す
@@ -85,14 +85,19 @@ impl ModifiedMoveSequence {
 
     /// 変更されたSequenceを状態に適用
     pub fn apply_to_state(&self, initial_state: &State) -> State {
-        let sequence = self.get_sequence();
-        let mut state = initial_state.clone();
-        
-        for mv in sequence {
-            state = crate::cube::operations::apply_notation_move(&state, &mv);
+        let mut state = PackedState::from(initial_state);
+        self.apply_to_packed(&mut state);
+        state.to_state()
+    }
+
+    /// 変更されたSequenceを PackedState にその場で適用（Sequence を組み立てない）
+    pub fn apply_to_packed(&self, state: &mut PackedState) {
+        for (i, mv) in self.original_sequence.iter().enumerate() {
+            match self.modifiers.iter().find(|m| m.step() == i) {
+                Some(modifier) => state.apply_notation_move(modifier.notation_move()),
+                None => state.apply_notation_move(mv),
+            }
         }
-        
-        state
     }
 
     /// 指定したステップが変更されているか確認
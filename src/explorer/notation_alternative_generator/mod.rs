@@ -103,6 +103,27 @@ impl SameGroupAlternativeGenerator {
                     NotationMove::E2,
                 ]
             }
+            NotationMove::X | NotationMove::X2 | NotationMove::XPrime => {
+                vec![
+                    NotationMove::X,
+                    NotationMove::XPrime,
+                    NotationMove::X2,
+                ]
+            }
+            NotationMove::Y | NotationMove::Y2 | NotationMove::YPrime => {
+                vec![
+                    NotationMove::Y,
+                    NotationMove::YPrime,
+                    NotationMove::Y2,
+                ]
+            }
+            NotationMove::Z | NotationMove::Z2 | NotationMove::ZPrime => {
+                vec![
+                    NotationMove::Z,
+                    NotationMove::ZPrime,
+                    NotationMove::Z2,
+                ]
+            }
             NotationMove::Noop => {
                 // Noopからの代替案は他の全てのムーブになる可能性があるが、
                 // 現実的には元のグループに基づくべき。
@@ -193,6 +214,9 @@ mod tests {
             NotationMove::M,
             NotationMove::S,
             NotationMove::E,
+            NotationMove::X,
+            NotationMove::Y,
+            NotationMove::Z,
         ];
         
         for mv in test_moves {
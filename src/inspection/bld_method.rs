@@ -0,0 +1,612 @@
+use super::operations_to_turns::{
+    EDGE_FLIP_TARGET_STICKERS, EDGE_TARGET_STICKERS, TARGET_STICKERS, TWIST_TARGET_STICKERS,
+};
+use super::{
+    CornerOperation, CornerSwapOperation, CornerTwistOperation, EdgeFlipOperation, EdgeOperation,
+    EdgeSwapOperation, MoveSequence, MoveSequenceCollection, OperationsToTurns,
+};
+use crate::cube::state::{CORNER_NAMES, EDGE_NAMES};
+use crate::cube::{PackedState, State};
+use crate::parser::{parse_and_expand, parse_sequence, reversed_sequence, Sequence};
+use std::collections::{HashMap, HashSet};
+
+/// 操作列（交換・ねじれ・反転）を手順に変換する解き方
+///
+/// `Op` は `CornerOperation` か `EdgeOperation`。手順表による 3-style（`OperationsToTurns`）の
+/// ほかに `OldPochmann`・`M2Edges`・`R2Corners` があり、コーナーとエッジで別の解き方を組み合わせられる
+pub trait BldMethod<Op> {
+    /// 操作列を MoveSequenceCollection に変換
+    fn convert(&self, operations: &[Op]) -> Result<MoveSequenceCollection, String>;
+}
+
+impl BldMethod<CornerOperation> for OperationsToTurns {
+    fn convert(&self, operations: &[CornerOperation]) -> Result<MoveSequenceCollection, String> {
+        OperationsToTurns::convert(self, operations)
+    }
+}
+
+impl BldMethod<EdgeOperation> for OperationsToTurns {
+    fn convert(&self, operations: &[EdgeOperation]) -> Result<MoveSequenceCollection, String> {
+        self.convert_edge_operations(operations)
+    }
+}
+
+/// コーナーとエッジの解き方の組
+#[derive(Clone, Copy)]
+pub struct BldMethods<'a> {
+    pub corners: &'a dyn BldMethod<CornerOperation>,
+    pub edges: &'a dyn BldMethod<EdgeOperation>,
+}
+
+/// setup の探索に使う手
+const SETUP_MOVES: [&str; 18] = [
+    "U", "U2", "U'", "D", "D2", "D'", "L", "L2", "L'", "R", "R2", "R'", "F", "F2", "F'", "B", "B2",
+    "B'",
+];
+
+/// setup の最大の手数
+const MAX_SETUP_LENGTH: usize = 4;
+
+/// Y perm（Old Pochmann のコーナー、バッファ UBL）
+const OP_CORNER_ALG: &str = "R U' R' U' R U R' F' R U R' U' R' F R";
+/// T perm（Old Pochmann のエッジ、バッファ UR / R2 のパリティ、バッファ UBR）
+const T_PERM: &str = "R U R' U' R' F R2 U' R' U' R U R' F'";
+
+/// M2 で setup が見つからないターゲットの手順（バッファ DF）
+const M2_SPECIAL_ALGS: [&str; 5] = [
+    "U2 M' U2 M'",
+    "M U2 M U2",
+    "D M' U R2 U' M U R2 U' D' M2",
+    "M2 D U R2 U' M' U R2 U' M D'",
+    "[U B' R U' B: M2]",
+];
+
+/// R2 で setup が見つからないターゲットの手順（バッファ UBR）
+const R2_SPECIAL_ALGS: [&str; 12] = [
+    "[D' B D' B' D2: R2]",
+    "[F' R2 B R2 F: R2]",
+    "[D2 R' B2 R D2: R2]",
+    "[U' F L2 F' U: R2]",
+    "[D' B L2 B' D: R2]",
+    "[U F2 U' F2 U': R2]",
+    "D' F2 D' B2 D F2 D' B2 D2 R2",
+    "R2 B U' B D2 B' U B D2 B2",
+    "B2 D2 B' U2 B D2 B' U2 B' R2",
+    "U2 F2 U B2 U' F2 U B2 U R2",
+    "R2 F2 U2 F' D' F U2 F' D F'",
+    "F D2 F U2 F' D2 F U2 F2 R2",
+];
+
+/// 手順の効果
+fn effect(moves: &Sequence) -> State {
+    let mut packed = PackedState::solved();
+    packed.apply_sequence(moves);
+    packed.to_state()
+}
+
+/// 2つの変化が同じ位置のコーナー・エッジを動かすか
+fn overlaps(a: &State, b: &State) -> bool {
+    let solved = State::solved();
+    let corner = |s: &State, i: usize| s.cp[i] != solved.cp[i] || s.co[i] != solved.co[i];
+    let edge = |s: &State, i: usize| s.ep[i] != solved.ep[i] || s.eo[i] != solved.eo[i];
+    (0..8).any(|i| corner(a, i) && corner(b, i)) || (0..12).any(|i| edge(a, i) && edge(b, i))
+}
+
+/// バッファと1つのターゲットを交換する手順の表（[setup: 核の手順] で撃つ）
+///
+/// 核の手順はバッファとヘルパーを交換し、ほかに2回で元に戻る変化（Y perm なら UB と UL の交換）を残す。
+/// 奇数回目の手順の後はこの変化が残っているので、次のターゲットは変化でずらして引く
+#[derive(Debug, Clone)]
+struct ShootingAlgs {
+    buffer: usize,
+    /// 交換の効果 → (ターゲットのステッカー, 手順)
+    algs: HashMap<State, (&'static str, Sequence)>,
+    /// 核の手順が交換の後に残す変化
+    side_effect: State,
+}
+
+impl ShootingAlgs {
+    /// `targets` は各ターゲットのステッカーとバッファとの交換の効果
+    ///
+    /// MAX_SETUP_LENGTH 手までの setup で核の手順に持ち込めるターゲットを探し、
+    /// `specials` でそれ以外のターゲットを補う（setup で見つかった手順より優先）
+    fn new(
+        buffer: usize,
+        core: &str,
+        targets: Vec<(&'static str, State)>,
+        specials: &[&str],
+    ) -> Result<Self, String> {
+        let parse = |alg: &str| {
+            parse_and_expand(alg).map_err(|e| format!("Failed to parse {}: {}", alg, e))
+        };
+        let core_moves = parse(core)?;
+        let core_effect = effect(&core_moves);
+
+        let side_effect = targets
+            .iter()
+            .find_map(|(_, swap)| {
+                let rest = swap.inverse().compose(&core_effect);
+                (!overlaps(&rest, swap)).then_some(rest)
+            })
+            .ok_or_else(|| format!("{} does not swap the buffer with a target", core))?;
+        if side_effect.compose(&side_effect) != State::solved() {
+            return Err(format!(
+                "The side effect of {} must undo itself when repeated",
+                core
+            ));
+        }
+
+        let stickers: HashMap<State, &'static str> = targets
+            .into_iter()
+            .map(|(sticker, swap)| (swap, sticker))
+            .collect();
+        let setup_moves: Vec<(Sequence, State)> = SETUP_MOVES
+            .iter()
+            .map(|m| {
+                let moves = parse_sequence(m).expect("setup moves are valid");
+                let state = effect(&moves);
+                (moves, state)
+            })
+            .collect();
+
+        let mut algs = HashMap::new();
+        let mut seen = HashSet::from([State::solved()]);
+        let mut layer = vec![(Sequence::new(), State::solved())];
+        for depth in 0..=MAX_SETUP_LENGTH {
+            let mut next = Vec::new();
+            for (setup, setup_effect) in &layer {
+                let swap = core_effect.conjugate(setup_effect).compose(&side_effect);
+                if let Some(&sticker) = stickers.get(&swap) {
+                    algs.entry(swap).or_insert_with(|| {
+                        let mut moves = setup.clone();
+                        moves.extend(core_moves.iter().cloned());
+                        moves.extend(reversed_sequence(setup));
+                        (sticker, moves)
+                    });
+                }
+                if depth == MAX_SETUP_LENGTH {
+                    continue;
+                }
+                for (m, e) in &setup_moves {
+                    let state = setup_effect.compose(e);
+                    if seen.insert(state.clone()) {
+                        let mut moves = setup.clone();
+                        moves.extend(m.iter().cloned());
+                        next.push((moves, state));
+                    }
+                }
+            }
+            layer = next;
+        }
+
+        for alg in specials {
+            let moves = parse(alg)?;
+            let swap = effect(&moves).compose(&side_effect);
+            let sticker = *stickers
+                .get(&swap)
+                .ok_or_else(|| format!("{} does not swap the buffer with a target", alg))?;
+            algs.insert(swap, (sticker, moves));
+        }
+
+        Ok(Self {
+            buffer,
+            algs,
+            side_effect,
+        })
+    }
+
+    /// コーナーのバッファと各ステッカーの交換
+    fn corner_targets(buffer: usize) -> Vec<(&'static str, State)> {
+        (0..8)
+            .filter(|&p| p != buffer)
+            .flat_map(|p| (0..3).map(move |o| (p, o)))
+            .map(|(p, o)| {
+                let swap = CornerSwapOperation::new(buffer, p, o as u8);
+                (TARGET_STICKERS[p][o], swap.apply(&State::solved()))
+            })
+            .collect()
+    }
+
+    /// エッジのバッファと各ステッカーの交換
+    fn edge_targets(buffer: usize) -> Vec<(&'static str, State)> {
+        (0..12)
+            .filter(|&p| p != buffer)
+            .flat_map(|p| (0..2).map(move |o| (p, o)))
+            .map(|(p, o)| {
+                let swap = EdgeSwapOperation::new(buffer, p, o as u8);
+                (EDGE_TARGET_STICKERS[p][o], swap.apply(&State::solved()))
+            })
+            .collect()
+    }
+
+    /// 交換 `swap` の手順とそれが実際に撃つステッカー
+    ///
+    /// `pending` なら前の手順が残した変化の分だけターゲットをずらす
+    fn shoot(&self, swap: &State, pending: bool) -> Option<(&'static str, &Sequence)> {
+        let swap = if pending {
+            swap.conjugate(&self.side_effect)
+        } else {
+            swap.clone()
+        };
+        self.algs
+            .get(&swap)
+            .map(|(sticker, moves)| (*sticker, moves))
+    }
+}
+
+/// 1つの MoveSequence にまとめる交換の組
+struct Shot {
+    /// ねじれ・反転なら "Twist" / "Flip"
+    label: Option<&'static str>,
+    sticker: &'static str,
+    swaps: Vec<State>,
+}
+
+/// 交換の組を順に撃つ
+///
+/// 交換の数が奇数なら最後の単独の交換を `parity` の手順表で撃つ。`parity` の核の手順が残す変化は
+/// パリティとして検査側が扱う（`SolverConfig::parity_edges`）
+fn convert_shots(
+    algs: &ShootingAlgs,
+    parity: Option<&ShootingAlgs>,
+    shots: &[Shot],
+) -> Result<MoveSequenceCollection, String> {
+    let count: usize = shots.iter().map(|shot| shot.swaps.len()).sum();
+    let parity_index = if count % 2 == 1 {
+        let index = shots
+            .iter()
+            .rposition(|shot| shot.swaps.len() == 1)
+            .ok_or_else(|| "Odd number of targets without a single target".to_string())?;
+        Some(index)
+    } else {
+        None
+    };
+
+    let mut result = MoveSequenceCollection::new();
+    let mut pending = false;
+    for (i, shot) in shots.iter().enumerate() {
+        if parity_index == Some(i) {
+            let parity = parity.ok_or_else(|| {
+                format!("Odd number of targets: no parity alg for {}", shot.sticker)
+            })?;
+            if pending {
+                return Err(format!(
+                    "Parity target {} must follow complete pairs",
+                    shot.sticker
+                ));
+            }
+            let (_, moves) = parity
+                .shoot(&shot.swaps[0], false)
+                .ok_or_else(|| format!("No parity alg for {}", shot.sticker))?;
+            result.push(MoveSequence::new(
+                moves.clone(),
+                format!("Parity: {}", shot.sticker),
+            ));
+            continue;
+        }
+
+        let mut moves = Sequence::new();
+        let mut shot_sticker = shot.sticker;
+        for swap in &shot.swaps {
+            let (sticker, alg) = algs
+                .shoot(swap, pending)
+                .ok_or_else(|| format!("No alg for {}", shot.sticker))?;
+            moves.extend(alg.iter().cloned());
+            shot_sticker = sticker;
+            pending = !pending;
+        }
+        let description = match shot.label {
+            Some(label) => format!("{}: {}", label, shot.sticker),
+            None if shot_sticker != shot.sticker => format!("{} ({})", shot.sticker, shot_sticker),
+            None => shot.sticker.to_string(),
+        };
+        result.push(MoveSequence::new(moves, description));
+    }
+    Ok(result)
+}
+
+/// CornerOperation 列をバッファとの交換の組にする
+///
+/// ねじれはそのコーナーの2つのステッカーを続けて撃つ（バッファのねじれは他のねじれで揃う）
+/// Inspection と同じく、ねじれは交換の後に並んでいること
+fn corner_shots(buffer: usize, operations: &[CornerOperation]) -> Result<Vec<Shot>, String> {
+    let solved = State::solved();
+    let mut shots = Vec::new();
+    for op in operations {
+        match op {
+            CornerOperation::Swap(swap) => {
+                if swap.target1 != buffer {
+                    return Err(format!(
+                        "Corner swap from {} does not use the buffer {}",
+                        CORNER_NAMES[swap.target1], CORNER_NAMES[buffer]
+                    ));
+                }
+                shots.push(Shot {
+                    label: None,
+                    sticker: TARGET_STICKERS[swap.target2][swap.orientation as usize],
+                    swaps: vec![swap.apply(&solved)],
+                });
+            }
+            CornerOperation::Twist(twist) => {
+                if twist.target == buffer {
+                    continue;
+                }
+                let expected = CornerTwistOperation::new(buffer, (3 - twist.orientation) % 3)
+                    .apply(&twist.apply(&solved));
+                let swaps = (0..3)
+                    .flat_map(|x| (0..3).map(move |y| (x, y)))
+                    .map(|(x, y)| {
+                        let first =
+                            CornerSwapOperation::new(buffer, twist.target, x).apply(&solved);
+                        let second = CornerSwapOperation::new(buffer, twist.target, y);
+                        (first.clone(), second.apply(&solved), second.apply(&first))
+                    })
+                    .find(|(_, _, both)| *both == expected)
+                    .map(|(first, second, _)| vec![first, second])
+                    .expect("a twist is two swaps with the buffer");
+                shots.push(Shot {
+                    label: Some("Twist"),
+                    sticker: TWIST_TARGET_STICKERS[twist.target][twist.orientation as usize],
+                    swaps,
+                });
+            }
+        }
+    }
+    Ok(shots)
+}
+
+/// EdgeOperation 列をバッファとの交換の組にする
+///
+/// 反転はそのエッジの2つのステッカーを続けて撃つ（バッファの反転は他の反転で揃う）
+/// Inspection と同じく、反転は交換の後に並んでいること
+fn edge_shots(buffer: usize, operations: &[EdgeOperation]) -> Result<Vec<Shot>, String> {
+    let solved = State::solved();
+    let mut shots = Vec::new();
+    for op in operations {
+        match op {
+            EdgeOperation::Swap(swap) => {
+                if swap.target1 != buffer {
+                    return Err(format!(
+                        "Edge swap from {} does not use the buffer {}",
+                        EDGE_NAMES[swap.target1], EDGE_NAMES[buffer]
+                    ));
+                }
+                shots.push(Shot {
+                    label: None,
+                    sticker: EDGE_TARGET_STICKERS[swap.target2][swap.orientation as usize],
+                    swaps: vec![swap.apply(&solved)],
+                });
+            }
+            EdgeOperation::Flip(flip) => {
+                if flip.target == buffer {
+                    continue;
+                }
+                let expected = EdgeFlipOperation::new(buffer).apply(&flip.apply(&solved));
+                let swaps = (0..2)
+                    .flat_map(|x| (0..2).map(move |y| (x, y)))
+                    .map(|(x, y)| {
+                        let first = EdgeSwapOperation::new(buffer, flip.target, x).apply(&solved);
+                        let second = EdgeSwapOperation::new(buffer, flip.target, y);
+                        (first.clone(), second.apply(&solved), second.apply(&first))
+                    })
+                    .find(|(_, _, both)| *both == expected)
+                    .map(|(first, second, _)| vec![first, second])
+                    .expect("a flip is two swaps with the buffer");
+                shots.push(Shot {
+                    label: Some("Flip"),
+                    sticker: EDGE_FLIP_TARGET_STICKERS[flip.target],
+                    swaps,
+                });
+            }
+        }
+    }
+    Ok(shots)
+}
+
+/// Old Pochmann（コーナーは Y perm、エッジは T perm を setup で使う）
+///
+/// バッファはコーナーが UBL、エッジが UR。Y perm は UB と UL も交換するので、
+/// コーナーの交換が奇数回ならエッジの検査で UB と UL を入れ替えておく（`OldPochmann::solver_config`）
+#[derive(Debug, Clone)]
+pub struct OldPochmann {
+    corners: ShootingAlgs,
+    edges: ShootingAlgs,
+}
+
+impl OldPochmann {
+    pub fn new() -> Result<Self, String> {
+        let corner_buffer = 0;
+        let edge_buffer = 5;
+        Ok(Self {
+            corners: ShootingAlgs::new(
+                corner_buffer,
+                OP_CORNER_ALG,
+                ShootingAlgs::corner_targets(corner_buffer),
+                &[],
+            )?,
+            edges: ShootingAlgs::new(
+                edge_buffer,
+                T_PERM,
+                ShootingAlgs::edge_targets(edge_buffer),
+                &[],
+            )?,
+        })
+    }
+
+    /// Old Pochmann に合わせた SolverConfig（バッファ UBL / UR、パリティのエッジ UB / UL）
+    pub fn solver_config() -> super::SolverConfig {
+        super::SolverConfig::new(0, 5, [4, 7]).expect("UBL / UR buffers are valid")
+    }
+}
+
+impl BldMethod<CornerOperation> for OldPochmann {
+    fn convert(&self, operations: &[CornerOperation]) -> Result<MoveSequenceCollection, String> {
+        let shots = corner_shots(self.corners.buffer, operations)?;
+        convert_shots(&self.corners, Some(&self.corners), &shots)
+    }
+}
+
+impl BldMethod<EdgeOperation> for OldPochmann {
+    fn convert(&self, operations: &[EdgeOperation]) -> Result<MoveSequenceCollection, String> {
+        let shots = edge_shots(self.edges.buffer, operations)?;
+        convert_shots(&self.edges, None, &shots)
+    }
+}
+
+/// M2（エッジ、バッファ DF）
+///
+/// 奇数番目のターゲットが UF / FU / DB / BD なら M2 の後の位置（DB / BD / UF / FU）を撃つ。
+/// パリティはコーナー側の解き方で処理するので、エッジの交換が奇数回ならエラー
+#[derive(Debug, Clone)]
+pub struct M2Edges {
+    algs: ShootingAlgs,
+}
+
+impl M2Edges {
+    pub fn new() -> Result<Self, String> {
+        let buffer = 10;
+        Ok(Self {
+            algs: ShootingAlgs::new(
+                buffer,
+                "M2",
+                ShootingAlgs::edge_targets(buffer),
+                &M2_SPECIAL_ALGS,
+            )?,
+        })
+    }
+}
+
+impl BldMethod<EdgeOperation> for M2Edges {
+    fn convert(&self, operations: &[EdgeOperation]) -> Result<MoveSequenceCollection, String> {
+        let shots = edge_shots(self.algs.buffer, operations)?;
+        convert_shots(&self.algs, None, &shots)
+    }
+}
+
+/// R2（コーナー、バッファ UBR）
+///
+/// 奇数番目のターゲットは R2 の後の位置を撃つ。コーナーの交換が奇数回なら最後のターゲットを
+/// T perm で撃つので、エッジの検査で UR と UL を入れ替えておく（`SolverConfig::parity_edges`）
+#[derive(Debug, Clone)]
+pub struct R2Corners {
+    algs: ShootingAlgs,
+    parity: ShootingAlgs,
+}
+
+impl R2Corners {
+    pub fn new() -> Result<Self, String> {
+        let buffer = 1;
+        Ok(Self {
+            algs: ShootingAlgs::new(
+                buffer,
+                "R2",
+                ShootingAlgs::corner_targets(buffer),
+                &R2_SPECIAL_ALGS,
+            )?,
+            parity: ShootingAlgs::new(buffer, T_PERM, ShootingAlgs::corner_targets(buffer), &[])?,
+        })
+    }
+}
+
+impl BldMethod<CornerOperation> for R2Corners {
+    fn convert(&self, operations: &[CornerOperation]) -> Result<MoveSequenceCollection, String> {
+        let shots = corner_shots(self.algs.buffer, operations)?;
+        convert_shots(&self.algs, Some(&self.parity), &shots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 操作列と手順の効果が一致するか
+    fn assert_solves<Op>(
+        method: &dyn BldMethod<Op>,
+        operations: &[Op],
+        apply: fn(&Op, &State) -> State,
+    ) {
+        let sequences = method.convert(operations).unwrap();
+        let expected = operations
+            .iter()
+            .fold(State::solved(), |state, op| apply(op, &state));
+        assert_eq!(effect(&sequences.flatten_moves()), expected);
+    }
+
+    #[test]
+    fn test_tables_cover_all_targets() {
+        let op = OldPochmann::new().unwrap();
+        assert_eq!(op.corners.algs.len(), 21);
+        assert_eq!(op.edges.algs.len(), 22);
+        assert_eq!(M2Edges::new().unwrap().algs.algs.len(), 22);
+        let r2 = R2Corners::new().unwrap();
+        assert_eq!(r2.algs.algs.len(), 21);
+        assert_eq!(r2.parity.algs.len(), 21);
+    }
+
+    #[test]
+    fn test_m2_odd_targets() {
+        let m2 = M2Edges::new().unwrap();
+        let operations = vec![
+            EdgeOperation::Swap(EdgeSwapOperation::new(10, 2, 0)),
+            EdgeOperation::Swap(EdgeSwapOperation::new(10, 6, 0)),
+        ];
+        let sequences = m2.convert(&operations).unwrap();
+        assert_eq!(sequences.sequences()[0].description, "FR");
+        assert_eq!(sequences.sequences()[1].description, "UF (DB)");
+        assert_solves(&m2, &operations, EdgeOperation::apply);
+
+        let flip = vec![
+            EdgeOperation::Swap(EdgeSwapOperation::new(10, 4, 1)),
+            EdgeOperation::Flip(EdgeFlipOperation::new(8)),
+            EdgeOperation::Flip(EdgeFlipOperation::new(10)),
+            EdgeOperation::Swap(EdgeSwapOperation::new(10, 3, 0)),
+        ];
+        assert_solves(&m2, &flip, EdgeOperation::apply);
+
+        let odd = vec![EdgeOperation::Swap(EdgeSwapOperation::new(10, 2, 0))];
+        assert!(m2.convert(&odd).is_err());
+        let floating = vec![EdgeOperation::Swap(EdgeSwapOperation::new(6, 2, 0))];
+        assert!(m2.convert(&floating).is_err());
+    }
+
+    #[test]
+    fn test_r2_corners_and_parity() {
+        let r2 = R2Corners::new().unwrap();
+        let operations = vec![
+            CornerOperation::Swap(CornerSwapOperation::new(1, 2, 1)),
+            CornerOperation::Swap(CornerSwapOperation::new(1, 5, 0)),
+            CornerOperation::Twist(CornerTwistOperation::new(6, 2)),
+            CornerOperation::Twist(CornerTwistOperation::new(1, 1)),
+        ];
+        assert_solves(&r2, &operations, CornerOperation::apply);
+
+        let odd = vec![CornerOperation::Swap(CornerSwapOperation::new(1, 3, 0))];
+        let sequences = r2.convert(&odd).unwrap();
+        assert_eq!(sequences.sequences()[0].description, "Parity: UFL");
+        // T perm は UR と UL も交換する
+        let parity_edges = EdgeSwapOperation::new(5, 7, 0).apply(&State::solved());
+        let expected = CornerOperation::apply(&odd[0], &State::solved()).compose(&parity_edges);
+        assert_eq!(effect(&sequences.flatten_moves()), expected);
+    }
+
+    #[test]
+    fn test_old_pochmann() {
+        let op = OldPochmann::new().unwrap();
+        let corners = vec![
+            CornerOperation::Swap(CornerSwapOperation::new(0, 6, 1)),
+            CornerOperation::Swap(CornerSwapOperation::new(0, 2, 2)),
+            CornerOperation::Twist(CornerTwistOperation::new(3, 1)),
+            CornerOperation::Twist(CornerTwistOperation::new(0, 2)),
+        ];
+        assert_solves(&op, &corners, CornerOperation::apply);
+        let edges = vec![
+            EdgeOperation::Swap(EdgeSwapOperation::new(5, 9, 1)),
+            EdgeOperation::Swap(EdgeSwapOperation::new(5, 0, 0)),
+            EdgeOperation::Flip(EdgeFlipOperation::new(6)),
+            EdgeOperation::Flip(EdgeFlipOperation::new(5)),
+        ];
+        assert_solves(&op, &edges, EdgeOperation::apply);
+        let odd = vec![EdgeOperation::Swap(EdgeSwapOperation::new(5, 9, 1))];
+        assert!(op.convert(&odd).is_err());
+    }
+}
@@ -1,8 +1,14 @@
+use crate::cube::state::{CORNER_NAMES, CORNER_STICKERS};
 use crate::cube::State;
 use serde::{Deserialize, Serialize};
 
-const BUFFER_PIECE: usize = 2;
-const NEW_LOOP_PRIORITY: [usize; 7] = [1, 3, 0, 4, 5, 6, 7];
+use super::{CycleBreak, SolverConfig};
+
+/// 連続する2つの交換をまとめた手順の長さ（手順が無ければ None）
+///
+/// `CycleBreak::Shortest` で新しいループを始めるコーナーを選ぶのに使う
+pub type CornerPairCost<'a> =
+    dyn Fn(&CornerSwapOperation, &CornerSwapOperation) -> Option<usize> + 'a;
 
 /// コーナーの2点交換操作を表す（co考慮版）
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -37,26 +43,15 @@ impl CornerSwapOperation {
         new_co[self.target1] = new_co_target1;
         new_co[self.target2] = new_co_target2;
 
-        State::new(new_cp, new_co, state.ep, state.eo)
+        State::with_centers(new_cp, new_co, state.ep, state.eo, state.centers)
     }
 }
 
 impl std::fmt::Display for CornerSwapOperation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // target_sticker[corner_index][orientation]
-        const TARGET_STICKERS: [[&str; 3]; 8] = [
-            ["UBL", "BUL", "LUB"], // 0
-            ["UBR", "RUB", "BUR"], // 1
-            ["UFR", "FUR", "RUF"], // 2
-            ["UFL", "LUF", "FUL"], // 3
-            ["DBL", "LDB", "BDL"], // 4
-            ["DBR", "BDR", "RDB"], // 5
-            ["DFR", "RDF", "FDR"], // 6
-            ["DFL", "FDL", "LDF"], // 7
-        ];
-
-        let target1_sticker = TARGET_STICKERS[self.target1][0]; // target1は常に0なのでorientation=0
-        let target2_sticker = TARGET_STICKERS[self.target2][self.orientation as usize];
+        // CORNER_STICKERS[corner_index][orientation]
+        let target1_sticker = CORNER_STICKERS[self.target1][0]; // target1は常に0なのでorientation=0
+        let target2_sticker = CORNER_STICKERS[self.target2][self.orientation as usize];
 
         write!(f, "Swap: {} ↔ {}", target1_sticker, target2_sticker)
     }
@@ -84,21 +79,19 @@ impl CornerTwistOperation {
         let mut new_co = state.co;
         new_co[self.target] = (new_co[self.target] + 3 - self.orientation) % 3;
 
-        State::new(state.cp, new_co, state.ep, state.eo)
+        State::with_centers(state.cp, new_co, state.ep, state.eo, state.centers)
     }
 }
 
 impl std::fmt::Display for CornerTwistOperation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        const TARGET_STICKERS: [&str; 8] = ["UBL", "UBR", "UFR", "UFL", "DBL", "DBR", "DFR", "DFL"];
-
         const ROTATION_DIRECTIONS: [&str; 3] = [
             "noop",              // 0: 回転なし
             "counter-clockwise", // 1: 反時計回り
             "clockwise",         // 2: 時計回り
         ];
 
-        let target_sticker = TARGET_STICKERS[self.target];
+        let target_sticker = CORNER_NAMES[self.target];
         let rotation_direction = ROTATION_DIRECTIONS[self.orientation as usize];
 
         write!(f, "Twist: {} ({})", target_sticker, rotation_direction)
@@ -144,33 +137,74 @@ impl CornerInspection {
     /// # Returns
     /// コーナー操作の列（Swap と Twist）
     pub fn solve_corner_permutation_with_orientation(state: &State) -> Vec<CornerOperation> {
-        // let mut cp = state.cp.clone();
-        // let mut co = state.co.clone();
+        Self::solve_corner_permutation_with_config(state, &SolverConfig::default())
+    }
+
+    /// `config.corner_buffer` をバッファとして cp と co を完成状態に戻すための操作列を計算
+    pub fn solve_corner_permutation_with_config(
+        state: &State,
+        config: &SolverConfig,
+    ) -> Vec<CornerOperation> {
+        Self::solve_corner_permutation_with_pair_cost(state, config, &|_, _| None)
+    }
+
+    /// `pair_cost` で手順の長さを見ながら操作列を計算
+    ///
+    /// `config.cycle_break` が `CycleBreak::Shortest` の場合、新しいループは
+    /// 前後の交換と組にした手順が最も短くなるコーナーから始める
+    ///
+    /// `config.floating_corner_buffers` がある場合、バッファのループが偶数個の交換で
+    /// 閉じたら、揃っていない次のバッファに切り替える。各交換の `target1` は
+    /// その交換を行ったバッファになる
+    pub fn solve_corner_permutation_with_pair_cost(
+        state: &State,
+        config: &SolverConfig,
+        pair_cost: &CornerPairCost,
+    ) -> Vec<CornerOperation> {
+        let mut buffer = config.corner_buffer;
         let mut current_state = state.clone();
         let mut operations = Vec::new();
+        // 今のバッファで行った交換の数
+        let mut swap_count = 0;
 
         loop {
-            // cp[BUFFER_PIECE]との二点交換ループ
-            while current_state.cp[BUFFER_PIECE]
-                != u8::try_from(BUFFER_PIECE).expect("value too large for u8")
-            {
-                let target = current_state.cp[BUFFER_PIECE] as usize;
-                let ori = current_state.co[BUFFER_PIECE]; // 交換前のco[0]を記録
+            // cp[buffer]との二点交換ループ
+            while current_state.cp[buffer] as usize != buffer {
+                let target = current_state.cp[buffer] as usize;
+                let ori = current_state.co[buffer]; // 交換前のco[buffer]を記録
 
                 let operation =
-                    CornerOperation::Swap(CornerSwapOperation::new(BUFFER_PIECE, target, ori));
+                    CornerOperation::Swap(CornerSwapOperation::new(buffer, target, ori));
                 operations.push(operation.clone());
+                swap_count += 1;
 
                 current_state = operation.apply(&current_state);
             }
 
+            // 交換が組になっていれば次のバッファへ
+            if swap_count % 2 == 0 {
+                if let Some(next_buffer) = config
+                    .floating_corner_buffers
+                    .iter()
+                    .copied()
+                    .find(|&b| current_state.cp[b] as usize != b)
+                {
+                    buffer = next_buffer;
+                    swap_count = 0;
+                    continue;
+                }
+            }
+
             // 別ループ探索
-            if let Some(next_index) = Self::find_next_misplaced_cp(&current_state.cp) {
-                let ori = current_state.co[BUFFER_PIECE]; // 交換前のco[BUFFER_PIECE]を記録
+            if let Some(next_index) =
+                Self::choose_cycle_break(&current_state, &operations, buffer, config, pair_cost)
+            {
+                let ori = current_state.co[buffer]; // 交換前のco[buffer]を記録
 
                 let operation =
-                    CornerOperation::Swap(CornerSwapOperation::new(BUFFER_PIECE, next_index, ori));
+                    CornerOperation::Swap(CornerSwapOperation::new(buffer, next_index, ori));
                 operations.push(operation.clone());
+                swap_count += 1;
 
                 current_state = operation.apply(&current_state);
             } else {
@@ -180,28 +214,86 @@ impl CornerInspection {
         }
 
         // co 専用の終了処理
-        for i in 0..8 {
-            if current_state.co[i] != 0 {
-                let operation =
-                    CornerOperation::Twist(CornerTwistOperation::new(i, current_state.co[i]));
+        for i in Self::twist_order(&current_state, config) {
+            let operation =
+                CornerOperation::Twist(CornerTwistOperation::new(i, current_state.co[i]));
 
-                operations.push(operation.clone());
-                current_state = operation.apply(&current_state);
-            }
+            operations.push(operation.clone());
+            current_state = operation.apply(&current_state);
         }
 
         operations
     }
 
-    /// cp[i] ≠ i となる最小の i を探す
-    fn find_next_misplaced_cp(cp: &[u8; 8]) -> Option<usize> {
-        // 1,3,0,4,5,6,7 で最初に cp[i] != i であるもの
-        for i in NEW_LOOP_PRIORITY {
-            if cp[i] != i as u8 {
-                return Some(i);
+    /// ねじれたコーナーを揃える順
+    ///
+    /// `config.pair_orientations` の場合、逆向きにねじれた2つを隣に並べて先に揃え、
+    /// 組にならないものとバッファは後に回す
+    fn twist_order(state: &State, config: &SolverConfig) -> Vec<usize> {
+        let twisted = (0..8).filter(|&i| state.co[i] != 0);
+        if !config.pair_orientations {
+            return twisted.collect();
+        }
+
+        let buffer = config.corner_buffer;
+        let mut remaining: Vec<usize> = twisted.clone().filter(|&i| i != buffer).collect();
+        let mut pairs = Vec::new();
+        let mut singles = Vec::new();
+        while !remaining.is_empty() {
+            let first = remaining.remove(0);
+            match remaining
+                .iter()
+                .position(|&i| state.co[i] + state.co[first] == 3)
+            {
+                Some(j) => pairs.extend([first, remaining.remove(j)]),
+                None => singles.push(first),
             }
         }
-        None
+        pairs.extend(singles);
+        pairs.extend(twisted.filter(|&i| i == buffer));
+        pairs
+    }
+
+    /// 新しいループを始めるコーナーを選ぶ（cp[i] ≠ i となる `buffer` 以外の i）
+    ///
+    /// `config.twist_in_cycle` の場合、その場でねじれたコーナーも候補にする
+    ///
+    /// # Arguments
+    /// * `state` - バッファが揃った状態
+    /// * `operations` - ここまでの交換（奇数個なら新しいループの交換は最後の交換と組になる）
+    /// * `buffer` - 今使っているバッファ
+    fn choose_cycle_break(
+        state: &State,
+        operations: &[CornerOperation],
+        buffer: usize,
+        config: &SolverConfig,
+        pair_cost: &CornerPairCost,
+    ) -> Option<usize> {
+        let mut candidates = config.corner_priority.into_iter().filter(|&i| {
+            i != buffer && (state.cp[i] != i as u8 || (config.twist_in_cycle && state.co[i] != 0))
+        });
+
+        match config.cycle_break {
+            CycleBreak::Priority => candidates.next(),
+            CycleBreak::Shortest => candidates.min_by_key(|&target| {
+                let swap = CornerSwapOperation::new(buffer, target, state.co[buffer]);
+                let cost = match operations.last() {
+                    Some(CornerOperation::Swap(previous)) if operations.len() % 2 == 1 => {
+                        pair_cost(previous, &swap)
+                    }
+                    _ => {
+                        let next_state = swap.apply(state);
+                        let next = CornerSwapOperation::new(
+                            buffer,
+                            next_state.cp[buffer] as usize,
+                            next_state.co[buffer],
+                        );
+                        pair_cost(&swap, &next)
+                    }
+                };
+                cost.unwrap_or(usize::MAX)
+            }),
+        }
     }
 
     /// 操作列を人間が読みやすい形式で出力
@@ -381,4 +473,157 @@ mod tests {
         assert_eq!(current_state.co, [0, 0, 0, 0, 0, 0, 0, 0]);
         assert!(current_state.is_solved());
     }
+
+    #[test]
+    fn test_configured_buffer() {
+        // UBL(0) をバッファにする
+        let config = SolverConfig::new(0, 6, [4, 7]).unwrap();
+        let state = State::new(
+            [3, 1, 0, 2, 5, 7, 4, 6],
+            [1, 0, 2, 0, 0, 1, 0, 2],
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+
+        let operations = CornerInspection::solve_corner_permutation_with_config(&state, &config);
+
+        let mut current_state = state.clone();
+        for op in &operations {
+            if let CornerOperation::Swap(swap) = op {
+                assert_eq!(swap.target1, 0);
+            }
+            current_state = op.apply(&current_state);
+        }
+        assert!(current_state.is_solved());
+    }
+
+    #[test]
+    fn test_cycle_break_priority_and_shortest() {
+        // バッファは揃っていて、(UBL UBR) と (UFL DBL) の2つのループがある
+        let state = State::new(
+            [1, 0, 2, 4, 3, 5, 6, 7],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+        let first_target = |operations: &[CornerOperation]| match &operations[0] {
+            CornerOperation::Swap(swap) => swap.target2,
+            op => panic!("unexpected {}", op),
+        };
+
+        let config = SolverConfig::default();
+        let operations = CornerInspection::solve_corner_permutation_with_config(&state, &config);
+        assert_eq!(first_target(&operations), 1);
+
+        let config = config.with_corner_priority(&[3]).unwrap();
+        let operations = CornerInspection::solve_corner_permutation_with_config(&state, &config);
+        assert_eq!(first_target(&operations), 3);
+
+        // DBL から始める組だけ手順が短い
+        let config = config.with_cycle_break(CycleBreak::Shortest);
+        let cost = |swap1: &CornerSwapOperation, _: &CornerSwapOperation| {
+            Some(if swap1.target2 == 4 { 8 } else { 12 })
+        };
+        let operations =
+            CornerInspection::solve_corner_permutation_with_pair_cost(&state, &config, &cost);
+        assert_eq!(first_target(&operations), 4);
+
+        let mut current_state = state.clone();
+        for op in &operations {
+            current_state = op.apply(&current_state);
+        }
+        assert!(current_state.is_solved());
+    }
+
+    #[test]
+    fn test_floating_buffer() {
+        // UFR のループ (UFR UBL UBR) と、UFL を含むループ (UFL DBL DBR)
+        let state = State::new(
+            [2, 0, 1, 4, 5, 3, 6, 7],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+        let config = SolverConfig::default()
+            .with_floating_corner_buffers(&[3])
+            .unwrap();
+        let operations = CornerInspection::solve_corner_permutation_with_config(&state, &config);
+
+        let buffers: Vec<usize> = operations
+            .iter()
+            .map(|op| match op {
+                CornerOperation::Swap(swap) => swap.target1,
+                op => panic!("unexpected {}", op),
+            })
+            .collect();
+        assert_eq!(buffers, vec![2, 2, 3, 3]);
+
+        let mut current_state = state.clone();
+        for op in &operations {
+            current_state = op.apply(&current_state);
+        }
+        assert!(current_state.is_solved());
+
+        // フローティングしなければ UFR のまま新しいループを始める
+        let operations = CornerInspection::solve_corner_permutation_with_orientation(&state);
+        assert!(operations.iter().all(|op| match op {
+            CornerOperation::Swap(swap) => swap.target1 == 2,
+            _ => true,
+        }));
+        assert_eq!(operations.len(), 6);
+    }
+
+    #[test]
+    fn test_twist_in_cycle() {
+        // cp は完成、UBL と UBR が逆向きにねじれている
+        let state = State::new(
+            [0, 1, 2, 3, 4, 5, 6, 7],
+            [1, 2, 0, 0, 0, 0, 0, 0],
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+        let config = SolverConfig::default().with_twist_in_cycle(true);
+        let operations = CornerInspection::solve_corner_permutation_with_config(&state, &config);
+
+        // 優先順 (UBR, UFL, UBL, ...) でねじれたコーナーからループを始める
+        let targets: Vec<usize> = operations
+            .iter()
+            .map(|op| match op {
+                CornerOperation::Swap(swap) => swap.target2,
+                op => panic!("unexpected {}", op),
+            })
+            .collect();
+        assert_eq!(targets, vec![1, 1, 0, 0]);
+
+        let mut current_state = state.clone();
+        for op in &operations {
+            current_state = op.apply(&current_state);
+        }
+        assert!(current_state.is_solved());
+    }
+
+    #[test]
+    fn test_pair_orientations_order() {
+        // UBL, UBR が反時計回り、UFL と バッファの UFR が時計回り
+        let state = State::new(
+            [0, 1, 2, 3, 4, 5, 6, 7],
+            [1, 1, 2, 2, 0, 0, 0, 0],
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+        let twisted = |config: &SolverConfig| -> Vec<usize> {
+            CornerInspection::solve_corner_permutation_with_config(&state, config)
+                .iter()
+                .map(|op| match op {
+                    CornerOperation::Twist(twist) => twist.target,
+                    op => panic!("unexpected {}", op),
+                })
+                .collect()
+        };
+
+        assert_eq!(twisted(&SolverConfig::default()), vec![0, 1, 2, 3]);
+        // 逆向きの組 (UBL, UFL) が先、組にならない UBR とバッファが後
+        let config = SolverConfig::default().with_pair_orientations(true);
+        assert_eq!(twisted(&config), vec![0, 3, 1, 2]);
+    }
 }
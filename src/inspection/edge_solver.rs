@@ -1,8 +1,13 @@
+use crate::cube::state::{EDGE_NAMES, EDGE_STICKERS};
 use crate::cube::State;
 use serde::{Deserialize, Serialize};
 
-const BUFFER_PIECE: usize = 6;
-const NEW_LOOP_PRIORITY: [usize; 11] = [0, 1, 2, 3, 4, 5, 7, 8, 9, 10, 11];
+use super::{CycleBreak, EdgeParity, SolverConfig};
+
+/// 連続する2つの交換をまとめた手順の長さ（手順が無ければ None）
+///
+/// `CycleBreak::Shortest` で新しいループを始めるエッジを選ぶのに使う
+pub type EdgePairCost<'a> = dyn Fn(&EdgeSwapOperation, &EdgeSwapOperation) -> Option<usize> + 'a;
 
 /// エッジの2点交換操作を表す（eo考慮版）
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -39,30 +44,15 @@ impl EdgeSwapOperation {
         new_eo[self.target1] = (old_eo_target2 + self.orientation) % 2;
         new_eo[self.target2] = (old_eo_target1 + self.orientation) % 2;
 
-        State::new(state.cp, state.co, new_ep, new_eo)
+        State::with_centers(state.cp, state.co, new_ep, new_eo, state.centers)
     }
 }
 
 impl std::fmt::Display for EdgeSwapOperation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // target_sticker[edge_index][orientation]
-        const TARGET_STICKERS: [[&str; 2]; 12] = [
-            ["BL", "LB"], // 0
-            ["BR", "RB"], // 1
-            ["FR", "RF"], // 2
-            ["FL", "LF"], // 3
-            ["UB", "BU"], // 4
-            ["UR", "RU"], // 5
-            ["UF", "FU"], // 6
-            ["UL", "LU"], // 7
-            ["DB", "BD"], // 8
-            ["DR", "RD"], // 9
-            ["DF", "FD"], // 10
-            ["DL", "LD"], // 11
-        ];
-
-        let target1_sticker = TARGET_STICKERS[self.target1][0]; // target1は常に0なのでorientation=0
-        let target2_sticker = TARGET_STICKERS[self.target2][self.orientation as usize];
+        // EDGE_STICKERS[edge_index][orientation]
+        let target1_sticker = EDGE_STICKERS[self.target1][0]; // target1は常に0なのでorientation=0
+        let target2_sticker = EDGE_STICKERS[self.target2][self.orientation as usize];
 
         write!(f, "Swap: {} ↔ {}", target1_sticker, target2_sticker)
     }
@@ -85,22 +75,18 @@ impl EdgeFlipOperation {
         let mut new_eo = state.eo;
         new_eo[self.target] = (new_eo[self.target] + 1) % 2;
 
-        State::new(state.cp, state.co, state.ep, new_eo)
+        State::with_centers(state.cp, state.co, state.ep, new_eo, state.centers)
     }
 }
 
 impl std::fmt::Display for EdgeFlipOperation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        const TARGET_STICKERS: [&str; 12] = [
-            "BL", "BR", "FR", "FL", "UB", "UR", "UF", "UL", "DB", "DR", "DF", "DL",
-        ];
-
         const FLIP_EXISTANCE: [&str; 2] = [
             "not flipped", // 0: フリップなし
             "flipped",     // 1: フリップ
         ];
 
-        let target_sticker = TARGET_STICKERS[self.target];
+        let target_sticker = EDGE_NAMES[self.target];
 
         // 現在の状態からフリップ後の状態を表示
         write!(f, "Flip: {} ({})", target_sticker, FLIP_EXISTANCE[1])
@@ -138,25 +124,6 @@ impl std::fmt::Display for EdgeOperation {
 pub struct EdgeInspection;
 
 impl EdgeInspection {
-    /// 交換分析モードでの値変換を行う
-    /// 
-    /// # Arguments
-    /// * `value` - 変換対象の値
-    /// * `use_swap_inspection` - 交換分析モードを使用するか
-    /// 
-    /// # Returns
-    /// 変換後の値（交換分析モードの場合、5→6, 6→5、それ以外はそのまま）
-    fn convert_value_for_swap_inspection(value: u8, use_swap_inspection: bool) -> u8 {
-        if !use_swap_inspection {
-            return value;
-        }
-        
-        match value {
-            5 => 6,
-            6 => 5,
-            _ => value,
-        }
-    }
     /// ep と eo を完成状態に戻すための操作列を計算
     ///
     /// # Arguments
@@ -170,46 +137,93 @@ impl EdgeInspection {
     /// `use_swap_inspection` が true の場合、ep 配列の値 5 と 6 を入れ替えて扱います。
     /// これにより、UF(6) と UR(5) の交換を特別に扱うことができます。
     pub fn solve_edge_permutation_with_orientation(state: &State, use_swap_inspection: bool) -> Vec<EdgeOperation> {
+        Self::solve_edge_permutation_with_config(state, use_swap_inspection, &SolverConfig::default())
+    }
+
+    /// `config.edge_buffer` をバッファとして ep と eo を完成状態に戻すための操作列を計算
+    ///
+    /// 交換分析モードでは `config.parity_edges` の2つのエッジを入れ替えて扱います。
+    pub fn solve_edge_permutation_with_config(
+        state: &State,
+        use_swap_inspection: bool,
+        config: &SolverConfig,
+    ) -> Vec<EdgeOperation> {
+        let no_cost: &EdgePairCost = &|_, _| None;
+        Self::solve_edge_permutation_with_pair_cost(state, use_swap_inspection, config, no_cost)
+    }
+
+    /// `pair_cost` で手順の長さを見ながら操作列を計算
+    ///
+    /// `config.cycle_break` が `CycleBreak::Shortest` の場合、新しいループは
+    /// 前後の交換と組にした手順が最も短くなるエッジから始める
+    ///
+    /// `config.floating_edge_buffers` がある場合、バッファのループが偶数個の交換で
+    /// 閉じたら、揃っていない次のバッファに切り替える。各交換の `target1` は
+    /// その交換を行ったバッファになる
+    pub fn solve_edge_permutation_with_pair_cost(
+        state: &State,
+        use_swap_inspection: bool,
+        config: &SolverConfig,
+        pair_cost: &EdgePairCost,
+    ) -> Vec<EdgeOperation> {
+        let convert =
+            |value: u8| config.convert_edge_for_swap_inspection(value, use_swap_inspection);
+        let mut buffer = config.edge_buffer;
         let mut current_state = state.clone();
         let mut operations = Vec::new();
+        // 今のバッファで行った交換の数
+        let mut swap_count = 0;
 
         loop {
-            // ep[BUFFER_PIECE]との二点交換ループ
+            // ep[buffer]との二点交換ループ
             // 交換分析モードの場合、値を変換して比較
             loop {
-                let buffer_value = Self::convert_value_for_swap_inspection(
-                    current_state.ep[BUFFER_PIECE],
-                    use_swap_inspection
-                );
-                let expected_buffer_value = u8::try_from(BUFFER_PIECE).expect("value too large for u8");
-                
-                if buffer_value == expected_buffer_value {
+                // 交換先ターゲットを決定（変換後の値を使用）
+                let target = convert(current_state.ep[buffer]) as usize;
+
+                if target == buffer {
                     break;
                 }
-                
-                // 交換先ターゲットを決定（変換後の値を使用）
-                let raw_target_value = current_state.ep[BUFFER_PIECE];
-                let converted_target_value = Self::convert_value_for_swap_inspection(
-                    raw_target_value,
-                    use_swap_inspection
-                );
-                let target = converted_target_value as usize;
-                let ori = current_state.eo[BUFFER_PIECE]; // 交換前のeo[BUFFER_PIECE]を記録
+
+                let ori = current_state.eo[buffer]; // 交換前のeo[buffer]を記録
 
                 let operation =
-                    EdgeOperation::Swap(EdgeSwapOperation::new(BUFFER_PIECE, target, ori));
+                    EdgeOperation::Swap(EdgeSwapOperation::new(buffer, target, ori));
                 operations.push(operation.clone());
+                swap_count += 1;
 
                 current_state = operation.apply(&current_state);
             }
 
+            // 交換が組になっていれば次のバッファへ
+            if swap_count % 2 == 0 {
+                if let Some(next_buffer) = config
+                    .floating_edge_buffers
+                    .iter()
+                    .copied()
+                    .find(|&b| convert(current_state.ep[b]) as usize != b)
+                {
+                    buffer = next_buffer;
+                    swap_count = 0;
+                    continue;
+                }
+            }
+
             // 別ループ探索
-            if let Some(next_index) = Self::find_next_misplaced_ep(&current_state.ep, use_swap_inspection) {
-                let ori = current_state.eo[BUFFER_PIECE]; // 交換前のeo[BUFFER_PIECE]を記録
+            if let Some(next_index) = Self::choose_cycle_break(
+                &current_state,
+                &operations,
+                buffer,
+                use_swap_inspection,
+                config,
+                pair_cost,
+            ) {
+                let ori = current_state.eo[buffer]; // 交換前のeo[buffer]を記録
 
                 let operation =
-                    EdgeOperation::Swap(EdgeSwapOperation::new(BUFFER_PIECE, next_index, ori));
+                    EdgeOperation::Swap(EdgeSwapOperation::new(buffer, next_index, ori));
                 operations.push(operation.clone());
+                swap_count += 1;
 
                 current_state = operation.apply(&current_state);
             } else {
@@ -218,32 +232,78 @@ impl EdgeInspection {
             }
         }
 
+        // パリティ手順表の手順は残った交換に加えてパリティのエッジも入れ替え、
+        // コーナーのパリティ手順まで戻らないので、反転はその入れ替えの後の状態で決める
+        let swaps = operations
+            .iter()
+            .filter(|op| matches!(op, EdgeOperation::Swap(_)))
+            .count();
+        if config.edge_parity == EdgeParity::Table && swaps % 2 == 1 {
+            let [edge1, edge2] = config.parity_edges;
+            current_state = EdgeSwapOperation::new(edge1, edge2, 0).apply(&current_state);
+        }
+
         // eo 専用の終了処理
-        for i in 0..12 {
-            if current_state.eo[i] != 0 {
-                let operation = EdgeOperation::Flip(EdgeFlipOperation::new(i));
+        // 組にして揃える場合、バッファは組の手順表に無いので最後に回す
+        let mut flipped: Vec<usize> = (0..12).filter(|&i| current_state.eo[i] != 0).collect();
+        if config.pair_orientations {
+            flipped.sort_by_key(|&i| i == config.edge_buffer);
+        }
+        for i in flipped {
+            let operation = EdgeOperation::Flip(EdgeFlipOperation::new(i));
 
-                operations.push(operation.clone());
-                current_state = operation.apply(&current_state);
-            }
+            operations.push(operation.clone());
+            current_state = operation.apply(&current_state);
         }
 
         operations
     }
 
-    /// ep[i] ≠ i となる最小の i を探す
-    /// 
+    /// 新しいループを始めるエッジを選ぶ（ep[i] ≠ i となる `buffer` 以外の i）
+    ///
     /// # Arguments
-    /// * `ep` - エッジ配列
+    /// * `state` - バッファが揃った状態
+    /// * `operations` - ここまでの交換（奇数個なら新しいループの交換は最後の交換と組になる）
+    /// * `buffer` - 今使っているバッファ
     /// * `use_swap_inspection` - 交換分析モードを使用するか
-    fn find_next_misplaced_ep(ep: &[u8; 12], use_swap_inspection: bool) -> Option<usize> {
-        for i in NEW_LOOP_PRIORITY {
-            let converted_value = Self::convert_value_for_swap_inspection(ep[i], use_swap_inspection);
-            if converted_value != i as u8 {
-                return Some(i);
-            }
+    /// * `config` - 優先順と選び方
+    /// * `pair_cost` - `CycleBreak::Shortest` で比べる手順の長さ
+    fn choose_cycle_break(
+        state: &State,
+        operations: &[EdgeOperation],
+        buffer: usize,
+        use_swap_inspection: bool,
+        config: &SolverConfig,
+        pair_cost: &EdgePairCost,
+    ) -> Option<usize> {
+        let convert =
+            |value: u8| config.convert_edge_for_swap_inspection(value, use_swap_inspection);
+        let mut candidates = config
+            .edge_priority
+            .into_iter()
+            .filter(|&i| i != buffer && convert(state.ep[i]) != i as u8);
+
+        match config.cycle_break {
+            CycleBreak::Priority => candidates.next(),
+            CycleBreak::Shortest => candidates.min_by_key(|&target| {
+                let swap = EdgeSwapOperation::new(buffer, target, state.eo[buffer]);
+                let cost = match operations.last() {
+                    Some(EdgeOperation::Swap(previous)) if operations.len() % 2 == 1 => {
+                        pair_cost(previous, &swap)
+                    }
+                    _ => {
+                        let next_state = swap.apply(state);
+                        let next = EdgeSwapOperation::new(
+                            buffer,
+                            convert(next_state.ep[buffer]) as usize,
+                            next_state.eo[buffer],
+                        );
+                        pair_cost(&swap, &next)
+                    }
+                };
+                cost.unwrap_or(usize::MAX)
+            }),
         }
-        None
     }
 
     /// 操作列を人間が読みやすい形式で出力
@@ -564,4 +624,62 @@ mod tests {
         println!("\n注意: 交換分析モードでは ep[5]=6, ep[6]=5 が終了状態");
         println!("======================================\n");
     }
+
+    #[test]
+    fn test_configured_buffer_with_swap_inspection() {
+        // DF(10) をバッファにし、パリティでは UB(4) と UL(7) を入れ替える
+        let config = SolverConfig::new(0, 10, [4, 7]).unwrap();
+        let state = State::new(
+            [0, 1, 2, 3, 4, 5, 6, 7],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [1, 0, 2, 3, 10, 5, 6, 7, 8, 9, 4, 11],
+            [0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0],
+        );
+
+        let operations = EdgeInspection::solve_edge_permutation_with_config(&state, true, &config);
+
+        let mut current_state = state.clone();
+        for op in &operations {
+            if let EdgeOperation::Swap(swap) = op {
+                assert_eq!(swap.target1, 10);
+            }
+            current_state = op.apply(&current_state);
+        }
+
+        // 交換分析モードでは UB と UL が入れ替わった状態で終わる
+        let mut expected_ep = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+        expected_ep.swap(4, 7);
+        assert_eq!(current_state.ep, expected_ep);
+        assert_eq!(current_state.eo, [0; 12]);
+    }
+
+    #[test]
+    fn test_floating_buffer() {
+        // UF のループ (UF UB UL) と、UR を含むループ (UR DB DR)
+        let state = State::new(
+            [0, 1, 2, 3, 4, 5, 6, 7],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 1, 2, 3, 6, 8, 7, 4, 9, 5, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+        let config = SolverConfig::default()
+            .with_floating_edge_buffers(&[5])
+            .unwrap();
+        let operations = EdgeInspection::solve_edge_permutation_with_config(&state, false, &config);
+
+        let buffers: Vec<usize> = operations
+            .iter()
+            .map(|op| match op {
+                EdgeOperation::Swap(swap) => swap.target1,
+                op => panic!("unexpected {}", op),
+            })
+            .collect();
+        assert_eq!(buffers, vec![6, 6, 5, 5]);
+
+        let mut current_state = state.clone();
+        for op in &operations {
+            current_state = op.apply(&current_state);
+        }
+        assert!(current_state.is_solved());
+    }
 }
@@ -0,0 +1,242 @@
+use super::{CornerOperation, EdgeOperation};
+use crate::cube::state::{CORNER_STICKERS, EDGE_STICKERS};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Speffz の A-X に対応するコーナーのステッカー（U, L, F, R, B, D の面の順）
+const SPEFFZ_CORNERS: [&str; 24] = [
+    "UBL", "UBR", "UFR", "UFL", "LUB", "LUF", "LDF", "LDB", "FUL", "FUR", "FDR", "FDL", "RUF",
+    "RUB", "RDB", "RDF", "BUR", "BUL", "BDL", "BDR", "DFL", "DFR", "DBR", "DBL",
+];
+
+/// Speffz の A-X に対応するエッジのステッカー
+const SPEFFZ_EDGES: [&str; 24] = [
+    "UB", "UR", "UF", "UL", "LU", "LF", "LD", "LB", "FU", "FR", "FD", "FL", "RU", "RB", "RD", "RF",
+    "BU", "BL", "BD", "BR", "DF", "DR", "DB", "DL",
+];
+
+/// ステッカーの名前から [ピース][向き] を引く
+///
+/// 先頭はステッカーの面、残りはピースの他の面（順不同）なので、"LUB" と "LBU" は同じステッカー
+fn find_sticker<const N: usize>(stickers: &[[&str; N]], name: &str) -> Option<(usize, usize)> {
+    let key = |s: &str| {
+        let mut chars: Vec<char> = s.chars().collect();
+        if chars.is_empty() {
+            return chars;
+        }
+        chars[1..].sort_unstable();
+        chars
+    };
+    let name = key(name);
+    stickers.iter().enumerate().find_map(|(piece, names)| {
+        names
+            .iter()
+            .position(|s| key(s) == name)
+            .map(|orientation| (piece, orientation))
+    })
+}
+
+/// ステッカーと記憶に使う文字の対応（Speffz、かなどのカスタム）
+///
+/// 文字は1文字に限らない文字列なので、かなや漢字も使える
+#[derive(Debug, Clone, PartialEq)]
+pub struct LetterScheme {
+    /// [コーナー][向き] の文字（`CORNER_STICKERS` と同じ並び）
+    corners: [[String; 3]; 8],
+    /// [エッジ][向き] の文字（`EDGE_STICKERS` と同じ並び）
+    edges: [[String; 2]; 12],
+}
+
+impl LetterScheme {
+    /// Speffz（U 面の UBL / UB を A として、U, L, F, R, B, D の面ごとに時計回り）
+    pub fn speffz() -> Self {
+        let letters = ('A'..='X').map(|c| c.to_string());
+        let pairs = SPEFFZ_CORNERS
+            .iter()
+            .chain(SPEFFZ_EDGES.iter())
+            .map(|sticker| sticker.to_string())
+            .zip(letters.clone().chain(letters));
+        Self::from_letters(pairs).expect("Speffz covers every sticker")
+    }
+
+    /// ステッカーと文字の JSON から作成（例: `{"UBL": "A", "UB": "A", ...}`）
+    ///
+    /// コーナー 24 枚とエッジ 24 枚の全てに文字が必要。ステッカーの2文字目以降は順不同
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse letter scheme: {}", e))?;
+        let object = value
+            .as_object()
+            .ok_or_else(|| "Letter scheme must be a JSON object".to_string())?;
+        let pairs = object
+            .iter()
+            .map(|(sticker, letter)| {
+                letter
+                    .as_str()
+                    .map(|letter| (sticker.clone(), letter.to_string()))
+                    .ok_or_else(|| format!("Letter for {} must be a string", sticker))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Self::from_letters(pairs)
+    }
+
+    /// (ステッカー, 文字) の列から作成
+    fn from_letters(pairs: impl IntoIterator<Item = (String, String)>) -> Result<Self, String> {
+        let mut corners: [[Option<String>; 3]; 8] = Default::default();
+        let mut edges: [[Option<String>; 2]; 12] = Default::default();
+        for (sticker, letter) in pairs {
+            if letter.is_empty() {
+                return Err(format!("Letter for {} is empty", sticker));
+            }
+            let slot = if let Some((piece, orientation)) = find_sticker(&CORNER_STICKERS, &sticker)
+            {
+                &mut corners[piece][orientation]
+            } else if let Some((piece, orientation)) = find_sticker(&EDGE_STICKERS, &sticker) {
+                &mut edges[piece][orientation]
+            } else {
+                return Err(format!("Unknown sticker: {}", sticker));
+            };
+            if slot.replace(letter).is_some() {
+                return Err(format!("Duplicate sticker: {}", sticker));
+            }
+        }
+
+        let missing: Vec<&str> = CORNER_STICKERS
+            .iter()
+            .flatten()
+            .zip(corners.iter().flatten())
+            .chain(EDGE_STICKERS.iter().flatten().zip(edges.iter().flatten()))
+            .filter(|(_, letter)| letter.is_none())
+            .map(|(sticker, _)| *sticker)
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("Missing letters for: {}", missing.join(", ")));
+        }
+
+        let mut seen: HashMap<&str, &str> = HashMap::new();
+        for (sticker, letter) in CORNER_STICKERS
+            .iter()
+            .flatten()
+            .zip(corners.iter().flatten())
+        {
+            let letter = letter.as_deref().unwrap_or_default();
+            if let Some(other) = seen.insert(letter, sticker) {
+                return Err(format!(
+                    "Letter {} is used for both {} and {}",
+                    letter, other, sticker
+                ));
+            }
+        }
+        seen.clear();
+        for (sticker, letter) in EDGE_STICKERS.iter().flatten().zip(edges.iter().flatten()) {
+            let letter = letter.as_deref().unwrap_or_default();
+            if let Some(other) = seen.insert(letter, sticker) {
+                return Err(format!(
+                    "Letter {} is used for both {} and {}",
+                    letter, other, sticker
+                ));
+            }
+        }
+
+        Ok(Self {
+            corners: corners.map(|c| c.map(Option::unwrap_or_default)),
+            edges: edges.map(|e| e.map(Option::unwrap_or_default)),
+        })
+    }
+
+    /// コーナーのステッカー `CORNER_STICKERS[corner][orientation]` の文字
+    pub fn corner_letter(&self, corner: usize, orientation: usize) -> &str {
+        &self.corners[corner][orientation]
+    }
+
+    /// エッジのステッカー `EDGE_STICKERS[edge][orientation]` の文字
+    pub fn edge_letter(&self, edge: usize, orientation: usize) -> &str {
+        &self.edges[edge][orientation]
+    }
+
+    /// 操作のターゲットの文字
+    ///
+    /// 交換は移動先のステッカー、ねじれは手順表と同じステッカー（`Twist: FUL` の FUL）
+    pub fn corner_target(&self, operation: &CornerOperation) -> &str {
+        match operation {
+            CornerOperation::Swap(swap) => {
+                self.corner_letter(swap.target2, swap.orientation as usize)
+            }
+            CornerOperation::Twist(twist) => {
+                self.corner_letter(twist.target, (3 - twist.orientation as usize) % 3)
+            }
+        }
+    }
+
+    /// 操作のターゲットの文字（反転は U/D または F/B のステッカー）
+    pub fn edge_target(&self, operation: &EdgeOperation) -> &str {
+        match operation {
+            EdgeOperation::Swap(swap) => self.edge_letter(swap.target2, swap.orientation as usize),
+            EdgeOperation::Flip(flip) => self.edge_letter(flip.target, 0),
+        }
+    }
+}
+
+impl Default for LetterScheme {
+    fn default() -> Self {
+        Self::speffz()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inspection::{CornerTwistOperation, EdgeSwapOperation};
+
+    #[test]
+    fn test_speffz() {
+        let speffz = LetterScheme::speffz();
+        assert_eq!(speffz.corner_letter(0, 0), "A"); // UBL
+        assert_eq!(speffz.corner_letter(5, 2), "O"); // RDB
+        assert_eq!(speffz.edge_letter(4, 1), "Q"); // BU
+        assert_eq!(speffz.edge_letter(11, 0), "X"); // DL
+        let twist = CornerOperation::Twist(CornerTwistOperation::new(3, 1));
+        assert_eq!(speffz.corner_target(&twist), "I"); // Twist: FUL
+        let swap = EdgeOperation::Swap(EdgeSwapOperation::new(6, 9, 1));
+        assert_eq!(speffz.edge_target(&swap), "O"); // RD
+    }
+
+    #[test]
+    fn test_kana_from_json() {
+        let kana =
+            LetterScheme::from_json(include_str!("../../resources/kana_scheme.json")).unwrap();
+        assert_eq!(kana.corner_letter(0, 0), "あ");
+        assert_eq!(kana.edge_letter(11, 0), "ね");
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        let mut letters: serde_json::Map<String, Value> = SPEFFZ_CORNERS
+            .iter()
+            .chain(SPEFFZ_EDGES.iter())
+            .zip(('A'..='X').chain('A'..='X'))
+            .map(|(sticker, letter)| (sticker.to_string(), Value::from(letter.to_string())))
+            .collect();
+        // 2文字目以降の順番は問わない
+        letters.remove("LUB");
+        letters.insert("LBU".to_string(), Value::from("E"));
+        let json = Value::Object(letters.clone()).to_string();
+        assert_eq!(
+            LetterScheme::from_json(&json).unwrap(),
+            LetterScheme::speffz()
+        );
+
+        let mut missing = letters.clone();
+        missing.remove("DL");
+        let err = LetterScheme::from_json(&Value::Object(missing).to_string()).unwrap_err();
+        assert!(err.contains("DL"), "{}", err);
+
+        let mut duplicate = letters.clone();
+        duplicate.insert("UBR".to_string(), Value::from("A"));
+        assert!(LetterScheme::from_json(&Value::Object(duplicate).to_string()).is_err());
+
+        let mut unknown = letters;
+        unknown.insert("XYZ".to_string(), Value::from("Z"));
+        assert!(LetterScheme::from_json(&Value::Object(unknown).to_string()).is_err());
+    }
+}
@@ -1,7 +1,18 @@
+mod bld_method;
 mod corner_solver;
 mod edge_solver;
+mod letter_scheme;
 mod operations_to_turns;
+mod sheet_verifier;
+mod solver_config;
+mod table_error;
 
-pub use corner_solver::{CornerSwapOperation, CornerTwistOperation, CornerOperation, CornerInspection};
-pub use edge_solver::{EdgeSwapOperation, EdgeFlipOperation, EdgeOperation, EdgeInspection};
+pub use bld_method::{BldMethod, BldMethods, M2Edges, OldPochmann, R2Corners};
+pub use corner_solver::{CornerSwapOperation, CornerTwistOperation, CornerOperation, CornerInspection, CornerPairCost};
+pub use edge_solver::{EdgeSwapOperation, EdgeFlipOperation, EdgeOperation, EdgeInspection, EdgePairCost};
+pub use letter_scheme::LetterScheme;
 pub use operations_to_turns::{MoveSequence, MoveSequenceCollection, OperationsToTurns};
+pub(crate) use operations_to_turns::EDGE_TARGET_STICKERS;
+pub use sheet_verifier::{SheetEntry, SheetReport};
+pub use solver_config::{CycleBreak, EdgeParity, SolverConfig};
+pub use table_error::TableError;
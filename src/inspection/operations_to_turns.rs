@@ -1,10 +1,15 @@
 use super::{
     CornerOperation, CornerSwapOperation, CornerTwistOperation, EdgeFlipOperation, EdgeOperation,
-    EdgeSwapOperation,
+    EdgeParity, EdgeSwapOperation, SolverConfig, TableError,
+};
+use crate::cube::state::{CORNER_NAMES, EDGE_NAMES};
+use crate::parser::{
+    parse_3style_csv, parse_alg_list_csv, parse_and_expand, parse_sequence, simplify, MoveCount,
+    ParseError, ParseLocation, Sequence,
 };
-use crate::parser::{parse_sequence, Sequence};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::Path;
 
 /// 一連の操作から得られた手順
 #[derive(Debug, Clone, PartialEq)]
@@ -30,6 +35,16 @@ impl MoveSequence {
     pub fn is_empty(&self) -> bool {
         self.moves.is_empty()
     }
+
+    /// 手数（HTM, QTM, STM, ETM）
+    pub fn move_count(&self) -> MoveCount {
+        MoveCount::of(&self.moves)
+    }
+
+    /// 手順内で打ち消し合う手をまとめた後の手数
+    pub fn simplified_move_count(&self) -> MoveCount {
+        MoveCount::of_simplified(&self.moves)
+    }
 }
 
 impl std::fmt::Display for MoveSequence {
@@ -103,6 +118,43 @@ impl MoveSequenceCollection {
             .collect()
     }
 
+    /// すべてのmovesを結合し、打ち消し合う手をまとめたSequenceを取得
+    pub fn simplified_moves(&self) -> Sequence {
+        simplify(&self.flatten_moves())
+    }
+
+    /// 全ての手順をそのまま結合した手数
+    pub fn move_count(&self) -> MoveCount {
+        MoveCount::of(&self.flatten_moves())
+    }
+
+    /// 境界で打ち消し合う手をまとめた後の手数
+    pub fn simplified_move_count(&self) -> MoveCount {
+        MoveCount::of(&self.simplified_moves())
+    }
+
+    /// 各MoveSequenceの境界で打ち消し合った手数
+    ///
+    /// i 番目の値は i 番目と i + 1 番目の境界で消えた手数。前から順に結合して
+    /// 簡約するので、1 つの手順が丸ごと消えて更に前の手順と打ち消し合う場合も数える。
+    /// 手順自体の中の簡約は含まない
+    pub fn boundary_cancellations(&self) -> Vec<usize> {
+        let mut cancellations = Vec::new();
+        let mut joined: Sequence = Vec::new();
+
+        for (i, seq) in self.sequences.iter().enumerate() {
+            let own = simplify(&seq.moves);
+            let before = joined.len() + own.len();
+            joined.extend(own);
+            joined = simplify(&joined);
+            if i > 0 {
+                cancellations.push(before - joined.len());
+            }
+        }
+
+        cancellations
+    }
+
     /// すべてのdescriptionを結合した文字列を取得
     pub fn flatten_description(&self, separator: &str) -> String {
         self.sequences
@@ -166,20 +218,12 @@ impl<'a> IntoIterator for &'a MoveSequenceCollection {
     }
 }
 
-/// 2点交換における TARGET_STICKER の定義
-const TARGET_STICKERS: [[&str; 3]; 8] = [
-    ["UBL", "BUL", "LUB"], // 0
-    ["UBR", "RUB", "BUR"], // 1
-    ["UFR", "FUR", "RUF"], // 2
-    ["UFL", "LUF", "FUL"], // 3
-    ["DBL", "LDB", "BDL"], // 4
-    ["DBR", "BDR", "RDB"], // 5
-    ["DFR", "RDF", "FDR"], // 6
-    ["DFL", "FDL", "LDF"], // 7
-];
+// 2点交換の TARGET_STICKER はステッカーの並び（`[ピース][向き]`）そのもの
+pub(super) use crate::cube::state::CORNER_STICKERS as TARGET_STICKERS;
+pub(crate) use crate::cube::state::EDGE_STICKERS as EDGE_TARGET_STICKERS;
 
 /// Twist における TARGET_STICKER の定義
-const TWIST_TARGET_STICKERS: [[&str; 3]; 8] = [
+pub(super) const TWIST_TARGET_STICKERS: [[&str; 3]; 8] = [
     ["UBL", "LUB", "BUL"], // 0
     ["UBR", "BUR", "RUB"], // 1
     ["UFR", "RUF", "FUR"], // 2
@@ -190,24 +234,8 @@ const TWIST_TARGET_STICKERS: [[&str; 3]; 8] = [
     ["DFL", "LDF", "FDL"], // 7
 ];
 
-/// エッジ交換における TARGET_STICKER の定義 (Swap)
-const EDGE_TARGET_STICKERS: [[&str; 2]; 12] = [
-    ["BL", "LB"], // 0
-    ["BR", "RB"], // 1
-    ["FR", "RF"], // 2
-    ["FL", "LF"], // 3
-    ["UB", "BU"], // 4
-    ["UR", "RU"], // 5
-    ["UF", "FU"], // 6
-    ["UL", "LU"], // 7
-    ["DB", "BD"], // 8
-    ["DR", "RD"], // 9
-    ["DF", "FD"], // 10
-    ["DL", "LD"], // 11
-];
-
 /// エッジフリップにおける TARGET_STICKER の定義 (Flip)
-const EDGE_FLIP_TARGET_STICKERS: [&str; 12] = [
+pub(super) const EDGE_FLIP_TARGET_STICKERS: [&str; 12] = [
     "BL", // 0
     "BR", // 1
     "FR", // 2
@@ -223,8 +251,14 @@ const EDGE_FLIP_TARGET_STICKERS: [&str; 12] = [
 ];
 
 /// JSONファイルを読み込んでHashMapに変換
-fn load_json_to_map(json_str: &str) -> Result<HashMap<String, HashMap<String, String>>, String> {
-    let json: Value = serde_json::from_str(json_str).map_err(|e| e.to_string())?;
+fn load_json_to_map(
+    table: &str,
+    json_str: &str,
+) -> Result<HashMap<String, HashMap<String, String>>, TableError> {
+    let json: Value = serde_json::from_str(json_str).map_err(|source| TableError::Json {
+        table: table.to_string(),
+        source,
+    })?;
 
     let mut result = HashMap::new();
 
@@ -245,40 +279,129 @@ fn load_json_to_map(json_str: &str) -> Result<HashMap<String, HashMap<String, St
     Ok(result)
 }
 
+/// 1階層の手順表の JSON を読み込む
+fn load_json_to_list(table: &str, json_str: &str) -> Result<HashMap<String, String>, TableError> {
+    serde_json::from_str(json_str).map_err(|source| TableError::Json {
+        table: table.to_string(),
+        source,
+    })
+}
+
+/// CSV シートを読めなかったエラー（読めない手順なら `ParseError` のまま）
+fn csv_error(path: &Path, err: Box<dyn std::error::Error>) -> TableError {
+    match err.downcast::<ParseError>() {
+        Ok(source) => TableError::Parse {
+            path: Some(path.to_path_buf()),
+            source,
+        },
+        Err(err) => TableError::Csv {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        },
+    }
+}
+
+/// 2階層の手順表の各手順をパースする
+///
+/// 読めない手順があれば、表の名前と "UBR-FDR" の形のステッカーの組をエラーに付ける
+fn parse_pair_table(
+    table: &str,
+    map: HashMap<String, HashMap<String, String>>,
+    parse: fn(&str) -> Result<Sequence, ParseError>,
+) -> Result<HashMap<String, HashMap<String, Sequence>>, ParseError> {
+    map.into_iter()
+        .map(|(first, inner)| {
+            let inner = inner
+                .into_iter()
+                .map(|(second, move_str)| {
+                    let key = format!("{}-{}", first, second);
+                    let moves = parse(&move_str).map_err(|e| {
+                        e.at(ParseLocation::Sticker {
+                            table: table.to_string(),
+                            key,
+                        })
+                    })?;
+                    Ok((second, moves))
+                })
+                .collect::<Result<_, ParseError>>()?;
+            Ok((first, inner))
+        })
+        .collect()
+}
+
+/// 1階層の手順表（parity, twist, flip）の各手順をパースする
+fn parse_single_table(
+    table: &str,
+    map: HashMap<String, String>,
+    parse: fn(&str) -> Result<Sequence, ParseError>,
+) -> Result<HashMap<String, Sequence>, ParseError> {
+    map.into_iter()
+        .map(|(key, move_str)| {
+            let moves = parse(&move_str).map_err(|e| {
+                e.at(ParseLocation::Sticker {
+                    table: table.to_string(),
+                    key: key.clone(),
+                })
+            })?;
+            Ok((key, moves))
+        })
+        .collect()
+}
+
+/// 順番を問わない組の手順表から `first` と `second` の手順を探す
+fn find_unordered_pair<'a>(
+    table: &'a HashMap<String, HashMap<String, Sequence>>,
+    first: &str,
+    second: &str,
+) -> Option<&'a Sequence> {
+    let get = |a: &str, b: &str| table.get(a)?.get(b).filter(|moves| !moves.is_empty());
+    get(first, second).or_else(|| get(second, first))
+}
+
 /// コーナーとエッジ操作列を手順列に変換する
 #[derive(Clone)]
 pub struct OperationsToTurns {
     // Corner data
-    ufr_expanded: HashMap<String, HashMap<String, String>>,
-    ufr_parity: HashMap<String, String>,
-    ufr_twist: HashMap<String, String>,
+    pub(super) ufr_expanded: HashMap<String, HashMap<String, Sequence>>,
+    pub(super) ufr_parity: HashMap<String, Sequence>,
+    pub(super) ufr_twist: HashMap<String, Sequence>,
     // Edge data
-    uf_expanded: HashMap<String, HashMap<String, String>>,
-    uf_flip: HashMap<String, String>,
+    pub(super) uf_expanded: HashMap<String, HashMap<String, Sequence>>,
+    pub(super) uf_flip: HashMap<String, Sequence>,
+    // EdgeParity::Table で使うエッジのパリティ手順
+    pub(super) uf_parity: HashMap<String, Sequence>,
+    // config.pair_orientations で使う2つのねじれ・反転を揃える手順
+    pub(super) ufr_twist_pairs: HashMap<String, HashMap<String, Sequence>>,
+    pub(super) uf_flip_pairs: HashMap<String, HashMap<String, Sequence>>,
+    // フローティングバッファごとの手順表（キーはバッファ）
+    pub(super) floating_corner_expanded: HashMap<usize, HashMap<String, HashMap<String, Sequence>>>,
+    pub(super) floating_corner_parity: HashMap<usize, HashMap<String, Sequence>>,
+    pub(super) floating_edge_expanded: HashMap<usize, HashMap<String, HashMap<String, Sequence>>>,
+    // 手順表が前提とするバッファ
+    pub(super) config: SolverConfig,
 }
 
 impl OperationsToTurns {
     /// JSONファイルの内容を受け取って初期化
+    ///
+    /// 全ての手順をここでパースし、読めない手順があればその表とステッカーを
+    /// `ParseLocation::Sticker` に持つ `TableError::Parse` を返す
     pub fn new(
         ufr_expanded_json: &str,
         ufr_parity_json: &str,
         ufr_twist_json: &str,
         uf_expanded_json: &str,
         uf_flip_json: &str,
-    ) -> Result<Self, String> {
-        let ufr_expanded = load_json_to_map(ufr_expanded_json)?;
+    ) -> Result<Self, TableError> {
+        let ufr_expanded = load_json_to_map("ufr_expanded", ufr_expanded_json)?;
 
         // parity と twist は1階層のJSON
-        let ufr_parity: HashMap<String, String> = serde_json::from_str(ufr_parity_json)
-            .map_err(|e| format!("Failed to parse ufr_parity: {}", e))?;
-
-        let ufr_twist: HashMap<String, String> = serde_json::from_str(ufr_twist_json)
-            .map_err(|e| format!("Failed to parse ufr_twist: {}", e))?;
+        let ufr_parity = load_json_to_list("ufr_parity", ufr_parity_json)?;
+        let ufr_twist = load_json_to_list("ufr_twist", ufr_twist_json)?;
 
         // Edge data loading
-        let uf_expanded = load_json_to_map(uf_expanded_json)?;
-        let uf_flip: HashMap<String, String> = serde_json::from_str(uf_flip_json)
-            .map_err(|e| format!("Failed to parse uf_flip: {}", e))?;
+        let uf_expanded = load_json_to_map("uf_expanded", uf_expanded_json)?;
+        let uf_flip = load_json_to_list("uf_flip", uf_flip_json)?;
 
         // print uft_paruity for debug
         // println!("ufr_parity: {:?}", ufr_parity);
@@ -288,14 +411,201 @@ impl OperationsToTurns {
         // println!("uf_flip: {:?}", uf_flip);
 
         Ok(Self {
-            ufr_expanded,
-            ufr_parity,
-            ufr_twist,
-            uf_expanded,
-            uf_flip,
+            ufr_expanded: parse_pair_table("ufr_expanded", ufr_expanded, parse_sequence)?,
+            ufr_parity: parse_single_table("ufr_parity", ufr_parity, parse_sequence)?,
+            ufr_twist: parse_single_table("ufr_twist", ufr_twist, parse_sequence)?,
+            uf_expanded: parse_pair_table("uf_expanded", uf_expanded, parse_sequence)?,
+            uf_flip: parse_single_table("uf_flip", uf_flip, parse_sequence)?,
+            uf_parity: HashMap::new(),
+            ufr_twist_pairs: HashMap::new(),
+            uf_flip_pairs: HashMap::new(),
+            floating_corner_expanded: HashMap::new(),
+            floating_corner_parity: HashMap::new(),
+            floating_edge_expanded: HashMap::new(),
+            config: SolverConfig::default(),
+        })
+    }
+
+    /// 3-style の CSV シートから直接初期化
+    ///
+    /// `ufr_csv` / `uf_csv` は `parse_3style_csv` の形式、パリティ・ツイスト・フリップは
+    /// `parse_alg_list_csv` の形式。`[A: [B, C]]` などの記法は読み込み時に
+    /// `parse_and_expand` で展開するので、展開済みの JSON を作り直す必要はない。
+    /// 読めない手順は、そのファイルとセル（`ParseLocation::Cell`）を持つ `TableError::Parse`
+    pub fn from_csv_sheets<P: AsRef<Path>>(
+        ufr_csv: P,
+        ufr_parity_csv: P,
+        ufr_twist_csv: P,
+        uf_csv: P,
+        uf_flip_csv: P,
+    ) -> Result<Self, TableError> {
+        let read_pairs = |path: P| parse_3style_csv(&path).map_err(|e| csv_error(path.as_ref(), e));
+        let read_list =
+            |path: P| parse_alg_list_csv(&path).map_err(|e| csv_error(path.as_ref(), e));
+
+        Ok(Self {
+            ufr_expanded: parse_pair_table("ufr_expanded", read_pairs(ufr_csv)?, parse_and_expand)?,
+            ufr_parity: parse_single_table(
+                "ufr_parity",
+                read_list(ufr_parity_csv)?,
+                parse_and_expand,
+            )?,
+            ufr_twist: parse_single_table(
+                "ufr_twist",
+                read_list(ufr_twist_csv)?,
+                parse_and_expand,
+            )?,
+            uf_expanded: parse_pair_table("uf_expanded", read_pairs(uf_csv)?, parse_and_expand)?,
+            uf_flip: parse_single_table("uf_flip", read_list(uf_flip_csv)?, parse_and_expand)?,
+            uf_parity: HashMap::new(),
+            ufr_twist_pairs: HashMap::new(),
+            uf_flip_pairs: HashMap::new(),
+            floating_corner_expanded: HashMap::new(),
+            floating_corner_parity: HashMap::new(),
+            floating_edge_expanded: HashMap::new(),
+            config: SolverConfig::default(),
         })
     }
 
+    /// 手順表が前提とするバッファを設定（デフォルトは UFR / UF）
+    pub fn with_config(mut self, config: SolverConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 手順表が前提とするバッファ
+    pub fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    /// `EdgeParity::Table` で使うエッジのパリティ手順表を追加
+    ///
+    /// JSON の形式は `ufr_parity` と同じで、キーはターゲットのステッカー。手順は
+    /// バッファとターゲットのエッジの交換に加えて `config.parity_edges` の2つを交換するもの
+    pub fn with_edge_parity_table(mut self, uf_parity_json: &str) -> Result<Self, TableError> {
+        let uf_parity = load_json_to_list("uf_parity", uf_parity_json)?;
+        self.uf_parity = parse_single_table("uf_parity", uf_parity, parse_sequence)?;
+        Ok(self)
+    }
+
+    /// `config.pair_orientations` で使う、2つのコーナーのねじれを揃える手順表を追加
+    ///
+    /// JSON の形式は `ufr_expanded` と同じで、キーは `ufr_twist` と同じねじれのステッカー。
+    /// 手順はバッファを動かさずに2つのコーナーだけをねじるもので、
+    /// 2つのステッカーはどちらの順でもよい
+    pub fn with_twist_pair_table(mut self, ufr_twist_pairs_json: &str) -> Result<Self, TableError> {
+        let twist_pairs = load_json_to_map("ufr_twist_pairs", ufr_twist_pairs_json)?;
+        self.ufr_twist_pairs = parse_pair_table("ufr_twist_pairs", twist_pairs, parse_sequence)?;
+        Ok(self)
+    }
+
+    /// `config.pair_orientations` で使う、2つのエッジの反転を揃える手順表を追加
+    ///
+    /// JSON の形式は `uf_expanded` と同じで、キーは `uf_flip` と同じエッジの名前。
+    /// 手順はバッファを動かさずに2つのエッジだけを反転するもので、
+    /// 2つのエッジはどちらの順でもよい
+    pub fn with_flip_pair_table(mut self, uf_flip_pairs_json: &str) -> Result<Self, TableError> {
+        let flip_pairs = load_json_to_map("uf_flip_pairs", uf_flip_pairs_json)?;
+        self.uf_flip_pairs = parse_pair_table("uf_flip_pairs", flip_pairs, parse_sequence)?;
+        Ok(self)
+    }
+
+    /// フローティングバッファ `buffer` からのコーナーの手順表を追加
+    ///
+    /// JSON の形式は `ufr_expanded` / `ufr_parity` と同じで、パリティ手順は
+    /// `config.parity_edges` の2つのエッジを交換するもの
+    pub fn with_corner_buffer_sheet(
+        mut self,
+        buffer: usize,
+        expanded_json: &str,
+        parity_json: &str,
+    ) -> Result<Self, TableError> {
+        let name = CORNER_NAMES
+            .get(buffer)
+            .ok_or(TableError::InvalidBuffer {
+                piece: "corner",
+                buffer,
+            })?
+            .to_lowercase();
+        let (expanded_table, parity_table) =
+            (format!("{}_expanded", name), format!("{}_parity", name));
+
+        let expanded = load_json_to_map(&expanded_table, expanded_json)?;
+        let parity = load_json_to_list(&parity_table, parity_json)?;
+
+        self.floating_corner_expanded.insert(
+            buffer,
+            parse_pair_table(&expanded_table, expanded, parse_sequence)?,
+        );
+        self.floating_corner_parity.insert(
+            buffer,
+            parse_single_table(&parity_table, parity, parse_sequence)?,
+        );
+        Ok(self)
+    }
+
+    /// フローティングバッファ `buffer` からのエッジの手順表を追加
+    ///
+    /// JSON の形式は `uf_expanded` と同じ
+    pub fn with_edge_buffer_sheet(
+        mut self,
+        buffer: usize,
+        expanded_json: &str,
+    ) -> Result<Self, TableError> {
+        let name = EDGE_NAMES
+            .get(buffer)
+            .ok_or(TableError::InvalidBuffer {
+                piece: "edge",
+                buffer,
+            })?
+            .to_lowercase();
+        let table = format!("{}_expanded", name);
+
+        let expanded = load_json_to_map(&table, expanded_json)?;
+        self.floating_edge_expanded
+            .insert(buffer, parse_pair_table(&table, expanded, parse_sequence)?);
+        Ok(self)
+    }
+
+    /// `buffer` からの2点交換2つ分の手順表
+    pub(super) fn corner_pair_table(
+        &self,
+        buffer: usize,
+    ) -> Result<&HashMap<String, HashMap<String, Sequence>>, String> {
+        if buffer == self.config.corner_buffer {
+            return Ok(&self.ufr_expanded);
+        }
+        self.floating_corner_expanded
+            .get(&buffer)
+            .ok_or_else(|| format!("No alg table for corner buffer {}", CORNER_NAMES[buffer]))
+    }
+
+    /// `buffer` からのパリティの手順表
+    pub(super) fn corner_parity_table(
+        &self,
+        buffer: usize,
+    ) -> Result<&HashMap<String, Sequence>, String> {
+        if buffer == self.config.corner_buffer {
+            return Ok(&self.ufr_parity);
+        }
+        self.floating_corner_parity
+            .get(&buffer)
+            .ok_or_else(|| format!("No parity table for corner buffer {}", CORNER_NAMES[buffer]))
+    }
+
+    /// `buffer` からのエッジの2点交換2つ分の手順表
+    pub(super) fn edge_pair_table(
+        &self,
+        buffer: usize,
+    ) -> Result<&HashMap<String, HashMap<String, Sequence>>, String> {
+        if buffer == self.config.edge_buffer {
+            return Ok(&self.uf_expanded);
+        }
+        self.floating_edge_expanded
+            .get(&buffer)
+            .ok_or_else(|| format!("No alg table for edge buffer {}", EDGE_NAMES[buffer]))
+    }
+
     /// CornerOperation列を MoveSequenceCollection に変換
     pub fn convert(
         &self,
@@ -324,15 +634,35 @@ impl OperationsToTurns {
                 }
             }
 
-            // 2. 1つの Swap を変換
+            // 2. 組にならない Swap を変換
             if let CornerOperation::Swap(swap) = &operations[i] {
+                if let Some(CornerOperation::Swap(next)) = operations.get(i + 1) {
+                    return Err(format!(
+                        "Corner alg not found: {} → {}",
+                        TARGET_STICKERS[swap.target2][swap.orientation as usize],
+                        TARGET_STICKERS[next.target2][next.orientation as usize]
+                    ));
+                }
                 let seq = self.convert_single_swap(swap)?;
                 result.push(seq);
                 i += 1;
                 continue;
             }
 
-            // 3. 1つの Twist を変換
+            // 3. 連続する2つの Twist を組の手順表で変換できるか試す
+            if self.config.pair_orientations && i + 1 < operations.len() {
+                if let (CornerOperation::Twist(twist1), CornerOperation::Twist(twist2)) =
+                    (&operations[i], &operations[i + 1])
+                {
+                    if let Some(seq) = self.try_convert_two_twists(twist1, twist2) {
+                        result.push(seq);
+                        i += 2;
+                        continue;
+                    }
+                }
+            }
+
+            // 4. 1つの Twist を変換
             if let CornerOperation::Twist(twist) = &operations[i] {
                 let seq = self.convert_twist(twist)?;
                 result.push(seq);
@@ -349,41 +679,63 @@ impl OperationsToTurns {
     /// 連続する2つのSwapを変換
     ///
     /// # 前提条件
-    /// - swap1.target1 と swap2.target1 は両方とも BUFFER_PIECE (2 = UFR) であること
+    /// - swap1.target1 と swap2.target1 は同じバッファであること
     /// - これは CornerInspection::solve_corner_permutation_with_orientation の
-    ///   アルゴリズムによって保証される
+    ///   アルゴリズムによって保証される（フローティングでも組の途中でバッファは変わらない）
     fn try_convert_two_swaps(
         &self,
         swap1: &CornerSwapOperation,
         swap2: &CornerSwapOperation,
     ) -> Result<Option<MoveSequence>, String> {
-        // target1 は両方とも BUFFER_PIECE (2) であることを前提とする
+        let buffer = swap1.target1;
+        if swap2.target1 != buffer {
+            return Err(format!(
+                "Cannot pair swaps from different buffers: {} and {}",
+                CORNER_NAMES[buffer], CORNER_NAMES[swap2.target1]
+            ));
+        }
+        let table = self.corner_pair_table(buffer)?;
+
         // swap1.target2 と swap2.target2 の orientation を使って target_sticker を決定
         let target_sticker1 =
             TARGET_STICKERS[swap1.target2][swap1.orientation as usize].to_string();
         let target_sticker2 =
             TARGET_STICKERS[swap2.target2][swap2.orientation as usize].to_string();
 
-        // ufr_expanded から target_sticker1 → target_sticker2 の手順を取得
-        if let Some(inner_map) = self.ufr_expanded.get(&target_sticker1) {
-            if let Some(move_str) = inner_map.get(&target_sticker2) {
-                let moves = parse_sequence(move_str)?;
-                let description = format!("{} → {}", target_sticker1, target_sticker2);
-                return Ok(Some(MoveSequence::new(moves, description)));
+        // バッファの手順表から target_sticker1 → target_sticker2 の手順を取得
+        if let Some(inner_map) = table.get(&target_sticker1) {
+            if let Some(moves) = inner_map.get(&target_sticker2) {
+                let mut description = format!("{} → {}", target_sticker1, target_sticker2);
+                if buffer != self.config.corner_buffer {
+                    description = format!("{}: {}", CORNER_NAMES[buffer], description);
+                }
+                return Ok(Some(MoveSequence::new(moves.clone(), description)));
             }
         }
 
         Ok(None)
     }
 
+    /// 2つのコーナー交換をまとめた手順の手数（HTM、手順が無ければ None）
+    pub fn corner_pair_cost(
+        &self,
+        swap1: &CornerSwapOperation,
+        swap2: &CornerSwapOperation,
+    ) -> Option<usize> {
+        let seq = self.try_convert_two_swaps(swap1, swap2).ok()??;
+        Some(seq.move_count().htm)
+    }
+
     /// 1つのSwapを変換
     fn convert_single_swap(&self, swap: &CornerSwapOperation) -> Result<MoveSequence, String> {
         let target_sticker = TARGET_STICKERS[swap.target2][swap.orientation as usize].to_string();
 
-        if let Some(move_str) = self.ufr_parity.get(&target_sticker) {
-            let moves = parse_sequence(move_str)?;
-            let description = format!("Parity: {}", target_sticker);
-            Ok(MoveSequence::new(moves, description))
+        if let Some(moves) = self.corner_parity_table(swap.target1)?.get(&target_sticker) {
+            let mut description = format!("Parity: {}", target_sticker);
+            if swap.target1 != self.config.corner_buffer {
+                description = format!("{} {}", CORNER_NAMES[swap.target1], description);
+            }
+            Ok(MoveSequence::new(moves.clone(), description))
         } else {
             Err(format!(
                 "Parity move not found for target_sticker: {}",
@@ -392,15 +744,28 @@ impl OperationsToTurns {
         }
     }
 
+    /// 2つのTwistを組の手順表で変換（手順が無ければ None）
+    fn try_convert_two_twists(
+        &self,
+        twist1: &CornerTwistOperation,
+        twist2: &CornerTwistOperation,
+    ) -> Option<MoveSequence> {
+        let target_sticker1 = TWIST_TARGET_STICKERS[twist1.target][twist1.orientation as usize];
+        let target_sticker2 = TWIST_TARGET_STICKERS[twist2.target][twist2.orientation as usize];
+
+        let moves = find_unordered_pair(&self.ufr_twist_pairs, target_sticker1, target_sticker2)?;
+        let description = format!("Twist: {} + {}", target_sticker1, target_sticker2);
+        Some(MoveSequence::new(moves.clone(), description))
+    }
+
     /// 1つのTwistを変換
     fn convert_twist(&self, twist: &CornerTwistOperation) -> Result<MoveSequence, String> {
         let target_sticker =
             TWIST_TARGET_STICKERS[twist.target][twist.orientation as usize].to_string();
 
-        if let Some(move_str) = self.ufr_twist.get(&target_sticker) {
-            let moves = parse_sequence(move_str)?;
+        if let Some(moves) = self.ufr_twist.get(&target_sticker) {
             let description = format!("Twist: {}", target_sticker);
-            Ok(MoveSequence::new(moves, description))
+            Ok(MoveSequence::new(moves.clone(), description))
         } else {
             Err(format!(
                 "Twist move not found for target_sticker: {}",
@@ -440,7 +805,35 @@ impl OperationsToTurns {
                 }
             }
 
-            // 2. 1つの Flip を変換
+            // 2. 組にならない Swap を変換
+            if let EdgeOperation::Swap(swap) = &operations[i] {
+                if let Some(EdgeOperation::Swap(next)) = operations.get(i + 1) {
+                    return Err(format!(
+                        "Edge alg not found: {} → {}",
+                        EDGE_TARGET_STICKERS[swap.target2][swap.orientation as usize],
+                        EDGE_TARGET_STICKERS[next.target2][next.orientation as usize]
+                    ));
+                }
+                let seq = self.convert_edge_parity(swap)?;
+                result.push(seq);
+                i += 1;
+                continue;
+            }
+
+            // 3. 連続する2つの Flip を組の手順表で変換できるか試す
+            if self.config.pair_orientations && i + 1 < operations.len() {
+                if let (EdgeOperation::Flip(flip1), EdgeOperation::Flip(flip2)) =
+                    (&operations[i], &operations[i + 1])
+                {
+                    if let Some(seq) = self.try_convert_two_flips(flip1, flip2) {
+                        result.push(seq);
+                        i += 2;
+                        continue;
+                    }
+                }
+            }
+
+            // 4. 1つの Flip を変換
             if let EdgeOperation::Flip(flip) = &operations[i] {
                 let seq = self.convert_edge_flip(flip)?;
                 result.push(seq);
@@ -448,7 +841,6 @@ impl OperationsToTurns {
                 continue;
             }
 
-            // Note: 1つの Swap からの変換は存在しない（ドキュメントより）
             i += 1;
         }
 
@@ -458,41 +850,136 @@ impl OperationsToTurns {
     /// 連続する2つのEdge Swapを変換
     ///
     /// # 前提条件
-    /// - swap1.target1 と swap2.target1 は両方とも BUFFER_PIECE (6 = UF) であること
+    /// - swap1.target1 と swap2.target1 は同じバッファであること
     /// - これは EdgeInspection::solve_edge_permutation_with_orientation の
-    ///   アルゴリズムによって保証される
+    ///   アルゴリズムによって保証される（フローティングでも組の途中でバッファは変わらない）
     fn try_convert_two_edge_swaps(
         &self,
         swap1: &EdgeSwapOperation,
         swap2: &EdgeSwapOperation,
     ) -> Result<Option<MoveSequence>, String> {
-        // target1 は両方とも BUFFER_PIECE (6) であることを前提とする
+        let buffer = swap1.target1;
+        if swap2.target1 != buffer {
+            return Err(format!(
+                "Cannot pair swaps from different buffers: {} and {}",
+                EDGE_NAMES[buffer], EDGE_NAMES[swap2.target1]
+            ));
+        }
+        let table = self.edge_pair_table(buffer)?;
+
         // swap1.target2 と swap2.target2 の orientation を使って target_sticker を決定
         let target_sticker1 =
             EDGE_TARGET_STICKERS[swap1.target2][swap1.orientation as usize].to_string();
         let target_sticker2 =
             EDGE_TARGET_STICKERS[swap2.target2][swap2.orientation as usize].to_string();
 
-        // uf_expanded から target_sticker1 → target_sticker2 の手順を取得
-        if let Some(inner_map) = self.uf_expanded.get(&target_sticker1) {
-            if let Some(move_str) = inner_map.get(&target_sticker2) {
-                let moves = parse_sequence(move_str)?;
-                let description = format!("{} → {}", target_sticker1, target_sticker2);
-                return Ok(Some(MoveSequence::new(moves, description)));
+        // バッファの手順表から target_sticker1 → target_sticker2 の手順を取得
+        if let Some(inner_map) = table.get(&target_sticker1) {
+            if let Some(moves) = inner_map.get(&target_sticker2) {
+                let mut description = format!("{} → {}", target_sticker1, target_sticker2);
+                if buffer != self.config.edge_buffer {
+                    description = format!("{}: {}", EDGE_NAMES[buffer], description);
+                }
+                return Ok(Some(MoveSequence::new(moves.clone(), description)));
             }
         }
 
         Ok(None)
     }
 
+    /// 2つのエッジ交換をまとめた手順の手数（HTM、手順が無ければ None）
+    pub fn edge_pair_cost(
+        &self,
+        swap1: &EdgeSwapOperation,
+        swap2: &EdgeSwapOperation,
+    ) -> Option<usize> {
+        let seq = self.try_convert_two_edge_swaps(swap1, swap2).ok()??;
+        Some(seq.move_count().htm)
+    }
+
+    /// 組にならずに残ったEdge Swap（エッジのパリティ）を `config.edge_parity` に従って変換
+    fn convert_edge_parity(&self, swap: &EdgeSwapOperation) -> Result<MoveSequence, String> {
+        let target_sticker = EDGE_TARGET_STICKERS[swap.target2][swap.orientation as usize];
+        let not_found = || {
+            format!(
+                "Edge parity move not found for target_sticker: {}",
+                target_sticker
+            )
+        };
+
+        // バッファがパリティのエッジなら、もう一方のパリティのエッジ
+        let [edge1, edge2] = self.config.parity_edges;
+        let other = match swap.target1 {
+            b if b == edge1 => Some(edge2),
+            b if b == edge2 => Some(edge1),
+            _ => None,
+        };
+        if self.config.edge_parity != EdgeParity::Error
+            && other == Some(swap.target2)
+            && swap.orientation == 0
+        {
+            // コーナーのパリティ手順だけで揃う
+            return Ok(MoveSequence::new(
+                Sequence::new(),
+                format!("Parity: {}", target_sticker),
+            ));
+        }
+
+        match self.config.edge_parity {
+            EdgeParity::SwapParityEdges => {
+                // もう一方のパリティのエッジとの組にする（交換はコーナーのパリティ手順で戻る）
+                let other = other.ok_or_else(|| {
+                    format!(
+                        "Edge parity from {} cannot use the parity edges {} and {}",
+                        EDGE_NAMES[swap.target1], EDGE_NAMES[edge1], EDGE_NAMES[edge2]
+                    )
+                })?;
+                // 1つ目の交換の後はバッファが揃っているので向きは 0
+                let parity_swap = EdgeSwapOperation::new(swap.target1, other, 0);
+                let mut seq = self
+                    .try_convert_two_edge_swaps(swap, &parity_swap)?
+                    .ok_or_else(not_found)?;
+                seq.description = format!("Parity: {}", seq.description);
+                Ok(seq)
+            }
+            EdgeParity::Table => {
+                if swap.target1 != self.config.edge_buffer {
+                    return Err(format!(
+                        "No edge parity table for buffer {}",
+                        EDGE_NAMES[swap.target1]
+                    ));
+                }
+                let moves = self.uf_parity.get(target_sticker).ok_or_else(not_found)?;
+                Ok(MoveSequence::new(
+                    moves.clone(),
+                    format!("Parity: {}", target_sticker),
+                ))
+            }
+            EdgeParity::Error => Err(format!("Unpaired edge swap (edge parity): {}", swap)),
+        }
+    }
+
+    /// 2つのEdge Flipを組の手順表で変換（手順が無ければ None）
+    fn try_convert_two_flips(
+        &self,
+        flip1: &EdgeFlipOperation,
+        flip2: &EdgeFlipOperation,
+    ) -> Option<MoveSequence> {
+        let target_sticker1 = EDGE_FLIP_TARGET_STICKERS[flip1.target];
+        let target_sticker2 = EDGE_FLIP_TARGET_STICKERS[flip2.target];
+
+        let moves = find_unordered_pair(&self.uf_flip_pairs, target_sticker1, target_sticker2)?;
+        let description = format!("Flip: {} + {}", target_sticker1, target_sticker2);
+        Some(MoveSequence::new(moves.clone(), description))
+    }
+
     /// 1つのEdge Flipを変換
     fn convert_edge_flip(&self, flip: &EdgeFlipOperation) -> Result<MoveSequence, String> {
         let target_sticker = EDGE_FLIP_TARGET_STICKERS[flip.target].to_string();
 
-        if let Some(move_str) = self.uf_flip.get(&target_sticker) {
-            let moves = parse_sequence(move_str)?;
+        if let Some(moves) = self.uf_flip.get(&target_sticker) {
             let description = format!("Flip: {}", target_sticker);
-            Ok(MoveSequence::new(moves, description))
+            Ok(MoveSequence::new(moves.clone(), description))
         } else {
             Err(format!(
                 "Flip move not found for target_sticker: {}",
@@ -589,6 +1076,115 @@ mod tests {
         assert_eq!(collection.len(), 1);
         let sequences = collection.sequences();
         assert_eq!(sequences[0].description, "Parity: RDB");
+
+        // 組の手順が無い交換は、パリティの手順にせずにエラーにする
+        let operations = vec![
+            CornerOperation::Swap(CornerSwapOperation::new(2, 6, 1)),
+            CornerOperation::Swap(CornerSwapOperation::new(2, 5, 2)),
+        ];
+        assert_eq!(
+            converter.convert(&operations).unwrap_err(),
+            "Corner alg not found: RDF → RDB"
+        );
+    }
+
+    #[test]
+    fn test_floating_buffer_tables() {
+        // UFL 用の表として同じ形の JSON を使う
+        let converter = OperationsToTurns::new(
+            TEST_UFR_EXPANDED,
+            TEST_UFR_PARITY,
+            TEST_UFR_TWIST,
+            TEST_UF_EXPANDED,
+            TEST_UF_FLIP,
+        )
+        .unwrap()
+        .with_corner_buffer_sheet(3, TEST_UFR_EXPANDED, TEST_UFR_PARITY)
+        .unwrap()
+        .with_edge_buffer_sheet(5, TEST_UF_EXPANDED)
+        .unwrap();
+
+        let operations = vec![
+            CornerOperation::Swap(CornerSwapOperation::new(3, 5, 2)),
+            CornerOperation::Swap(CornerSwapOperation::new(3, 6, 1)),
+            CornerOperation::Swap(CornerSwapOperation::new(3, 5, 2)),
+        ];
+        let collection = converter.convert(&operations).unwrap();
+        let descriptions: Vec<&str> = collection
+            .sequences()
+            .iter()
+            .map(|seq| seq.description.as_str())
+            .collect();
+        assert_eq!(descriptions, vec!["UFL: RDB → RDF", "UFL Parity: RDB"]);
+
+        // UR からのエッジの交換
+        let edge_operations = vec![
+            EdgeOperation::Swap(EdgeSwapOperation::new(5, 2, 0)),
+            EdgeOperation::Swap(EdgeSwapOperation::new(5, 9, 0)),
+        ];
+        let collection = converter.convert_edge_operations(&edge_operations).unwrap();
+        assert_eq!(collection.sequences()[0].description, "UR: FR → DR");
+
+        // 表の無いバッファと、バッファをまたぐ組はエラー
+        let operations = vec![CornerOperation::Swap(CornerSwapOperation::new(1, 5, 2))];
+        assert_eq!(
+            converter.convert(&operations).unwrap_err(),
+            "No parity table for corner buffer UBR"
+        );
+        let operations = vec![
+            CornerOperation::Swap(CornerSwapOperation::new(2, 5, 2)),
+            CornerOperation::Swap(CornerSwapOperation::new(3, 6, 1)),
+        ];
+        assert!(converter.convert(&operations).is_err());
+    }
+
+    #[test]
+    fn test_edge_parity() {
+        let uf_expanded = r#"{ "FR": { "UR": "R U R'" } }"#;
+        let converter = OperationsToTurns::new(
+            TEST_UFR_EXPANDED,
+            TEST_UFR_PARITY,
+            TEST_UFR_TWIST,
+            uf_expanded,
+            TEST_UF_FLIP,
+        )
+        .unwrap()
+        .with_edge_parity_table(r#"{ "FR": "R2 U2" }"#)
+        .unwrap();
+        let lone = |target: usize| vec![EdgeOperation::Swap(EdgeSwapOperation::new(6, target, 0))];
+
+        // UF ↔ FR は FR → UR の組にし、UF ↔ UR はコーナーのパリティ手順に任せる
+        let collection = converter.convert_edge_operations(&lone(2)).unwrap();
+        assert_eq!(collection.sequences()[0].description, "Parity: FR → UR");
+        let collection = converter.convert_edge_operations(&lone(5)).unwrap();
+        assert_eq!(collection.sequences()[0].description, "Parity: UR");
+        assert!(collection.sequences()[0].is_empty());
+        assert!(converter.convert_edge_operations(&lone(9)).is_err());
+
+        let table = converter
+            .clone()
+            .with_config(SolverConfig::default().with_edge_parity(EdgeParity::Table));
+        let collection = table.convert_edge_operations(&lone(2)).unwrap();
+        assert_eq!(collection.sequences()[0].description, "Parity: FR");
+        assert_eq!(
+            collection.sequences()[0].moves,
+            parse_sequence("R2 U2").unwrap()
+        );
+
+        let error = converter
+            .clone()
+            .with_config(SolverConfig::default().with_edge_parity(EdgeParity::Error));
+        assert!(error.convert_edge_operations(&lone(2)).is_err());
+
+        // 組の手順が無い交換は、黙って飛ばさずにエラーにする
+        let operations = vec![
+            EdgeOperation::Swap(EdgeSwapOperation::new(6, 9, 0)),
+            EdgeOperation::Swap(EdgeSwapOperation::new(6, 2, 0)),
+        ];
+        assert_eq!(
+            converter.convert_edge_operations(&operations).unwrap_err(),
+            "Edge alg not found: DR → FR"
+        );
     }
 
     #[test]
@@ -683,6 +1279,24 @@ mod tests {
         assert_eq!(count, 2);
     }
 
+    #[test]
+    fn test_boundary_cancellations() {
+        let sequence = |moves: &str| MoveSequence::new(parse_sequence(moves).unwrap(), String::new());
+        let collection = MoveSequenceCollection::from_vec(vec![
+            sequence("R U R' U'"),
+            sequence("U R' F"),
+            sequence("D U D'"),
+            sequence("U' F"),
+        ]);
+
+        // U' U が消えて R' R' が R2 にまとまる。D U D' は U になり、U' と消えて F F が F2 になる
+        assert_eq!(collection.boundary_cancellations(), vec![3, 0, 3]);
+        assert_eq!(
+            collection.simplified_moves(),
+            parse_sequence("R U R2 F2").unwrap()
+        );
+    }
+
     #[test]
     fn test_convert_two_edge_swaps() {
         let converter = OperationsToTurns::new(
@@ -739,6 +1353,109 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_convert_twist_pair() {
+        let config = SolverConfig::default().with_pair_orientations(true);
+        let converter = OperationsToTurns::new(
+            TEST_UFR_EXPANDED,
+            TEST_UFR_PARITY,
+            TEST_UFR_TWIST,
+            TEST_UF_EXPANDED,
+            TEST_UF_FLIP,
+        )
+        .expect("Failed to create converter")
+        .with_twist_pair_table(r#"{"RUB": {"FUL": "R' D R D' R' D R U R' D' R D R' D' R U'"}}"#)
+        .expect("Failed to load twist pairs");
+
+        // FUL と RUB は逆向きのねじれ（表は逆の順で持っている）
+        let operations = vec![
+            CornerOperation::Twist(CornerTwistOperation::new(3, 1)),
+            CornerOperation::Twist(CornerTwistOperation::new(1, 2)),
+        ];
+
+        let collection = converter
+            .clone()
+            .with_config(config)
+            .convert(&operations)
+            .expect("Failed to convert");
+        assert_eq!(collection.len(), 1);
+        assert_eq!(collection.sequences()[0].description, "Twist: FUL + RUB");
+
+        // 組にしなければ RUB の手順が無い
+        assert!(converter.convert(&operations).is_err());
+    }
+
+    #[test]
+    fn test_convert_flip_pair() {
+        let config = SolverConfig::default().with_pair_orientations(true);
+        let converter = OperationsToTurns::new(
+            TEST_UFR_EXPANDED,
+            TEST_UFR_PARITY,
+            TEST_UFR_TWIST,
+            TEST_UF_EXPANDED,
+            TEST_UF_FLIP,
+        )
+        .expect("Failed to create converter")
+        .with_config(config);
+
+        let operations = vec![
+            EdgeOperation::Flip(EdgeFlipOperation::new(4)),
+            EdgeOperation::Flip(EdgeFlipOperation::new(5)),
+        ];
+
+        // 組の手順が無ければ1つずつ
+        let collection = converter
+            .convert_edge_operations(&operations)
+            .expect("Failed to convert");
+        assert_eq!(collection.len(), 2);
+
+        let converter = converter
+            .with_flip_pair_table(r#"{"UB": {"UR": "M' U M' U M' U2 M U M U M U2"}}"#)
+            .expect("Failed to load flip pairs");
+        let collection = converter
+            .convert_edge_operations(&operations)
+            .expect("Failed to convert");
+        assert_eq!(collection.len(), 1);
+        assert_eq!(collection.sequences()[0].description, "Flip: UB + UR");
+    }
+
+    #[test]
+    fn test_new_reports_invalid_alg() {
+        let uf_expanded = r#"{ "FR": { "DL": "R U R' Q R F'" } }"#;
+        let err = OperationsToTurns::new(
+            TEST_UFR_EXPANDED,
+            TEST_UFR_PARITY,
+            TEST_UFR_TWIST,
+            uf_expanded,
+            TEST_UF_FLIP,
+        )
+        .err()
+        .expect("invalid alg must be rejected");
+
+        assert_eq!(
+            err.to_string(),
+            "uf_expanded [FR-DL]: unexpected 'Q' at byte 7, expected move"
+        );
+        assert_eq!(
+            err.parse_error().and_then(|e| e.location.clone()),
+            Some(ParseLocation::Sticker {
+                table: "uf_expanded".to_string(),
+                key: "FR-DL".to_string(),
+            })
+        );
+
+        let err = OperationsToTurns::new(
+            TEST_UFR_EXPANDED,
+            "{",
+            TEST_UFR_TWIST,
+            TEST_UF_EXPANDED,
+            TEST_UF_FLIP,
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(err, TableError::Json { ref table, .. } if table == "ufr_parity"));
+    }
+
     #[test]
     fn test_mixed_edge_operations() {
         let converter = OperationsToTurns::new(
@@ -803,4 +1520,58 @@ mod tests {
         assert!(display.contains("// RDB → RDF"));
         assert!(display.contains("// Twist: FUL"));
     }
+
+    #[test]
+    fn test_from_csv_sheets() {
+        let resources = concat!(env!("CARGO_MANIFEST_DIR"), "/resources");
+        let path = |name: &str| format!("{}/{}", resources, name);
+        let converter = OperationsToTurns::from_csv_sheets(
+            path("ufr.csv"),
+            path("ufr_parity.csv"),
+            path("ufr_twist.csv"),
+            path("uf.csv"),
+            path("uf_flip.csv"),
+        )
+        .expect("Failed to load CSV sheets");
+
+        // 記法が展開され、全ての手順が正しいサイクルになっている
+        let report = converter.verify();
+        assert!(report.is_ok(), "{}", report);
+        assert_eq!(report.checked, 864);
+
+        let err = OperationsToTurns::from_csv_sheets(
+            path("missing.csv"),
+            path("ufr_parity.csv"),
+            path("ufr_twist.csv"),
+            path("uf.csv"),
+            path("uf_flip.csv"),
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(err, TableError::Csv { .. }), "{}", err);
+        assert!(err.to_string().starts_with("Failed to load"));
+        assert!(err.to_string().contains("missing.csv"));
+
+        // 読めない手順はファイルとセルの位置を持つ ParseError のまま返す
+        let bad_parity = std::env::temp_dir().join("test_bad_ufr_parity.csv");
+        std::fs::write(&bad_parity, "sticker,alg\nUBL,R U\nRUB,R [U\n").unwrap();
+        let err = OperationsToTurns::from_csv_sheets(
+            path("ufr.csv").into(),
+            bad_parity.clone(),
+            path("ufr_twist.csv").into(),
+            path("uf.csv").into(),
+            path("uf_flip.csv").into(),
+        )
+        .err()
+        .unwrap();
+        std::fs::remove_file(&bad_parity).ok();
+        let TableError::Parse { path, source } = err else {
+            panic!("expected a parse error: {}", err);
+        };
+        assert_eq!(path, Some(bad_parity));
+        assert_eq!(
+            source.location,
+            Some(ParseLocation::Cell { row: 3, column: 2 })
+        );
+    }
 }
@@ -0,0 +1,588 @@
+use super::operations_to_turns::{
+    OperationsToTurns, EDGE_FLIP_TARGET_STICKERS, EDGE_TARGET_STICKERS, TARGET_STICKERS,
+    TWIST_TARGET_STICKERS,
+};
+use super::{
+    CornerSwapOperation, CornerTwistOperation, EdgeFlipOperation, EdgeParity, EdgeSwapOperation,
+};
+use crate::cube::state::{CORNER_NAMES, EDGE_NAMES};
+use crate::cube::{PackedState, State};
+use crate::parser::{sequence_to_string, Sequence};
+use std::collections::HashMap;
+use std::fmt;
+
+/// 手順表の 1 つのエントリ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetEntry {
+    /// "ufr_expanded" のような表の名前
+    pub table: String,
+    /// "UBR-FDR" のようなステッカーの組、または 1 つのステッカー
+    pub key: String,
+    pub alg: String,
+}
+
+impl fmt::Display for SheetEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.table, self.key)?;
+        if !self.alg.is_empty() {
+            write!(f, ": {}", self.alg)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of checking every alg table against the cycle it should perform
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SheetReport {
+    /// 実際に適用して確かめたエントリの数
+    pub checked: usize,
+    /// 期待した状態にならないエントリ
+    pub wrong: Vec<SheetEntry>,
+    /// 解法で使われうるのに無い（または空の）エントリ
+    pub missing: Vec<SheetEntry>,
+    /// 解法で使われることのないエントリ（空の手順は除く）
+    pub redundant: Vec<SheetEntry>,
+}
+
+impl SheetReport {
+    /// 間違った手順も足りない手順もないか
+    pub fn is_ok(&self) -> bool {
+        self.wrong.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl fmt::Display for SheetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Checked {} entries: {} wrong, {} missing, {} redundant",
+            self.checked,
+            self.wrong.len(),
+            self.missing.len(),
+            self.redundant.len()
+        )?;
+        for (title, entries) in [
+            ("Wrong", &self.wrong),
+            ("Missing", &self.missing),
+            ("Redundant", &self.redundant),
+        ] {
+            if entries.is_empty() {
+                continue;
+            }
+            writeln!(f, "{}:", title)?;
+            for entry in entries {
+                writeln!(f, "  {}", entry)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 完成状態に手順を適用した状態
+fn apply_to_solved(moves: &Sequence) -> State {
+    let mut state = PackedState::solved();
+    state.apply_sequence(moves);
+    state.to_state()
+}
+
+/// 1 つの表を、キーごとの期待する状態と比べて `report` に追加する
+fn check_table(
+    report: &mut SheetReport,
+    table: &str,
+    entries: HashMap<String, &Sequence>,
+    expected: Vec<(String, State)>,
+) {
+    let entry = |key: &str, moves: Option<&Sequence>| SheetEntry {
+        table: table.to_string(),
+        key: key.to_string(),
+        alg: moves.map(sequence_to_string).unwrap_or_default(),
+    };
+
+    for (key, state) in &expected {
+        match entries.get(key) {
+            Some(moves) if !moves.is_empty() => {
+                report.checked += 1;
+                if apply_to_solved(moves) != *state {
+                    report.wrong.push(entry(key, Some(moves)));
+                }
+            }
+            _ => report.missing.push(entry(key, None)),
+        }
+    }
+
+    let mut redundant: Vec<SheetEntry> = entries
+        .iter()
+        .filter(|(key, moves)| !moves.is_empty() && !expected.iter().any(|(k, _)| k == *key))
+        .map(|(key, moves)| entry(key, Some(moves)))
+        .collect();
+    redundant.sort_by(|a, b| a.key.cmp(&b.key));
+    report.redundant.extend(redundant);
+}
+
+/// 2階層の表を "A-B" のキーで平らにする
+fn flatten(table: &HashMap<String, HashMap<String, Sequence>>) -> HashMap<String, &Sequence> {
+    table
+        .iter()
+        .flat_map(|(first, inner)| {
+            inner
+                .iter()
+                .map(move |(second, moves)| (format!("{}-{}", first, second), moves))
+        })
+        .collect()
+}
+
+fn single(table: &HashMap<String, Sequence>) -> HashMap<String, &Sequence> {
+    table
+        .iter()
+        .map(|(key, moves)| (key.clone(), moves))
+        .collect()
+}
+
+/// 順番を問わない2階層の表を、`stickers` の順に並べた "A-B" のキーで平らにする
+fn flatten_unordered<'a>(
+    table: &'a HashMap<String, HashMap<String, Sequence>>,
+    stickers: &[&str],
+) -> HashMap<String, &'a Sequence> {
+    let position = |sticker: &str| stickers.iter().position(|&s| s == sticker);
+    let mut entries: HashMap<String, &Sequence> = HashMap::new();
+    for (first, inner) in table {
+        for (second, moves) in inner {
+            let key = match (position(first), position(second)) {
+                (Some(i), Some(j)) if j < i => format!("{}-{}", second, first),
+                _ => format!("{}-{}", first, second),
+            };
+            // 両方の順がある場合は空でない方を確かめる
+            if entries.get(&key).is_none_or(|existing| existing.is_empty()) {
+                entries.insert(key, moves);
+            }
+        }
+    }
+    entries
+}
+
+/// バッファ以外のコーナーとその向きの全ての組
+fn corner_targets(buffer: usize) -> impl Iterator<Item = (usize, u8)> {
+    (0..8)
+        .filter(move |&p| p != buffer)
+        .flat_map(|p| (0..3).map(move |o| (p, o)))
+}
+
+/// バッファ以外のエッジとその向きの全ての組
+fn edge_targets(buffer: usize) -> impl Iterator<Item = (usize, u8)> {
+    (0..12)
+        .filter(move |&p| p != buffer)
+        .flat_map(|p| (0..2).map(move |o| (p, o)))
+}
+
+/// `buffer` からの2点交換2つ分の手順表を確かめる
+fn check_corner_pairs(
+    report: &mut SheetReport,
+    table: &str,
+    entries: &HashMap<String, HashMap<String, Sequence>>,
+    buffer: usize,
+) {
+    let solved = State::solved();
+    let pairs = corner_targets(buffer)
+        .flat_map(|(p1, o1)| {
+            corner_targets(buffer)
+                .filter(move |&(p2, _)| p2 != p1)
+                .map(move |(p2, o2)| ((p1, o1), (p2, o2)))
+        })
+        .map(|((p1, o1), (p2, o2))| {
+            let key = format!(
+                "{}-{}",
+                TARGET_STICKERS[p1][o1 as usize], TARGET_STICKERS[p2][o2 as usize]
+            );
+            let state = CornerSwapOperation::new(buffer, p1, o1).apply(&solved);
+            let state = CornerSwapOperation::new(buffer, p2, o2).apply(&state);
+            (key, state)
+        })
+        .collect();
+    check_table(report, table, flatten(entries), pairs);
+}
+
+/// `buffer` からのパリティの手順表を確かめる
+fn check_corner_parity(
+    report: &mut SheetReport,
+    table: &str,
+    entries: &HashMap<String, Sequence>,
+    buffer: usize,
+    parity_edges: [usize; 2],
+) {
+    let solved = State::solved();
+    let parity_edge_swap = EdgeSwapOperation::new(parity_edges[0], parity_edges[1], 0);
+    let parities = corner_targets(buffer)
+        .map(|(p, o)| {
+            let state = CornerSwapOperation::new(buffer, p, o).apply(&solved);
+            let state = parity_edge_swap.apply(&state);
+            (TARGET_STICKERS[p][o as usize].to_string(), state)
+        })
+        .collect();
+    check_table(report, table, single(entries), parities);
+}
+
+/// `buffer` からのエッジの2点交換2つ分の手順表を確かめる
+fn check_edge_pairs(
+    report: &mut SheetReport,
+    table: &str,
+    entries: &HashMap<String, HashMap<String, Sequence>>,
+    buffer: usize,
+) {
+    let solved = State::solved();
+    let pairs = edge_targets(buffer)
+        .flat_map(|(p1, o1)| {
+            edge_targets(buffer)
+                .filter(move |&(p2, _)| p2 != p1)
+                .map(move |(p2, o2)| ((p1, o1), (p2, o2)))
+        })
+        .map(|((p1, o1), (p2, o2))| {
+            let key = format!(
+                "{}-{}",
+                EDGE_TARGET_STICKERS[p1][o1 as usize], EDGE_TARGET_STICKERS[p2][o2 as usize]
+            );
+            let state = EdgeSwapOperation::new(buffer, p1, o1).apply(&solved);
+            let state = EdgeSwapOperation::new(buffer, p2, o2).apply(&state);
+            (key, state)
+        })
+        .collect();
+    check_table(report, table, flatten(entries), pairs);
+}
+
+impl OperationsToTurns {
+    /// Check every alg table against the cycle implied by its key
+    ///
+    /// Each entry is applied to a solved cube and compared with the state
+    /// that the matching inspection operations produce:
+    /// - `ufr_expanded` / `uf_expanded`: two swaps with the buffer
+    /// - `ufr_parity`: one corner swap with the buffer, plus the two parity
+    ///   edges of the config swapped
+    /// - `ufr_twist` / `uf_flip`: the target twisted or flipped, with the
+    ///   buffer taking the opposite twist or flip
+    /// - `uf_parity` (when loaded or `EdgeParity::Table` is set): one edge swap
+    ///   with the buffer, plus the two parity edges swapped
+    /// - `ufr_twist_pairs` / `uf_flip_pairs` (when loaded or
+    ///   `pair_orientations` is set): two corners twisted in opposite
+    ///   directions or two edges flipped, with the buffer untouched; either
+    ///   order of the two stickers counts, and keys are reported in piece order
+    ///
+    /// Floating buffer tables are checked the same way against their own
+    /// buffer and reported as e.g. `ufl_expanded` / `ufl_parity`.
+    pub fn verify(&self) -> SheetReport {
+        let mut report = SheetReport::default();
+        let solved = State::solved();
+        let (corner_buffer, edge_buffer) = (self.config.corner_buffer, self.config.edge_buffer);
+        let parity_edges = self.config.parity_edges;
+
+        check_corner_pairs(
+            &mut report,
+            "ufr_expanded",
+            &self.ufr_expanded,
+            corner_buffer,
+        );
+        check_corner_parity(
+            &mut report,
+            "ufr_parity",
+            &self.ufr_parity,
+            corner_buffer,
+            parity_edges,
+        );
+
+        let twists = corner_targets(corner_buffer)
+            .filter(|&(_, o)| o != 0)
+            .map(|(p, o)| {
+                let state = CornerTwistOperation::new(p, o).apply(&solved);
+                let state = CornerTwistOperation::new(corner_buffer, (3 - o) % 3).apply(&state);
+                (TWIST_TARGET_STICKERS[p][o as usize].to_string(), state)
+            })
+            .collect();
+        check_table(&mut report, "ufr_twist", single(&self.ufr_twist), twists);
+
+        check_edge_pairs(&mut report, "uf_expanded", &self.uf_expanded, edge_buffer);
+
+        let flips = (0..12)
+            .filter(|&p| p != edge_buffer)
+            .map(|p| {
+                let state = EdgeFlipOperation::new(p).apply(&solved);
+                let state = EdgeFlipOperation::new(edge_buffer).apply(&state);
+                (EDGE_FLIP_TARGET_STICKERS[p].to_string(), state)
+            })
+            .collect();
+        check_table(&mut report, "uf_flip", single(&self.uf_flip), flips);
+
+        if self.config.edge_parity == EdgeParity::Table || !self.uf_parity.is_empty() {
+            let parity_edge_swap = EdgeSwapOperation::new(parity_edges[0], parity_edges[1], 0);
+            let parities = edge_targets(edge_buffer)
+                .map(|(p, o)| {
+                    let state = EdgeSwapOperation::new(edge_buffer, p, o).apply(&solved);
+                    let state = parity_edge_swap.apply(&state);
+                    (EDGE_TARGET_STICKERS[p][o as usize].to_string(), state)
+                })
+                // パリティのエッジ同士の交換はコーナーのパリティ手順だけで揃う
+                .filter(|(_, state)| *state != solved)
+                .collect();
+            check_table(&mut report, "uf_parity", single(&self.uf_parity), parities);
+        }
+
+        if self.config.pair_orientations || !self.ufr_twist_pairs.is_empty() {
+            // 逆向きの2つのねじれだけがバッファを動かさずに揃えられる
+            let twisted: Vec<_> = corner_targets(corner_buffer)
+                .filter(|&(_, o)| o != 0)
+                .collect();
+            let twist_pairs = twisted
+                .iter()
+                .flat_map(|&(p1, o1)| {
+                    twisted
+                        .iter()
+                        .filter(move |&&(p2, o2)| p1 < p2 && o1 + o2 == 3)
+                        .map(move |&(p2, o2)| ((p1, o1), (p2, o2)))
+                })
+                .map(|((p1, o1), (p2, o2))| {
+                    let key = format!(
+                        "{}-{}",
+                        TWIST_TARGET_STICKERS[p1][o1 as usize],
+                        TWIST_TARGET_STICKERS[p2][o2 as usize]
+                    );
+                    let state = CornerTwistOperation::new(p1, o1).apply(&solved);
+                    let state = CornerTwistOperation::new(p2, o2).apply(&state);
+                    (key, state)
+                })
+                .collect();
+            let stickers: Vec<&str> = TWIST_TARGET_STICKERS.iter().flatten().copied().collect();
+            check_table(
+                &mut report,
+                "ufr_twist_pairs",
+                flatten_unordered(&self.ufr_twist_pairs, &stickers),
+                twist_pairs,
+            );
+        }
+
+        if self.config.pair_orientations || !self.uf_flip_pairs.is_empty() {
+            let flip_pairs = (0..12)
+                .filter(|&p| p != edge_buffer)
+                .flat_map(|p1| {
+                    (p1 + 1..12)
+                        .filter(|&p| p != edge_buffer)
+                        .map(move |p2| (p1, p2))
+                })
+                .map(|(p1, p2)| {
+                    let key = format!(
+                        "{}-{}",
+                        EDGE_FLIP_TARGET_STICKERS[p1], EDGE_FLIP_TARGET_STICKERS[p2]
+                    );
+                    let state = EdgeFlipOperation::new(p1).apply(&solved);
+                    let state = EdgeFlipOperation::new(p2).apply(&state);
+                    (key, state)
+                })
+                .collect();
+            check_table(
+                &mut report,
+                "uf_flip_pairs",
+                flatten_unordered(&self.uf_flip_pairs, &EDGE_FLIP_TARGET_STICKERS),
+                flip_pairs,
+            );
+        }
+
+        let mut corner_buffers: Vec<_> = self.floating_corner_expanded.keys().copied().collect();
+        corner_buffers.sort();
+        for buffer in corner_buffers {
+            let name = CORNER_NAMES[buffer].to_lowercase();
+            check_corner_pairs(
+                &mut report,
+                &format!("{}_expanded", name),
+                &self.floating_corner_expanded[&buffer],
+                buffer,
+            );
+            if let Some(parity) = self.floating_corner_parity.get(&buffer) {
+                check_corner_parity(
+                    &mut report,
+                    &format!("{}_parity", name),
+                    parity,
+                    buffer,
+                    parity_edges,
+                );
+            }
+        }
+
+        let mut edge_buffers: Vec<_> = self.floating_edge_expanded.keys().copied().collect();
+        edge_buffers.sort();
+        for buffer in edge_buffers {
+            check_edge_pairs(
+                &mut report,
+                &format!("{}_expanded", EDGE_NAMES[buffer].to_lowercase()),
+                &self.floating_edge_expanded[&buffer],
+                buffer,
+            );
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inspection::SolverConfig;
+    use serde_json::Value;
+
+    const UFR_EXPANDED: &str = include_str!("../../resources/ufr_expanded.json");
+    const UFR_PARITY: &str = include_str!("../../resources/ufr_parity.json");
+    const UFR_TWIST: &str = include_str!("../../resources/ufr_twist.json");
+    const UF_EXPANDED: &str = include_str!("../../resources/uf_expanded.json");
+    const UF_FLIP: &str = include_str!("../../resources/uf_flip.json");
+
+    #[test]
+    fn test_bundled_sheets_are_correct() {
+        let converter =
+            OperationsToTurns::new(UFR_EXPANDED, UFR_PARITY, UFR_TWIST, UF_EXPANDED, UF_FLIP)
+                .unwrap();
+        let report = converter.verify();
+
+        assert!(report.is_ok(), "{}", report);
+        assert!(report.redundant.is_empty(), "{}", report);
+        // 378 + 21 + 14 + 440 + 11
+        assert_eq!(report.checked, 864);
+    }
+
+    #[test]
+    fn test_reports_wrong_missing_and_redundant() {
+        let mut ufr: Value = serde_json::from_str(UFR_EXPANDED).unwrap();
+        // 逆向きの 3-cycle に差し替える
+        let inverse = ufr["RDB"]["RDF"].clone();
+        ufr["RDB"]["RDF"] = ufr["RDF"]["RDB"].clone();
+        ufr["RDF"]["RDB"] = inverse;
+        ufr["UBL"].as_object_mut().unwrap().remove("DFL");
+
+        let mut flip: Value = serde_json::from_str(UF_FLIP).unwrap();
+        flip["UF"] = Value::String("M' U M' U M' U M' U".to_string());
+
+        let converter = OperationsToTurns::new(
+            &ufr.to_string(),
+            UFR_PARITY,
+            UFR_TWIST,
+            UF_EXPANDED,
+            &flip.to_string(),
+        )
+        .unwrap();
+        let report = converter.verify();
+
+        assert!(!report.is_ok());
+        let keys = |entries: &[SheetEntry]| -> Vec<String> {
+            let mut keys: Vec<String> = entries
+                .iter()
+                .map(|e| format!("{} [{}]", e.table, e.key))
+                .collect();
+            keys.sort();
+            keys
+        };
+        assert_eq!(
+            keys(&report.wrong),
+            vec!["ufr_expanded [RDB-RDF]", "ufr_expanded [RDF-RDB]"]
+        );
+        assert_eq!(keys(&report.missing), vec!["ufr_expanded [UBL-DFL]"]);
+        assert_eq!(keys(&report.redundant), vec!["uf_flip [UF]"]);
+        assert!(report
+            .to_string()
+            .starts_with("Checked 863 entries: 2 wrong, 1 missing, 1 redundant\nWrong:\n"));
+    }
+
+    #[test]
+    fn test_ubl_buffer_sheet() {
+        let config = SolverConfig::from_names("UBL", "UF", ["UB", "UL"]).unwrap();
+        let converter = OperationsToTurns::new(
+            include_str!("../../resources/ubl_expanded.json"),
+            include_str!("../../resources/ubl_parity.json"),
+            "{}",
+            "{}",
+            "{}",
+        )
+        .unwrap()
+        .with_config(config);
+        let report = converter.verify();
+
+        // 2 点交換 180 個とパリティ 21 個（空の UBL, LUB, BUL を除く）を確かめる
+        assert_eq!(report.checked, 201);
+        // UBL のシートは 2 点交換 378 個のうち 180 個だけを持つ
+        assert_eq!(
+            report
+                .missing
+                .iter()
+                .filter(|e| e.table == "ufr_expanded")
+                .count(),
+            198
+        );
+        assert!(report.wrong.is_empty(), "{}", report);
+        assert!(report.redundant.is_empty());
+    }
+
+    #[test]
+    fn test_floating_buffer_sheet() {
+        let converter =
+            OperationsToTurns::new(UFR_EXPANDED, UFR_PARITY, UFR_TWIST, UF_EXPANDED, UF_FLIP)
+                .unwrap()
+                .with_corner_buffer_sheet(
+                    0,
+                    include_str!("../../resources/ubl_expanded.json"),
+                    "{}",
+                )
+                .unwrap();
+        let report = converter.verify();
+
+        // UFR の表はそのまま、UBL の表は UBL をバッファとして確かめる
+        assert!(report.wrong.is_empty(), "{}", report);
+        assert!(report.redundant.is_empty(), "{}", report);
+        assert!(report.checked > 864);
+        assert!(report.missing.iter().all(|e| e.table.starts_with("ubl_")));
+        assert_eq!(
+            report
+                .missing
+                .iter()
+                .filter(|e| e.table == "ubl_parity")
+                .count(),
+            21
+        );
+    }
+
+    #[test]
+    fn test_orientation_pair_tables() {
+        // 1つずつの手順を2つ続けると、バッファのねじれ・反転は打ち消し合う
+        let twist: Value = serde_json::from_str(UFR_TWIST).unwrap();
+        let flip: Value = serde_json::from_str(UF_FLIP).unwrap();
+        let pairs = |singles: &Value, keys: &[(&str, &str)]| {
+            let mut table = serde_json::Map::new();
+            for (first, second) in keys {
+                let alg = format!("{} {}", singles[first], singles[second]).replace('"', "");
+                table
+                    .entry(first.to_string())
+                    .or_insert_with(|| Value::Object(serde_json::Map::new()))[second] =
+                    Value::String(alg);
+            }
+            Value::Object(table).to_string()
+        };
+
+        // 表は逆の順 (FDR-BUL) でもよい
+        let twist_pairs = pairs(&twist, &[("FDR", "BUL"), ("LUB", "RDF")]);
+        let flip_pairs = pairs(&flip, &[("UB", "UR"), ("DL", "BL")]);
+
+        let converter =
+            OperationsToTurns::new(UFR_EXPANDED, UFR_PARITY, UFR_TWIST, UF_EXPANDED, UF_FLIP)
+                .unwrap()
+                .with_config(SolverConfig::default().with_pair_orientations(true))
+                .with_twist_pair_table(&twist_pairs)
+                .unwrap()
+                .with_flip_pair_table(&flip_pairs)
+                .unwrap();
+        let report = converter.verify();
+
+        assert!(report.wrong.is_empty(), "{}", report);
+        assert!(report.redundant.is_empty(), "{}", report);
+        assert_eq!(report.checked, 864 + 4);
+        // 逆向きのねじれの組 21 * 2 と、反転の組 55
+        let missing = |table: &str| report.missing.iter().filter(|e| e.table == table).count();
+        assert_eq!(missing("ufr_twist_pairs"), 42 - 2);
+        assert_eq!(missing("uf_flip_pairs"), 55 - 2);
+        assert!(report
+            .missing
+            .iter()
+            .all(|e| e.key != "BUL-FDR" && e.key != "BL-DL"));
+    }
+}
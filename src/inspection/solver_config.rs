@@ -0,0 +1,466 @@
+use crate::cube::state::{CORNER_NAMES, EDGE_NAMES};
+use serde::{Deserialize, Serialize};
+
+/// 新しいループを始めるコーナーの優先順の既定値
+const DEFAULT_CORNER_PRIORITY: [usize; 8] = [1, 3, 0, 4, 5, 6, 7, 2];
+/// 新しいループを始めるエッジの優先順の既定値
+const DEFAULT_EDGE_PRIORITY: [usize; 12] = [0, 1, 2, 3, 4, 5, 7, 8, 9, 10, 11, 6];
+
+/// How to choose the piece that starts a new cycle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CycleBreak {
+    /// 優先順で最初の未完成のピース
+    #[default]
+    Priority,
+    /// 組になる交換の手順が最も短いピース（同じ長さなら優先順）
+    Shortest,
+}
+
+/// How an edge swap left without a pair (edge parity) is solved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EdgeParity {
+    /// コーナーがパリティの場合、エッジは `parity_edges` を入れ替えて分析する（交換分析モード）
+    ///
+    /// 残ったエッジの交換は、もう一方のパリティのエッジとの組の手順にし、
+    /// パリティのエッジの交換はコーナーのパリティ手順に任せる
+    #[default]
+    SwapParityEdges,
+    /// エッジはそのまま分析し、残った交換はエッジのパリティ手順表（`uf_parity`）を使う
+    ///
+    /// 表の手順は、バッファとターゲットの交換に加えて `parity_edges` の2つを交換するもの
+    /// （コーナーのパリティ手順がもう一度交換して元に戻す）
+    Table,
+    /// エッジはそのまま分析し、残った交換はエラーにする
+    Error,
+}
+
+/// Buffers, parity and cycle-break settings shared by the inspection, the
+/// alternative generators and the alg tables
+///
+/// Pieces are indexed like `State::cp` / `State::ep`. The default is the
+/// UFR / UF setup that the bundled `ufr_*.json` and `uf_*.json` sheets use.
+///
+/// Floating buffers are off unless `floating_corner_buffers` or
+/// `floating_edge_buffers` is set: once the current buffer's cycle closes with
+/// its swaps in complete pairs, the inspection moves on to the first unsolved
+/// floating buffer instead of breaking into a new cycle.
+///
+/// Deserializing runs the same checks as the constructors (`validate`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "SolverConfigFields")]
+pub struct SolverConfig {
+    /// コーナーのバッファ
+    pub corner_buffer: usize,
+    /// エッジのバッファ
+    pub edge_buffer: usize,
+    /// コーナーのパリティ手順で一緒に交換される2つのエッジ
+    ///
+    /// パリティがある場合、エッジはこの2つを入れ替えたものとして分析する（交換分析モード）
+    pub parity_edges: [usize; 2],
+    /// 新しいループを始めるコーナーの優先順（バッファは飛ばす）
+    pub corner_priority: [usize; 8],
+    /// 新しいループを始めるエッジの優先順（バッファは飛ばす）
+    pub edge_priority: [usize; 12],
+    /// 新しいループを始めるピースの選び方
+    pub cycle_break: CycleBreak,
+    /// エッジのパリティの扱い
+    pub edge_parity: EdgeParity,
+    /// バッファが揃った後に使うコーナーのバッファ（使う順）
+    pub floating_corner_buffers: Vec<usize>,
+    /// バッファが揃った後に使うエッジのバッファ（使う順）
+    pub floating_edge_buffers: Vec<usize>,
+    /// ねじれたコーナー・反転したエッジを2つずつ組にして、1つの手順で揃える
+    ///
+    /// 組の手順表（`ufr_twist_pairs` / `uf_flip_pairs`）に無い組は1つずつ揃える
+    pub pair_orientations: bool,
+    /// 新しいループを始めるとき、その場でねじれたコーナーも候補にする
+    ///
+    /// ねじれたコーナーから始めたループは、そのコーナーに戻ったときに向きも揃う
+    pub twist_in_cycle: bool,
+}
+
+impl SolverConfig {
+    /// バッファとパリティのエッジを指定して作成
+    ///
+    /// 存在しないピースや、同じエッジを2つ指定したパリティはエラー
+    pub fn new(
+        corner_buffer: usize,
+        edge_buffer: usize,
+        parity_edges: [usize; 2],
+    ) -> Result<Self, String> {
+        let config = Self {
+            corner_buffer,
+            edge_buffer,
+            parity_edges,
+            corner_priority: DEFAULT_CORNER_PRIORITY,
+            edge_priority: DEFAULT_EDGE_PRIORITY,
+            cycle_break: CycleBreak::Priority,
+            edge_parity: EdgeParity::SwapParityEdges,
+            floating_corner_buffers: Vec::new(),
+            floating_edge_buffers: Vec::new(),
+            pair_orientations: false,
+            twist_in_cycle: false,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 全ての設定が正しいか
+    ///
+    /// バッファとパリティのエッジが存在するピースか、パリティのエッジが異なるか、
+    /// 優先順が全てのピースを1回ずつ含むか、フローティングバッファが正しいかを確かめる
+    pub fn validate(&self) -> Result<(), String> {
+        if self.corner_buffer >= CORNER_NAMES.len() {
+            return Err(format!("Invalid corner buffer: {}", self.corner_buffer));
+        }
+        if self.edge_buffer >= EDGE_NAMES.len() {
+            return Err(format!("Invalid edge buffer: {}", self.edge_buffer));
+        }
+        if let Some(&edge) = self.parity_edges.iter().find(|&&e| e >= EDGE_NAMES.len()) {
+            return Err(format!("Invalid parity edge: {}", edge));
+        }
+        if self.parity_edges[0] == self.parity_edges[1] {
+            return Err(format!(
+                "Parity edges must be two different edges: {}",
+                EDGE_NAMES[self.parity_edges[0]]
+            ));
+        }
+        // 全てのピースを含む優先順なら、付け足すものはない
+        complete_priority(&self.corner_priority, DEFAULT_CORNER_PRIORITY)?;
+        complete_priority(&self.edge_priority, DEFAULT_EDGE_PRIORITY)?;
+        check_floating_buffers(&self.floating_corner_buffers, self.corner_buffer, 8)?;
+        check_floating_buffers(&self.floating_edge_buffers, self.edge_buffer, 12)?;
+        Ok(())
+    }
+
+    /// 新しいループを始めるコーナーの優先順を設定
+    ///
+    /// 含まれないコーナーは既定の順で後ろに付け足す
+    pub fn with_corner_priority(mut self, priority: &[usize]) -> Result<Self, String> {
+        self.corner_priority = complete_priority(priority, DEFAULT_CORNER_PRIORITY)?;
+        Ok(self)
+    }
+
+    /// 新しいループを始めるエッジの優先順を設定
+    ///
+    /// 含まれないエッジは既定の順で後ろに付け足す
+    pub fn with_edge_priority(mut self, priority: &[usize]) -> Result<Self, String> {
+        self.edge_priority = complete_priority(priority, DEFAULT_EDGE_PRIORITY)?;
+        Ok(self)
+    }
+
+    /// 新しいループを始めるピースの選び方を設定
+    pub fn with_cycle_break(mut self, cycle_break: CycleBreak) -> Self {
+        self.cycle_break = cycle_break;
+        self
+    }
+
+    /// エッジのパリティの扱いを設定
+    pub fn with_edge_parity(mut self, edge_parity: EdgeParity) -> Self {
+        self.edge_parity = edge_parity;
+        self
+    }
+
+    /// フローティングで使うコーナーのバッファを設定（使う順）
+    pub fn with_floating_corner_buffers(mut self, buffers: &[usize]) -> Result<Self, String> {
+        check_floating_buffers(buffers, self.corner_buffer, 8)?;
+        self.floating_corner_buffers = buffers.to_vec();
+        Ok(self)
+    }
+
+    /// フローティングで使うエッジのバッファを設定（使う順）
+    pub fn with_floating_edge_buffers(mut self, buffers: &[usize]) -> Result<Self, String> {
+        check_floating_buffers(buffers, self.edge_buffer, 12)?;
+        self.floating_edge_buffers = buffers.to_vec();
+        Ok(self)
+    }
+
+    /// ねじれ・反転を2つずつ組にして揃えるかを設定
+    pub fn with_pair_orientations(mut self, pair_orientations: bool) -> Self {
+        self.pair_orientations = pair_orientations;
+        self
+    }
+
+    /// ねじれたコーナーから新しいループを始められるかを設定
+    pub fn with_twist_in_cycle(mut self, twist_in_cycle: bool) -> Self {
+        self.twist_in_cycle = twist_in_cycle;
+        self
+    }
+
+    /// 使う順に並べたコーナーのバッファ（最初は `corner_buffer`）
+    pub fn corner_buffers(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::once(self.corner_buffer).chain(self.floating_corner_buffers.iter().copied())
+    }
+
+    /// 使う順に並べたエッジのバッファ（最初は `edge_buffer`）
+    pub fn edge_buffers(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::once(self.edge_buffer).chain(self.floating_edge_buffers.iter().copied())
+    }
+
+    /// "UBL", "UF" のようなピースの名前から作成
+    ///
+    /// # Example
+    /// ```
+    /// use bld_simulator::inspection::SolverConfig;
+    ///
+    /// // resources/ubl_*.json のパリティ手順は UB と UL を交換する
+    /// let config = SolverConfig::from_names("UBL", "UF", ["UB", "UL"]).unwrap();
+    /// assert_eq!(config.corner_buffer, 0);
+    /// assert_eq!(config.parity_edges, [4, 7]);
+    /// ```
+    pub fn from_names(
+        corner_buffer: &str,
+        edge_buffer: &str,
+        parity_edges: [&str; 2],
+    ) -> Result<Self, String> {
+        let corner = |name: &str| {
+            CORNER_NAMES
+                .iter()
+                .position(|&n| n == name)
+                .ok_or_else(|| format!("Unknown corner: {}", name))
+        };
+        let edge = |name: &str| {
+            EDGE_NAMES
+                .iter()
+                .position(|&n| n == name)
+                .ok_or_else(|| format!("Unknown edge: {}", name))
+        };
+
+        Self::new(
+            corner(corner_buffer)?,
+            edge(edge_buffer)?,
+            [edge(parity_edges[0])?, edge(parity_edges[1])?],
+        )
+    }
+
+    /// 交換分析モードでの値変換を行う
+    ///
+    /// 交換分析モードの場合、パリティで交換される2つのエッジの値を入れ替える
+    pub(crate) fn convert_edge_for_swap_inspection(
+        &self,
+        value: u8,
+        use_swap_inspection: bool,
+    ) -> u8 {
+        let [a, b] = self.parity_edges;
+        match value as usize {
+            v if use_swap_inspection && v == a => b as u8,
+            v if use_swap_inspection && v == b => a as u8,
+            _ => value,
+        }
+    }
+}
+
+/// デシリアライズした `SolverConfig` の値（`validate` を通してから使う）
+#[derive(Deserialize)]
+struct SolverConfigFields {
+    corner_buffer: usize,
+    edge_buffer: usize,
+    parity_edges: [usize; 2],
+    corner_priority: [usize; 8],
+    edge_priority: [usize; 12],
+    cycle_break: CycleBreak,
+    edge_parity: EdgeParity,
+    floating_corner_buffers: Vec<usize>,
+    floating_edge_buffers: Vec<usize>,
+    pair_orientations: bool,
+    twist_in_cycle: bool,
+}
+
+impl TryFrom<SolverConfigFields> for SolverConfig {
+    type Error = String;
+
+    fn try_from(fields: SolverConfigFields) -> Result<Self, String> {
+        let config = SolverConfig {
+            corner_buffer: fields.corner_buffer,
+            edge_buffer: fields.edge_buffer,
+            parity_edges: fields.parity_edges,
+            corner_priority: fields.corner_priority,
+            edge_priority: fields.edge_priority,
+            cycle_break: fields.cycle_break,
+            edge_parity: fields.edge_parity,
+            floating_corner_buffers: fields.floating_corner_buffers,
+            floating_edge_buffers: fields.floating_edge_buffers,
+            pair_orientations: fields.pair_orientations,
+            twist_in_cycle: fields.twist_in_cycle,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// 一部だけ指定された優先順に、残りのピースを既定の順で付け足す
+fn complete_priority<const N: usize>(
+    priority: &[usize],
+    default: [usize; N],
+) -> Result<[usize; N], String> {
+    let mut result = Vec::with_capacity(N);
+    for &piece in priority {
+        if piece >= N {
+            return Err(format!("Invalid piece in priority: {}", piece));
+        }
+        if result.contains(&piece) {
+            return Err(format!("Duplicate piece in priority: {}", piece));
+        }
+        result.push(piece);
+    }
+    result.extend(
+        default
+            .into_iter()
+            .filter(|piece| !priority.contains(piece)),
+    );
+
+    Ok(result.try_into().expect("priority covers every piece"))
+}
+
+/// フローティングバッファの並びが正しいか（範囲内、重複なし、最初のバッファを含まない）
+fn check_floating_buffers(buffers: &[usize], buffer: usize, len: usize) -> Result<(), String> {
+    for (i, &piece) in buffers.iter().enumerate() {
+        if piece >= len {
+            return Err(format!("Invalid floating buffer: {}", piece));
+        }
+        if piece == buffer || buffers[..i].contains(&piece) {
+            return Err(format!("Duplicate buffer: {}", piece));
+        }
+    }
+    Ok(())
+}
+
+impl Default for SolverConfig {
+    /// UFR / UF バッファ、パリティは UF と UR の交換
+    fn default() -> Self {
+        Self::new(2, 6, [6, 5]).expect("UFR / UF buffers are valid")
+    }
+}
+
+impl std::fmt::Display for SolverConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Corner buffer: {}, Edge buffer: {}, Parity: {} ↔ {}",
+            CORNER_NAMES[self.corner_buffer],
+            EDGE_NAMES[self.edge_buffer],
+            EDGE_NAMES[self.parity_edges[0]],
+            EDGE_NAMES[self.parity_edges[1]]
+        )?;
+        if !self.floating_corner_buffers.is_empty() {
+            let names: Vec<&str> = self
+                .floating_corner_buffers
+                .iter()
+                .map(|&i| CORNER_NAMES[i])
+                .collect();
+            write!(f, ", Floating corners: {}", names.join(" "))?;
+        }
+        if !self.floating_edge_buffers.is_empty() {
+            let names: Vec<&str> = self
+                .floating_edge_buffers
+                .iter()
+                .map(|&i| EDGE_NAMES[i])
+                .collect();
+            write!(f, ", Floating edges: {}", names.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_names() {
+        assert_eq!(
+            SolverConfig::from_names("UFR", "UF", ["UF", "UR"]).unwrap(),
+            SolverConfig::default()
+        );
+        assert!(SolverConfig::from_names("UFX", "UF", ["UF", "UR"]).is_err());
+        assert!(SolverConfig::from_names("UFR", "UF", ["UR", "UR"]).is_err());
+
+        let config = SolverConfig::from_names("UBL", "DF", ["UB", "UL"]).unwrap();
+        assert_eq!(config, SolverConfig::new(0, 10, [4, 7]).unwrap());
+        assert_eq!(
+            config.to_string(),
+            "Corner buffer: UBL, Edge buffer: DF, Parity: UB ↔ UL"
+        );
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_pieces() {
+        assert!(SolverConfig::new(8, 6, [6, 5]).is_err());
+        assert!(SolverConfig::new(2, 12, [6, 5]).is_err());
+        assert!(SolverConfig::new(2, 6, [6, 12]).is_err());
+        assert_eq!(
+            SolverConfig::new(2, 6, [5, 5]).unwrap_err(),
+            "Parity edges must be two different edges: UR"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_validates() {
+        let json = serde_json::to_string(&SolverConfig::default()).unwrap();
+        let config: SolverConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(config, SolverConfig::default());
+
+        let bad_buffer = json.replace("\"corner_buffer\":2", "\"corner_buffer\":9");
+        let err = serde_json::from_str::<SolverConfig>(&bad_buffer).unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid corner buffer: 9"),
+            "{}",
+            err
+        );
+
+        let bad_priority = json.replace("[1,3,0,4,5,6,7,2]", "[1,1,0,4,5,6,7,2]");
+        assert!(serde_json::from_str::<SolverConfig>(&bad_priority).is_err());
+
+        let mut config = SolverConfig::default();
+        config.floating_edge_buffers = vec![6];
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_priority() {
+        let config = SolverConfig::default()
+            .with_corner_priority(&[5, 1])
+            .unwrap()
+            .with_edge_priority(&[])
+            .unwrap();
+        assert_eq!(config.corner_priority, [5, 1, 3, 0, 4, 6, 7, 2]);
+        assert_eq!(config.edge_priority, SolverConfig::default().edge_priority);
+
+        assert!(SolverConfig::default().with_corner_priority(&[8]).is_err());
+        assert!(SolverConfig::default().with_edge_priority(&[3, 3]).is_err());
+    }
+
+    #[test]
+    fn test_floating_buffers() {
+        let config = SolverConfig::default()
+            .with_floating_corner_buffers(&[3, 1])
+            .unwrap()
+            .with_floating_edge_buffers(&[5])
+            .unwrap();
+        assert_eq!(config.corner_buffers().collect::<Vec<_>>(), vec![2, 3, 1]);
+        assert_eq!(config.edge_buffers().collect::<Vec<_>>(), vec![6, 5]);
+        assert_eq!(
+            SolverConfig::default().corner_buffers().collect::<Vec<_>>(),
+            vec![2]
+        );
+
+        assert!(SolverConfig::default()
+            .with_floating_corner_buffers(&[2])
+            .is_err());
+        assert!(SolverConfig::default()
+            .with_floating_corner_buffers(&[8])
+            .is_err());
+        assert!(SolverConfig::default()
+            .with_floating_edge_buffers(&[5, 5])
+            .is_err());
+    }
+
+    #[test]
+    fn test_convert_edge_for_swap_inspection() {
+        let config = SolverConfig::default();
+        assert_eq!(config.convert_edge_for_swap_inspection(5, true), 6);
+        assert_eq!(config.convert_edge_for_swap_inspection(6, true), 5);
+        assert_eq!(config.convert_edge_for_swap_inspection(4, true), 4);
+        assert_eq!(config.convert_edge_for_swap_inspection(5, false), 5);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::parser::ParseError;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Error from loading an alg table (JSON or CSV sheet)
+///
+/// Unreadable algs keep the typed `ParseError`, whose `location` is the
+/// table entry (`ParseLocation::Sticker`) or the CSV cell
+/// (`ParseLocation::Cell`).
+#[derive(Debug)]
+pub enum TableError {
+    /// JSON として読めない（`table` は "ufr_parity" などの表の名前）
+    Json {
+        table: String,
+        source: serde_json::Error,
+    },
+    /// CSV ファイルを開けない、または空
+    Csv { path: PathBuf, message: String },
+    /// 手順が読めない（CSV から読んだ場合は `path` にそのファイル）
+    Parse {
+        path: Option<PathBuf>,
+        source: Box<ParseError>,
+    },
+    /// 存在しないピースのバッファ
+    InvalidBuffer { piece: &'static str, buffer: usize },
+}
+
+impl TableError {
+    /// 手順が読めなかった場合のエラー
+    pub fn parse_error(&self) -> Option<&ParseError> {
+        match self {
+            TableError::Parse { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::Json { table, source } => {
+                write!(f, "Failed to parse {}: {}", table, source)
+            }
+            TableError::Csv { path, message } => {
+                write!(f, "Failed to load {}: {}", path.display(), message)
+            }
+            TableError::Parse {
+                path: Some(path),
+                source,
+            } => write!(f, "Failed to load {}: {}", path.display(), source),
+            TableError::Parse { path: None, source } => write!(f, "{}", source),
+            TableError::InvalidBuffer { piece, buffer } => {
+                write!(f, "Invalid {} buffer: {}", piece, buffer)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TableError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TableError::Json { source, .. } => Some(source),
+            TableError::Parse { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for TableError {
+    fn from(source: ParseError) -> Self {
+        TableError::Parse {
+            path: None,
+            source: Box::new(source),
+        }
+    }
+}
+
+impl From<TableError> for String {
+    fn from(err: TableError) -> Self {
+        err.to_string()
+    }
+}
@@ -13,16 +13,22 @@ pub mod wasm;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use cli_state_input::StateInputEditor;
-pub use cube::{Move, PartialStatePattern, RubiksCube, SolutionSearcher, State};
+pub use cube::{
+    FaceletError, MoveSet, PartialStatePattern, RandomStateGenerator, RubiksCube,
+    ScrambleConstraint, SolutionSearcher, State, StateIssue, StateValidation, TwoPhaseSolver,
+};
 #[cfg(not(target_arch = "wasm32"))]
 pub use display::{CubeColor, CubeDisplay, CubeNetWidget, Face, StateToDisplay};
 pub use inspection::{
-    CornerInspection, CornerOperation, CornerSwapOperation, CornerTwistOperation,
-    EdgeFlipOperation, EdgeInspection, EdgeOperation, EdgeSwapOperation, MoveSequence,
-    OperationsToTurns,
+    BldMethod, CornerInspection, CornerOperation, CornerSwapOperation, CornerTwistOperation,
+    CycleBreak, EdgeFlipOperation, EdgeInspection, EdgeOperation, EdgeParity, EdgeSwapOperation,
+    LetterScheme, M2Edges, MoveSequence, OldPochmann, OperationsToTurns, R2Corners, SheetEntry,
+    SheetReport, SolverConfig, TableError,
 };
 pub use parser::{
-    parse_3style_csv, parse_and_expand, parse_sequence, sequence_to_string, NotationMove, Sequence,
+    find_commutator, parse_3style_csv, parse_alg_list_csv, parse_and_expand, parse_sequence,
+    sequence_to_string, simplify, Metric, MoveCount, NotationMove, NotationStyle, ParseError,
+    ParseLocation, Sequence,
 };
 #[cfg(not(target_arch = "wasm32"))]
 pub use workflow::BldWorkflow;
@@ -1,4 +1,5 @@
-use bld_simulator::{State, StateInputEditor};
+use bld_simulator::{LetterScheme, OperationsToTurns, State, StateInputEditor};
+use bld_simulator::parser::{find_commutator, parse_and_expand, sequence_to_string};
 use bld_simulator::workflow::CombinedNearbySearchWorkflow;
 use ratatui::{
     backend::CrosstermBackend,
@@ -10,7 +11,81 @@ use ratatui::{
 };
 use std::{fs, io, env};
 
+/// `bld_simulator analyze [--cancel] <alg>`: 手順を `[A: [B, C]]` の形に直して表示する
+fn analyze(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let allow_cancellation = args.iter().any(|a| a == "--cancel");
+    let alg = args
+        .iter()
+        .filter(|a| *a != "--cancel")
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let sequence = parse_and_expand(&alg)?;
+    println!("Alg: {}", sequence_to_string(&sequence));
+
+    match find_commutator(&sequence, allow_cancellation) {
+        Some(notation) => println!("Notation: {}", notation),
+        None if allow_cancellation => println!("✗ Not a commutator or conjugate"),
+        None => println!("✗ Not a commutator or conjugate (try --cancel to allow cancellation)"),
+    }
+
+    Ok(())
+}
+
+/// 手順表の JSON があるディレクトリ
+fn resources_dir() -> String {
+    if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+        // Running with cargo
+        format!("{}/resources", manifest_dir)
+    } else {
+        // Running as installed binary
+        "resources".to_string()
+    }
+}
+
+/// `bld_simulator verify [dir]`: 手順表の各手順が正しいサイクルになっているか調べる
+fn verify(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = args.first().cloned().unwrap_or_else(resources_dir);
+    let read = |name: &str| fs::read_to_string(format!("{}/{}.json", dir, name));
+
+    let converter = OperationsToTurns::new(
+        &read("ufr_expanded")?,
+        &read("ufr_parity")?,
+        &read("ufr_twist")?,
+        &read("uf_expanded")?,
+        &read("uf_flip")?,
+    )?;
+    let report = converter.verify();
+    print!("{}", report);
+
+    if !report.is_ok() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// `--scheme <file>` で指定した文字の割り当て（省略時と `speffz` は Speffz）
+fn letter_scheme(args: &[String]) -> Result<LetterScheme, Box<dyn std::error::Error>> {
+    let Some(i) = args.iter().position(|a| a == "--scheme") else {
+        return Ok(LetterScheme::speffz());
+    };
+    match args.get(i + 1).map(String::as_str) {
+        None => Err("--scheme requires a file path or `speffz`".into()),
+        Some("speffz") => Ok(LetterScheme::speffz()),
+        Some(path) => Ok(LetterScheme::from_json(&fs::read_to_string(path)?)?),
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("analyze") => return analyze(&args[1..]),
+        Some("verify") => return verify(&args[1..]),
+        _ => {}
+    }
+    let scheme = letter_scheme(&args)?;
+
     // Enable TUI mode
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -19,7 +94,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create and run the state input editor
-    let mut editor = StateInputEditor::new();
+    let mut editor = StateInputEditor::new().with_letter_scheme(scheme.clone());
     let result = editor.run(&mut terminal);
 
     // Disable TUI mode
@@ -42,13 +117,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             let target_state = State::from_arrays(cp, co, ep, eo);
 
             // Determine the resources directory
-            let resources_dir = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
-                // Running with cargo
-                format!("{}/resources", manifest_dir)
-            } else {
-                // Running as installed binary
-                "resources".to_string()
-            };
+            let resources_dir = resources_dir();
 
             // Load JSON resources
             let ufr_expanded = fs::read_to_string(format!("{}/ufr_expanded.json", resources_dir))?;
@@ -76,7 +145,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             match workflow.search_from_scramble(&scramble, &target_state) {
                 Ok(result) => {
                     // Display detailed results using display_detailed
-                    println!("{}", result.display_detailed(5));
+                    println!("{}", result.display_detailed_with_scheme(5, &scheme));
                 }
                 Err(e) => {
                     eprintln!("✗ Search failed: {}", e);
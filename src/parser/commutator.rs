@@ -0,0 +1,309 @@
+use super::move_parser::{reversed_sequence, NotationMove, Sequence};
+use super::notation_expander::Notation;
+use super::simplifier::{decompose, kind_move, simplify};
+
+/// 見つかった分解と、その記法で書く手数
+struct Candidate {
+    notation: Notation,
+    cost: usize,
+}
+
+/// Find the shortest commutator notation for a sequence
+///
+/// Looks for `[A: [B, C]]`, `[A: m/Y]` and their forms without a setup
+/// move `A`, and returns the one with the fewest written moves. With
+/// `allow_cancellation` the expansion only has to equal `seq` after
+/// cancellation (so `R U R' D R U' R' D'` and algs whose setup merges into
+/// the commutator are both found). Without it the expansion must be exactly
+/// `seq`. When only the outer `A X A'` structure exists, falls back to
+/// `[A: X]` with `X` written out (e.g. `R U R'` is `[R: U]`). Returns `None`
+/// when the sequence is not even a conjugate.
+pub fn find_commutator(seq: &Sequence, allow_cancellation: bool) -> Option<Notation> {
+    let conjugates = if allow_cancellation {
+        cancelled_conjugates(&simplify(seq))
+    } else {
+        exact_conjugates(seq)
+    };
+
+    let mut best: Option<Candidate> = None;
+    for (setup, inner) in &conjugates {
+        let bound = best.as_ref().map_or(usize::MAX, |b| b.cost);
+        if setup.len() >= bound {
+            continue;
+        }
+
+        let found = if allow_cancellation {
+            cancelled_inner(inner, bound - setup.len())
+        } else {
+            exact_inner(inner)
+        };
+
+        for candidate in found {
+            let cost = setup.len() + candidate.cost;
+            if best.as_ref().is_some_and(|b| b.cost <= cost) {
+                continue;
+            }
+            let notation = if setup.is_empty() {
+                candidate.notation
+            } else {
+                Notation::Colon {
+                    seq_a: Box::new(Notation::Plain(setup.clone())),
+                    seq_b: Box::new(candidate.notation),
+                }
+            };
+            best = Some(Candidate { notation, cost });
+        }
+    }
+
+    best.or_else(|| plain_conjugate(conjugates, seq.len()))
+        .map(|b| b.notation)
+}
+
+/// 交換子が見つからないときの `[A: X]`（X はそのまま書く）
+///
+/// 書く手数が最も少ないもの。元の手順より短くならなければ `None`
+fn plain_conjugate(conjugates: Vec<(Sequence, Sequence)>, seq_len: usize) -> Option<Candidate> {
+    conjugates
+        .into_iter()
+        .filter(|(setup, _)| !setup.is_empty())
+        .map(|(setup, inner)| Candidate {
+            cost: setup.len() + inner.len(),
+            notation: Notation::Colon {
+                seq_a: Box::new(Notation::Plain(setup)),
+                seq_b: Box::new(Notation::Plain(inner)),
+            },
+        })
+        .filter(|candidate| candidate.cost < seq_len)
+        .min_by_key(|candidate| candidate.cost)
+}
+
+/// `[B, C]` の記法
+fn comma(b: Sequence, c: Sequence) -> Notation {
+    Notation::Comma {
+        seq_a: Box::new(Notation::Plain(b)),
+        seq_b: Box::new(Notation::Plain(c)),
+    }
+}
+
+/// `m/Y` の記法
+fn slash(mov: NotationMove, y: Sequence) -> Notation {
+    Notation::Slash {
+        mov,
+        seq: Box::new(Notation::Plain(y)),
+    }
+}
+
+/// 手順を繋げて打ち消すと何も残らないか
+fn cancels(parts: &[&[NotationMove]]) -> bool {
+    simplify(&parts.concat()).is_empty()
+}
+
+/// 90 度回しの手か（2 回転や Noop ではない）
+fn is_quarter_turn(mv: &NotationMove) -> bool {
+    matches!(decompose(mv), Some((_, _, 1 | 3)))
+}
+
+/// 手順をそのまま `A X A'` に分ける全ての方法（A が空の場合を含む）
+fn exact_conjugates(seq: &Sequence) -> Vec<(Sequence, Sequence)> {
+    let n = seq.len();
+    (0..=n / 2)
+        .filter(|&k| seq[n - k..] == reversed_sequence(&seq[..k].to_vec())[..])
+        .map(|k| (seq[..k].to_vec(), seq[k..n - k].to_vec()))
+        .filter(|(_, inner)| !inner.is_empty())
+        .collect()
+}
+
+/// 手順そのものが `[B, C]` か `m/Y` になっているもの
+fn exact_inner(x: &Sequence) -> Vec<Candidate> {
+    let n = x.len();
+    let mut result = Vec::new();
+
+    // [B, C] = B C B' C'（偶数手のみ）
+    for b in (1..n / 2).filter(|_| n.is_multiple_of(2)) {
+        let c = n / 2 - b;
+        let (seq_b, seq_c) = (x[..b].to_vec(), x[b..b + c].to_vec());
+        if x[b + c..2 * b + c] == reversed_sequence(&seq_b)[..]
+            && x[2 * b + c..] == reversed_sequence(&seq_c)[..]
+        {
+            result.push(Candidate {
+                notation: comma(seq_b, seq_c),
+                cost: b + c,
+            });
+        }
+    }
+
+    // m/Y = m Y m2 Y' m
+    if n >= 5 && !n.is_multiple_of(2) && is_quarter_turn(&x[0]) && x[n - 1] == x[0] {
+        let y_len = (n - 3) / 2;
+        let y = x[1..1 + y_len].to_vec();
+        if x[1 + y_len] == x[0].doubled() && x[2 + y_len..n - 1] == reversed_sequence(&y)[..] {
+            result.push(Candidate {
+                notation: slash(x[0].clone(), y),
+                cost: 1 + y_len,
+            });
+        }
+    }
+
+    result
+}
+
+/// 同じ種類で回転量だけが違う手（自分自身を含む）
+fn turn_variants(mv: &NotationMove) -> Vec<NotationMove> {
+    match decompose(mv) {
+        Some((axis, kind, _)) => (1..4).map(|amount| kind_move(axis, kind, amount)).collect(),
+        None => vec![mv.clone()],
+    }
+}
+
+/// 先頭から取った部分手順と、その最後の手の回転量だけを変えたもの（空は含まない）
+///
+/// 打ち消しのある手順では、セットアップや B の最後の手が次の手とまとまっていることがあるため
+fn prefix_variants(seq: &Sequence, max_len: usize) -> Vec<Sequence> {
+    (1..=seq.len().min(max_len))
+        .flat_map(|k| {
+            turn_variants(&seq[k - 1]).into_iter().map(move |last| {
+                let mut prefix = seq[..k - 1].to_vec();
+                prefix.push(last);
+                prefix
+            })
+        })
+        .collect()
+}
+
+/// 打ち消しを許して `A X A'` に分ける
+///
+/// A を決めると X = A' T A が決まるので、A の候補ごとに X を求める
+fn cancelled_conjugates(seq: &Sequence) -> Vec<(Sequence, Sequence)> {
+    let mut result = vec![(Vec::new(), seq.clone())];
+    for setup in prefix_variants(seq, seq.len()) {
+        let inner = simplify(&[reversed_sequence(&setup), seq.clone(), setup.clone()].concat());
+        if !inner.is_empty() {
+            result.push((setup, inner));
+        }
+    }
+    result
+}
+
+/// 打ち消しを許して `[B, C]` か `m/Y` に分ける（`bound` 手未満のものだけ）
+fn cancelled_inner(x: &Sequence, bound: usize) -> Vec<Candidate> {
+    let mut result = Vec::new();
+    if x.is_empty() {
+        return result;
+    }
+
+    // X = B C B' C' なら B' X = C B' C' は B' を C で挟んだ形
+    for b in prefix_variants(x, bound.saturating_sub(1)) {
+        let rest = simplify(&[reversed_sequence(&b), x.clone()].concat());
+        for c in prefix_variants(&rest, bound.saturating_sub(b.len() + 1)) {
+            if cancels(&[&reversed_sequence(&c), &rest, &c, &b]) {
+                result.push(Candidate {
+                    cost: b.len() + c.len(),
+                    notation: comma(b.clone(), c),
+                });
+            }
+        }
+    }
+
+    // X = m Y m2 Y' m なら m' X m' = Y m2 Y'
+    for mov in turn_variants(&x[0]).into_iter().filter(is_quarter_turn) {
+        let prime = [mov.reversed()];
+        let conjugated = simplify(&[&prime[..], x, &prime[..]].concat());
+        let double = [mov.doubled()];
+        for y in prefix_variants(&conjugated, bound.saturating_sub(2)) {
+            if cancels(&[&reversed_sequence(&y), &conjugated, &y, &double]) {
+                result.push(Candidate {
+                    cost: 1 + y.len(),
+                    notation: slash(mov.clone(), y),
+                });
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_and_expand, parse_sequence};
+
+    fn find(alg: &str, allow_cancellation: bool) -> Option<String> {
+        find_commutator(&parse_sequence(alg).unwrap(), allow_cancellation).map(|n| n.to_string())
+    }
+
+    #[test]
+    fn test_exact_commutators() {
+        assert_eq!(
+            find("U R' D R U' R' D' R", false).as_deref(),
+            Some("[U, R' D R]")
+        );
+        assert_eq!(
+            find("R' D' U R D R' U2 R D' R' U D R", false).as_deref(),
+            Some("[R' D': [U/R D R']]")
+        );
+        assert_eq!(
+            find("U R U' D R' U' R D' R' U R U R' U'", false).as_deref(),
+            Some("[U R U': [D, R' U' R]]")
+        );
+        assert_eq!(
+            find("R U R' U R U2 R'", false).as_deref(),
+            Some("[R: U R' U R U2]")
+        );
+        assert_eq!(find("R U R' U'", false).as_deref(), Some("[R, U]"));
+        assert_eq!(find("R U F", false), None);
+    }
+
+    #[test]
+    fn test_plain_conjugate() {
+        assert_eq!(find("R U R'", false).as_deref(), Some("[R: U]"));
+        assert_eq!(find("R U R'", true).as_deref(), Some("[R: U]"));
+        assert_eq!(find("F R U R' F'", false).as_deref(), Some("[F R: U]"));
+        // セットアップが打ち消し合うだけなら分解しない
+        assert_eq!(find("R U", true), None);
+    }
+
+    #[test]
+    fn test_round_trip_through_expand() {
+        for notation in [
+            "[R D R': [U, R D' R']]",
+            "[R' E': [R U' R', E']]",
+            "[L F': [L/S]]",
+            "[R, U]",
+        ] {
+            let seq = parse_and_expand(notation).unwrap();
+            let found = find_commutator(&seq, false).unwrap();
+            assert_eq!(found.expand(), seq, "{}", notation);
+            assert!(found.expand().len() <= seq.len());
+        }
+    }
+
+    #[test]
+    fn test_commutator_after_cancellation() {
+        // 展開すると R' R' が R2 にまとまる
+        let expanded = parse_and_expand("[R' D R: [U', R D' R']]").unwrap();
+        let cancelled = simplify(&expanded);
+        assert_eq!(cancelled.len(), expanded.len() - 1);
+        // 打ち消しを許さなければ外側のセットアップしか見つからない
+        assert_eq!(
+            find_commutator(&cancelled, false).unwrap().to_string(),
+            "[R' D: R U' R D' R' U R D R2]"
+        );
+
+        let found = find_commutator(&cancelled, true).unwrap();
+        assert_eq!(found.to_string(), "[R' D R: [U', R D' R']]");
+        assert!(cancels(&[&found.expand(), &reversed_sequence(&cancelled)]));
+    }
+
+    #[test]
+    fn test_expanded_sheet_entry() {
+        // resources/ufr.json の手順（セットアップとの境目に "R' R" が残っている）
+        let seq = parse_sequence("D R' F2 R D' R' D F2 D' R D R' R D'").unwrap();
+        let found = find_commutator(&seq, false).unwrap();
+        assert_eq!(found.to_string(), "[D R': [F2, R D' R' D]]");
+        assert_eq!(found.expand(), seq);
+
+        // 打ち消した後の手順からも同じ長さの分解が見つかる
+        let found = find_commutator(&simplify(&seq), true).unwrap();
+        assert!(cancels(&[&found.expand(), &reversed_sequence(&seq)]));
+    }
+}
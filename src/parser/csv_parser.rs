@@ -3,6 +3,9 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
+use super::error::ParseLocation;
+use super::notation_expander::parse_notation;
+
 /// CSVファイルをパースして二次元のHashMapに変換する
 /// 
 /// 1行目のセル（列ヘッダー）を第1階層のキー、
@@ -16,6 +19,9 @@ use std::path::Path;
 /// FDR,"D: U, R D R'",
 /// ```
 /// 
+/// 各セルは `parse_notation` で記法として読めることを確認し、読めない場合は
+/// そのセルの行・列（1 始まり、見出しを含む）を付けた `ParseError` を返す。
+///
 /// これは以下のような構造に変換される:
 /// ```json
 /// {
@@ -44,8 +50,9 @@ pub fn parse_3style_csv<P: AsRef<Path>>(
     let mut result: HashMap<String, HashMap<String, String>> = HashMap::new();
 
     // 2行目以降を読み込む
-    for line in lines {
+    for (line_index, line) in lines.enumerate() {
         let line = line?;
+        let row = line_index + 2;
         let cells: Vec<String> = parse_csv_line(&line);
 
         if cells.is_empty() {
@@ -64,6 +71,9 @@ pub fn parse_3style_csv<P: AsRef<Path>>(
         // 2列目以降のセルを処理
         for (i, cell_value) in cells.iter().enumerate().skip(1) {
             if !cell_value.is_empty() {
+                parse_notation(cell_value)
+                    .map_err(|e| e.at(ParseLocation::Cell { row, column: i + 1 }))?;
+
                 // 対応する列ヘッダーを取得
                 if let Some(column_key) = column_headers.get(i) {
                     if !column_key.is_empty() {
@@ -82,6 +92,51 @@ pub fn parse_3style_csv<P: AsRef<Path>>(
     Ok(result)
 }
 
+/// 「ステッカー,手順」の2列の CSV ファイルを HashMap に変換する
+///
+/// パリティ・ツイスト・フリップのような1階層の手順表に使う。
+/// 1行目は見出しとして読み飛ばし、手順が空の行は含めない。
+///
+/// # 例
+/// ```text
+/// sticker,alg
+/// UBL,"[R' D R, U2]"
+/// UBR,
+/// ```
+///
+/// 各手順は `parse_3style_csv` と同じく `parse_notation` で確認し、
+/// 読めない場合はそのセルの行・列を付けた `ParseError` を返す。
+pub fn parse_alg_list_csv<P: AsRef<Path>>(
+    path: P,
+) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+    let mut result: HashMap<String, String> = HashMap::new();
+
+    // 1行目（見出し）は読み飛ばす
+    for (line_index, line) in reader.lines().enumerate().skip(1) {
+        let line = line?;
+        let cells: Vec<String> = parse_csv_line(&line);
+
+        let (Some(key), Some(alg)) = (cells.first(), cells.get(1)) else {
+            continue;
+        };
+        if key.is_empty() || alg.is_empty() {
+            continue;
+        }
+
+        parse_notation(alg).map_err(|e| {
+            e.at(ParseLocation::Cell {
+                row: line_index + 1,
+                column: 2,
+            })
+        })?;
+        result.insert(key.clone(), alg.clone());
+    }
+
+    Ok(result)
+}
+
 /// CSV行を解析してセルのベクトルに変換する
 /// 
 /// ダブルクォートで囲まれたセル内のカンマは区切り文字として扱わない
@@ -123,6 +178,7 @@ fn parse_csv_line(line: &str) -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::ParseError;
 
     #[test]
     fn test_parse_csv_line() {
@@ -165,4 +221,40 @@ FDR,"D: U, R D R'","#;
         // クリーンアップ
         std::fs::remove_file(&temp_file).ok();
     }
+
+    #[test]
+    fn test_parse_3style_csv_reports_cell() {
+        let test_csv = r#",UBR,FDR
+UBR,,"D: R D R', U"
+FDR,"D: U, R Q R'","#;
+
+        let temp_file = std::env::temp_dir().join("test_3style_error.csv");
+        std::fs::write(&temp_file, test_csv).unwrap();
+
+        let err = parse_3style_csv(&temp_file).unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(err.location, Some(ParseLocation::Cell { row: 3, column: 2 }));
+        assert_eq!((err.offset, err.token.as_str()), (8, "Q"));
+
+        std::fs::remove_file(&temp_file).ok();
+    }
+
+    #[test]
+    fn test_parse_alg_list_csv() {
+        let test_csv = "sticker,alg\nUBL,\"[R' D R, U2]\"\nUBR,\nRUB,R U R'\n";
+        let temp_file = std::env::temp_dir().join("test_alg_list.csv");
+        std::fs::write(&temp_file, test_csv).unwrap();
+
+        let result = parse_alg_list_csv(&temp_file).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result["UBL"], "[R' D R, U2]");
+        assert_eq!(result["RUB"], "R U R'");
+
+        std::fs::write(&temp_file, "sticker,alg\nUBL,R U\nRUB,R [U\n").unwrap();
+        let err = parse_alg_list_csv(&temp_file).unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(err.location, Some(ParseLocation::Cell { row: 3, column: 2 }));
+
+        std::fs::remove_file(&temp_file).ok();
+    }
 }
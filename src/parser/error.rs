@@ -0,0 +1,108 @@
+use std::fmt;
+
+/// 手順の文字列中でエラーが起きた場所（セルや表のキー）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseLocation {
+    /// CSV のセル（行・列ともに 1 始まり、見出しを含む）
+    Cell { row: usize, column: usize },
+    /// 手順表のエントリ（`key` は "UBR-FDR" のようなステッカーの組）
+    Sticker { table: String, key: String },
+}
+
+impl fmt::Display for ParseLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseLocation::Cell { row, column } => write!(f, "row {}, column {}", row, column),
+            ParseLocation::Sticker { table, key } => write!(f, "{} [{}]", table, key),
+        }
+    }
+}
+
+/// Error from parsing a move sequence or notation
+///
+/// `offset` is the byte offset of `token` in the parsed string. An empty
+/// `token` means the input ended early.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub offset: usize,
+    pub token: String,
+    pub expected: Vec<String>,
+    pub location: Option<ParseLocation>,
+}
+
+impl ParseError {
+    pub fn new(offset: usize, token: &str, expected: &[&str]) -> Self {
+        ParseError {
+            offset,
+            token: token.to_string(),
+            expected: expected.iter().map(|e| e.to_string()).collect(),
+            location: None,
+        }
+    }
+
+    /// 部分文字列の中で起きたエラーを、元の文字列での位置に直す
+    pub fn shifted(mut self, by: usize) -> Self {
+        self.offset += by;
+        self
+    }
+
+    /// エラーが起きたセルや表のキーを付ける
+    pub fn at(mut self, location: ParseLocation) -> Self {
+        self.location = Some(location);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{}: ", location)?;
+        }
+        if self.token.is_empty() {
+            write!(f, "unexpected end of input at byte {}", self.offset)?;
+        } else {
+            write!(f, "unexpected '{}' at byte {}", self.token, self.offset)?;
+        }
+        if !self.expected.is_empty() {
+            write!(f, ", expected {}", self.expected.join(" or "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for String {
+    fn from(err: ParseError) -> Self {
+        err.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let err = ParseError::new(4, "Q", &["move"]);
+        assert_eq!(err.to_string(), "unexpected 'Q' at byte 4, expected move");
+
+        let err =
+            ParseError::new(5, "", &["']'", "','"]).at(ParseLocation::Cell { row: 3, column: 7 });
+        assert_eq!(
+            err.to_string(),
+            "row 3, column 7: unexpected end of input at byte 5, expected ']' or ','"
+        );
+
+        let err = ParseError::new(0, "X", &["move"])
+            .shifted(2)
+            .at(ParseLocation::Sticker {
+                table: "ufr_expanded".to_string(),
+                key: "UBR-FDR".to_string(),
+            });
+        assert_eq!(
+            err.to_string(),
+            "ufr_expanded [UBR-FDR]: unexpected 'X' at byte 2, expected move"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use super::move_parser::{NotationMove, Sequence};
+use super::simplifier::simplify;
+use serde::{Deserialize, Serialize};
+
+/// 手数の数え方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Metric {
+    /// Half turn metric: 面の回転は角度によらず 1、スライスは 2、持ち替えは 0
+    Htm,
+    /// Quarter turn metric: 90 度ごとに 1、スライスはその 2 倍、持ち替えは 0
+    Qtm,
+    /// Slice turn metric: 面・スライス・2 層の回転は 1、持ち替えは 0
+    Stm,
+    /// Execution turn metric: 持ち替えも含めて全ての手が 1
+    Etm,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 4] = [Metric::Htm, Metric::Qtm, Metric::Stm, Metric::Etm];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::Htm => "HTM",
+            Metric::Qtm => "QTM",
+            Metric::Stm => "STM",
+            Metric::Etm => "ETM",
+        }
+    }
+}
+
+/// 手の種類（手数の計算用）
+enum MoveKind {
+    /// 面の回転と 2 層回し
+    Outer,
+    Slice,
+    Rotation,
+}
+
+impl NotationMove {
+    /// この手の手数
+    pub fn count(&self, metric: Metric) -> usize {
+        use NotationMove::*;
+
+        let (kind, half_turn) = match self {
+            Noop => return 0,
+            U2 | D2 | R2 | L2 | F2 | B2 | UWide2 | DWide2 | RWide2 | LWide2 | FWide2 | BWide2 => {
+                (MoveKind::Outer, true)
+            }
+            M2 | S2 | E2 => (MoveKind::Slice, true),
+            X2 | Y2 | Z2 => (MoveKind::Rotation, true),
+            M | MPrime | S | SPrime | E | EPrime => (MoveKind::Slice, false),
+            X | XPrime | Y | YPrime | Z | ZPrime => (MoveKind::Rotation, false),
+            _ => (MoveKind::Outer, false),
+        };
+        let quarters = if half_turn { 2 } else { 1 };
+
+        match (metric, kind) {
+            (Metric::Etm, _) => 1,
+            (_, MoveKind::Rotation) => 0,
+            (Metric::Stm, _) => 1,
+            (Metric::Htm, MoveKind::Outer) => 1,
+            (Metric::Htm, MoveKind::Slice) => 2,
+            (Metric::Qtm, MoveKind::Outer) => quarters,
+            (Metric::Qtm, MoveKind::Slice) => 2 * quarters,
+        }
+    }
+}
+
+/// Move counts of a sequence in every metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MoveCount {
+    pub htm: usize,
+    pub qtm: usize,
+    pub stm: usize,
+    pub etm: usize,
+}
+
+impl MoveCount {
+    /// 手順をそのまま数える
+    pub fn of(seq: &Sequence) -> Self {
+        let count = |metric| seq.iter().map(|m| m.count(metric)).sum();
+        MoveCount {
+            htm: count(Metric::Htm),
+            qtm: count(Metric::Qtm),
+            stm: count(Metric::Stm),
+            etm: count(Metric::Etm),
+        }
+    }
+
+    /// `simplify` で打ち消し合う手をまとめてから数える
+    pub fn of_simplified(seq: &Sequence) -> Self {
+        MoveCount::of(&simplify(seq))
+    }
+
+    pub fn get(&self, metric: Metric) -> usize {
+        match metric {
+            Metric::Htm => self.htm,
+            Metric::Qtm => self.qtm,
+            Metric::Stm => self.stm,
+            Metric::Etm => self.etm,
+        }
+    }
+}
+
+impl std::fmt::Display for MoveCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = Metric::ALL
+            .iter()
+            .map(|&metric| format!("{} {}", self.get(metric), metric.name()))
+            .collect();
+        write!(f, "{}", parts.join(" / "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_sequence;
+
+    fn count(s: &str) -> MoveCount {
+        MoveCount::of(&parse_sequence(s).unwrap())
+    }
+
+    #[test]
+    fn test_face_turns() {
+        let c = count("R U R' U' R2");
+        assert_eq!((c.htm, c.qtm, c.stm, c.etm), (5, 6, 5, 5));
+    }
+
+    #[test]
+    fn test_slices_wide_and_rotations() {
+        let c = count("M' U2 M");
+        assert_eq!((c.htm, c.qtm, c.stm, c.etm), (5, 6, 3, 3));
+
+        let c = count("r U r' M2");
+        assert_eq!((c.htm, c.qtm, c.stm, c.etm), (5, 7, 4, 4));
+
+        let c = count("x R U y2");
+        assert_eq!((c.htm, c.qtm, c.stm, c.etm), (2, 2, 2, 4));
+    }
+
+    #[test]
+    fn test_simplified_count() {
+        let seq = parse_sequence("R U R' U' U R U' R'").unwrap();
+        assert_eq!(MoveCount::of(&seq).htm, 8);
+        assert_eq!(MoveCount::of_simplified(&seq).htm, 0);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(count("R2 M").to_string(), "3 HTM / 4 QTM / 2 STM / 2 ETM");
+    }
+}
@@ -1,7 +1,19 @@
 pub mod move_parser;
+mod error;
 mod notation_expander;
 mod csv_parser;
+mod simplifier;
+mod metrics;
+mod commutator;
 
-pub use move_parser::{NotationMove, Sequence, parse_sequence, sequence_to_string, reversed_sequence};
+pub use move_parser::{
+    NotationMove, NotationStyle, Sequence, parse_sequence, sequence_to_string,
+    sequence_to_string_with_style, reversed_sequence,
+};
 pub use notation_expander::{Notation, parse_notation, parse_and_expand};
-pub use csv_parser::parse_3style_csv;
+pub use error::{ParseError, ParseLocation};
+pub use csv_parser::{parse_3style_csv, parse_alg_list_csv};
+pub use simplifier::simplify;
+pub(crate) use simplifier::{decompose, kind_move};
+pub use metrics::{Metric, MoveCount};
+pub use commutator::find_commutator;
@@ -1,3 +1,5 @@
+use super::error::ParseError;
+
 /// Moveの定義
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NotationMove {
@@ -46,13 +48,23 @@ pub enum NotationMove {
     E,
     E2,
     EPrime,
+    /// 持ち替え（キューブ全体の回転）
+    X,
+    X2,
+    XPrime,
+    Y,
+    Y2,
+    YPrime,
+    Z,
+    Z2,
+    ZPrime,
     /// NOOP（何もしない操作、空文字列""に相当）
     Noop,
 }
 
 impl NotationMove {
     /// 文字列からNotationMoveをパース
-    pub fn from_str(s: &str) -> Result<Self, String> {
+    pub fn from_str(s: &str) -> Result<Self, ParseError> {
         match s {
             "" => Ok(NotationMove::Noop),
             "U" => Ok(NotationMove::U),
@@ -115,10 +127,42 @@ impl NotationMove {
             "E2" => Ok(NotationMove::E2),
             "E2'" => Ok(NotationMove::E2),
             "E'" => Ok(NotationMove::EPrime),
-            _ => Err(format!("Unknown move1: {}", s)),
+            "x" => Ok(NotationMove::X),
+            "x2" => Ok(NotationMove::X2),
+            "x2'" => Ok(NotationMove::X2),
+            "x'" => Ok(NotationMove::XPrime),
+            "y" => Ok(NotationMove::Y),
+            "y2" => Ok(NotationMove::Y2),
+            "y2'" => Ok(NotationMove::Y2),
+            "y'" => Ok(NotationMove::YPrime),
+            "z" => Ok(NotationMove::Z),
+            "z2" => Ok(NotationMove::Z2),
+            "z2'" => Ok(NotationMove::Z2),
+            "z'" => Ok(NotationMove::ZPrime),
+            _ => parse_extended_move(s).ok_or_else(|| ParseError::new(0, s, &["move"])),
+        }
+    }
+
+    /// 指定した表記で文字列に変換
+    pub fn to_string_with_style(&self, style: NotationStyle) -> String {
+        let s = self.to_string();
+        match style {
+            NotationStyle::Sign => s,
+            // 2 層回しだけ `Rw` の形にする
+            NotationStyle::Wca => match s.chars().next() {
+                Some(c) if "udrlfb".contains(c) => {
+                    format!("{}w{}", c.to_ascii_uppercase(), &s[c.len_utf8()..])
+                }
+                _ => s,
+            },
         }
     }
 
+    /// 操作表などで使う通し番号（宣言順、Noop が最後）
+    pub fn index(&self) -> usize {
+        self.clone() as usize
+    }
+
     /// NotationMoveを文字列に変換
     pub fn to_string(&self) -> String {
         match self {
@@ -168,6 +212,15 @@ impl NotationMove {
             NotationMove::E => "E".to_string(),
             NotationMove::E2 => "E2".to_string(),
             NotationMove::EPrime => "E'".to_string(),
+            NotationMove::X => "x".to_string(),
+            NotationMove::X2 => "x2".to_string(),
+            NotationMove::XPrime => "x'".to_string(),
+            NotationMove::Y => "y".to_string(),
+            NotationMove::Y2 => "y2".to_string(),
+            NotationMove::YPrime => "y'".to_string(),
+            NotationMove::Z => "z".to_string(),
+            NotationMove::Z2 => "z2".to_string(),
+            NotationMove::ZPrime => "z'".to_string(),
         }
     }
 
@@ -220,6 +273,15 @@ impl NotationMove {
             NotationMove::E => NotationMove::EPrime,
             NotationMove::EPrime => NotationMove::E,
             NotationMove::E2 => NotationMove::E2,
+            NotationMove::X => NotationMove::XPrime,
+            NotationMove::XPrime => NotationMove::X,
+            NotationMove::X2 => NotationMove::X2,
+            NotationMove::Y => NotationMove::YPrime,
+            NotationMove::YPrime => NotationMove::Y,
+            NotationMove::Y2 => NotationMove::Y2,
+            NotationMove::Z => NotationMove::ZPrime,
+            NotationMove::ZPrime => NotationMove::Z,
+            NotationMove::Z2 => NotationMove::Z2,
         }
     }
 
@@ -242,6 +304,9 @@ impl NotationMove {
             NotationMove::M | NotationMove::MPrime => NotationMove::M2,
             NotationMove::S | NotationMove::SPrime => NotationMove::S2,
             NotationMove::E | NotationMove::EPrime => NotationMove::E2,
+            NotationMove::X | NotationMove::XPrime => NotationMove::X2,
+            NotationMove::Y | NotationMove::YPrime => NotationMove::Y2,
+            NotationMove::Z | NotationMove::ZPrime => NotationMove::Z2,
             // すでに2回転の場合はそのまま
             m @ (NotationMove::U2
             | NotationMove::UWide2
@@ -257,23 +322,126 @@ impl NotationMove {
             | NotationMove::BWide2
             | NotationMove::M2
             | NotationMove::S2
-            | NotationMove::E2) => m.clone(),
+            | NotationMove::E2
+            | NotationMove::X2
+            | NotationMove::Y2
+            | NotationMove::Z2) => m.clone(),
         }
     }
 }
 
+/// 手の出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotationStyle {
+    /// 2 層回しを小文字で書く（`r`, `u'`）
+    #[default]
+    Sign,
+    /// 2 層回しを WCA の形で書く（`Rw`, `Uw'`）
+    Wca,
+}
+
+/// 面 `face` の側から数えて `layers` 層目までをまとめて回す（`wide`）か、
+/// `layers` 層目だけを回す 90 度の手
+///
+/// 戻り値は手と、面の時計回りに対する向き（1 か -1）
+fn layer_move(face: char, layers: u32, wide: bool) -> Option<(NotationMove, i32)> {
+    use NotationMove::*;
+
+    // 面, 2 層, 3 層（持ち替え）, 2 層目のみ（スライス）, 3 層目のみ（反対の面）
+    let (outer, two, whole, slice, opposite) = match face {
+        'U' => (U, UWide, (Y, 1), (E, -1), D),
+        'D' => (D, DWide, (Y, -1), (E, 1), U),
+        'R' => (R, RWide, (X, 1), (M, -1), L),
+        'L' => (L, LWide, (X, -1), (M, 1), R),
+        'F' => (F, FWide, (Z, 1), (S, 1), B),
+        'B' => (B, BWide, (Z, -1), (S, -1), F),
+        _ => return None,
+    };
+
+    match (layers, wide) {
+        (1, _) => Some((outer, 1)),
+        (2, true) => Some((two, 1)),
+        (3, true) => Some(whole),
+        (2, false) => Some(slice),
+        (3, false) => Some((opposite, -1)),
+        _ => None,
+    }
+}
+
+/// 表にない書き方の手をパースする
+///
+/// `[層数]面[w][回数]['][回数]` の形を受け付ける。
+/// - WCA: `Rw`, `Rw2'`, `3Rw`（3x3 では持ち替え）
+/// - SiGN: `r2'`, `2R`（2 層目のみ = `M'`）, `3r`
+/// - `R'2`, `R3` のような冗長な回数
+///
+/// 回数が 4 の倍数（`R4`, `R0`）は何もしない手になるので、書き間違いとしてエラーにする
+fn parse_extended_move(s: &str) -> Option<NotationMove> {
+    let digits_end = s.find(|c: char| !c.is_ascii_digit())?;
+    let layers: Option<u32> = if digits_end == 0 {
+        None
+    } else {
+        Some(s[..digits_end].parse().ok()?)
+    };
+
+    let mut rest = s[digits_end..].chars();
+    let base = rest.next()?;
+    let rest = rest.as_str();
+    let (wide_suffix, rest) = match rest.strip_prefix('w') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+
+    // ' は回数の前でも後でもよい
+    let (prime, amount) = match (rest.strip_prefix('\''), rest.strip_suffix('\'')) {
+        (Some(amount), _) | (None, Some(amount)) => (true, amount),
+        (None, None) => (false, rest),
+    };
+    let amount: i32 = match amount {
+        "" => 1,
+        digits if digits.chars().all(|c| c.is_ascii_digit()) => {
+            match digits.parse::<u32>().ok()? % 4 {
+                0 => return None,
+                amount => amount as i32,
+            }
+        }
+        _ => return None,
+    };
+
+    let (quarter, sign) = match base {
+        'U' | 'D' | 'R' | 'L' | 'F' | 'B' => {
+            let default_layers = if wide_suffix { 2 } else { 1 };
+            layer_move(base, layers.unwrap_or(default_layers), wide_suffix)?
+        }
+        'u' | 'd' | 'r' | 'l' | 'f' | 'b' if !wide_suffix => {
+            layer_move(base.to_ascii_uppercase(), layers.unwrap_or(2), true)?
+        }
+        'M' | 'S' | 'E' | 'x' | 'y' | 'z' if layers.is_none() && !wide_suffix => {
+            (NotationMove::from_str(&base.to_string()).ok()?, 1)
+        }
+        _ => return None,
+    };
+
+    let direction = if prime { -sign } else { sign };
+    Some(match (amount * direction).rem_euclid(4) {
+        1 => quarter,
+        2 => quarter.doubled(),
+        _ => quarter.reversed(),
+    })
+}
+
 /// Sequence（NotationMoveの列）
 pub type Sequence = Vec<NotationMove>;
 
 /// Sequenceをパース
-pub fn parse_sequence(s: &str) -> Result<Sequence, String> {
-    if s.trim().is_empty() {
-        return Ok(Vec::new());
-    }
-
-    s.trim()
-        .split_whitespace()
-        .map(|token| NotationMove::from_str(token))
+///
+/// エラーの位置は `s` の先頭からのバイト数
+pub fn parse_sequence(s: &str) -> Result<Sequence, ParseError> {
+    s.split_whitespace()
+        .map(|token| {
+            let offset = token.as_ptr() as usize - s.as_ptr() as usize;
+            NotationMove::from_str(token).map_err(|e| e.shifted(offset))
+        })
         .collect()
 }
 
@@ -285,6 +453,14 @@ pub fn sequence_to_string(seq: &Sequence) -> String {
         .join(" ")
 }
 
+/// Sequenceを指定した表記で文字列に変換
+pub fn sequence_to_string_with_style(seq: &Sequence, style: NotationStyle) -> String {
+    seq.iter()
+        .map(|m| m.to_string_with_style(style))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 /// Sequenceの逆操作を返す
 pub fn reversed_sequence(seq: &Sequence) -> Sequence {
     seq.iter().rev().map(|m| m.reversed()).collect()
@@ -302,6 +478,96 @@ mod tests {
         assert!(NotationMove::from_str("X").is_err());
     }
 
+    #[test]
+    fn test_parse_sequence_error_offset() {
+        let err = parse_sequence("R U  Q2 D").unwrap_err();
+        assert_eq!(err.offset, 5);
+        assert_eq!(err.token, "Q2");
+        assert_eq!(err.expected, vec!["move"]);
+
+        // 何もしない回数は書き間違いとして位置を返す
+        let err = parse_sequence("R U4 F").unwrap_err();
+        assert_eq!(err.offset, 2);
+        assert_eq!(err.token, "U4");
+    }
+
+    #[test]
+    fn test_extended_move_from_str() {
+        let parse = |s: &str| NotationMove::from_str(s).unwrap();
+
+        // WCA
+        assert_eq!(parse("Rw"), NotationMove::RWide);
+        assert_eq!(parse("Rw2'"), NotationMove::RWide2);
+        assert_eq!(parse("Uw'"), NotationMove::UWidePrime);
+        assert_eq!(parse("2Fw"), NotationMove::FWide);
+        assert_eq!(parse("3Rw"), NotationMove::X);
+        assert_eq!(parse("3Dw'"), NotationMove::Y);
+
+        // SiGN
+        assert_eq!(parse("2R"), NotationMove::MPrime);
+        assert_eq!(parse("2L'"), NotationMove::MPrime);
+        assert_eq!(parse("2U"), NotationMove::EPrime);
+        assert_eq!(parse("2F2"), NotationMove::S2);
+        assert_eq!(parse("3R"), NotationMove::LPrime);
+        assert_eq!(parse("3r'"), NotationMove::XPrime);
+
+        // 冗長な回数
+        assert_eq!(parse("M2'"), NotationMove::M2);
+        assert_eq!(parse("R'2"), NotationMove::R2);
+        assert_eq!(parse("R3"), NotationMove::RPrime);
+        assert_eq!(parse("x3'"), NotationMove::X);
+
+        for invalid in [
+            "rw", "4Rw", "0R", "2M", "Mw", "R''", "R2'3", "X", "Rx", "U4", "R0", "R8'", "Rw4",
+        ] {
+            assert!(NotationMove::from_str(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_extended_moves_match_cube() {
+        use crate::cube::operations::apply_notation_move;
+        use crate::cube::State;
+
+        let apply = |seq: &str| {
+            parse_sequence(seq)
+                .unwrap()
+                .iter()
+                .fold(State::solved(), |s, m| apply_notation_move(&s, m))
+        };
+
+        // 2R = R 側から 2 層目 = r R'
+        assert_eq!(apply("2R"), apply("r R'"));
+        assert_eq!(apply("2D'"), apply("d' D"));
+        assert_eq!(apply("2B2"), apply("b2 B2"));
+        assert_eq!(apply("3Lw"), apply("x'"));
+        assert_eq!(apply("3F"), apply("B'"));
+    }
+
+    #[test]
+    fn test_sequence_to_string_with_style() {
+        let seq = parse_sequence("Rw U' r2 M x").unwrap();
+        assert_eq!(sequence_to_string(&seq), "r U' r2 M x");
+        assert_eq!(
+            sequence_to_string_with_style(&seq, NotationStyle::Sign),
+            "r U' r2 M x"
+        );
+        assert_eq!(
+            sequence_to_string_with_style(&seq, NotationStyle::Wca),
+            "Rw U' Rw2 M x"
+        );
+    }
+
+    #[test]
+    fn test_rotation_from_str() {
+        assert_eq!(NotationMove::from_str("x").unwrap(), NotationMove::X);
+        assert_eq!(NotationMove::from_str("y2").unwrap(), NotationMove::Y2);
+        assert_eq!(NotationMove::from_str("z'").unwrap(), NotationMove::ZPrime);
+        assert_eq!(NotationMove::ZPrime.to_string(), "z'");
+        assert_eq!(NotationMove::X.reversed(), NotationMove::XPrime);
+        assert_eq!(NotationMove::YPrime.doubled(), NotationMove::Y2);
+    }
+
     #[test]
     fn test_move_reversed() {
         assert_eq!(NotationMove::U.reversed(), NotationMove::UPrime);
@@ -1,14 +1,34 @@
-use super::move_parser::{parse_sequence, reversed_sequence, NotationMove, Sequence};
+use super::error::ParseError;
+use super::move_parser::{reversed_sequence, sequence_to_string, NotationMove, Sequence};
+use std::fmt;
 
 /// 記法の種類
+///
+/// 各記法の中身も記法なので、`[A: [B, C]]` のような入れ子をそのまま木として表せる
 #[derive(Debug, Clone, PartialEq)]
 pub enum Notation {
     /// カンマ記法: seq_a, seq_b
-    Comma { seq_a: Sequence, seq_b: Sequence },
+    Comma {
+        seq_a: Box<Notation>,
+        seq_b: Box<Notation>,
+    },
     /// スラッシュ記法: move/seq
-    Slash { mov: NotationMove, seq: Sequence },
+    Slash {
+        mov: NotationMove,
+        seq: Box<Notation>,
+    },
     /// コロン記法: seq_a: seq_b
-    Colon { seq_a: Sequence, seq_b: Sequence },
+    Colon {
+        seq_a: Box<Notation>,
+        seq_b: Box<Notation>,
+    },
+    /// 繰り返し: (seq)n
+    Repeat {
+        notation: Box<Notation>,
+        count: usize,
+    },
+    /// 記法の連結: R [U, R'] D
+    Concat(Vec<Notation>),
     /// プレーンなシーケンス（記法なし）
     Plain(Sequence),
 }
@@ -19,106 +39,318 @@ impl Notation {
         match self {
             // 規則1: seq_a, seq_b → seq_a seq_b reversed(seq_a) reversed(seq_b)
             Notation::Comma { seq_a, seq_b } => {
+                let seq_a = seq_a.expand();
+                let seq_b = seq_b.expand();
                 let mut result = seq_a.clone();
                 result.extend(seq_b.clone());
-                result.extend(reversed_sequence(seq_a));
-                result.extend(reversed_sequence(seq_b));
+                result.extend(reversed_sequence(&seq_a));
+                result.extend(reversed_sequence(&seq_b));
                 result
             }
 
             // 規則2: m/seq → m seq doubled(m) reversed(seq) m
             Notation::Slash { mov, seq } => {
+                let seq = seq.expand();
                 let mut result = vec![mov.clone()];
                 result.extend(seq.clone());
                 result.push(mov.doubled());
-                result.extend(reversed_sequence(seq));
+                result.extend(reversed_sequence(&seq));
                 result.push(mov.clone());
                 result
             }
 
             // 規則3: seq_a: seq_b → seq_a seq_b reversed(seq_a)
             Notation::Colon { seq_a, seq_b } => {
+                let seq_a = seq_a.expand();
                 let mut result = seq_a.clone();
-                result.extend(seq_b.clone());
-                result.extend(reversed_sequence(seq_a));
+                result.extend(seq_b.expand());
+                result.extend(reversed_sequence(&seq_a));
                 result
             }
 
+            Notation::Repeat { notation, count } => {
+                let seq = notation.expand();
+                (0..*count).flat_map(|_| seq.iter().cloned()).collect()
+            }
+
+            Notation::Concat(parts) => parts.iter().flat_map(Notation::expand).collect(),
+
             // プレーンなシーケンスはそのまま返す
             Notation::Plain(seq) => seq.clone(),
         }
     }
+
+    /// 括弧で囲んで表示される記法か
+    fn is_bracketed(&self) -> bool {
+        matches!(
+            self,
+            Notation::Comma { .. } | Notation::Slash { .. } | Notation::Colon { .. }
+        )
+    }
+}
+
+impl fmt::Display for Notation {
+    /// `parse_notation` で読み戻せる形式で表示する（`[A: [B, C]]`, `(R U R' U')3`）
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Notation::Comma { seq_a, seq_b } => write!(f, "[{}, {}]", seq_a, seq_b),
+            Notation::Slash { mov, seq } => write!(f, "[{}/{}]", mov.to_string(), seq),
+            Notation::Colon { seq_a, seq_b } => write!(f, "[{}: {}]", seq_a, seq_b),
+            Notation::Repeat { notation, count } if notation.is_bracketed() => {
+                write!(f, "{}{}", notation, count)
+            }
+            Notation::Repeat { notation, count } => write!(f, "({}){}", notation, count),
+            Notation::Concat(parts) => {
+                let parts: Vec<String> = parts.iter().map(|p| p.to_string()).collect();
+                write!(f, "{}", parts.join(" "))
+            }
+            Notation::Plain(seq) => write!(f, "{}", sequence_to_string(seq)),
+        }
+    }
 }
 
 /// 文字列から記法をパースする
 ///
-/// パースの優先順位:
-/// 1. スラッシュ記法 (move/seq)
-/// 2. カンマ記法 (seq, seq)
-/// 3. コロン記法 (seq: seq)
-/// 4. プレーンなシーケンス
-pub fn parse_notation(input: &str) -> Result<Notation, String> {
-    let input = input.trim();
-
-    // コロン記法をチェック
-    if let Some(colon_pos) = find_top_level_delimiter(input, ':') {
-        let left = &input[..colon_pos].trim();
-        let right = &input[colon_pos + 1..].trim();
+/// 文法（`:` と `,` は右結合、`/` の左側は 1 手のみ）:
+///
+/// ```text
+/// notation := slash (':' notation)?
+/// slash    := MOVE '/' slash | comma
+/// comma    := seq (',' slash)?
+/// seq      := item*
+/// item     := MOVE | '[' notation ']' COUNT? | '(' notation ')' COUNT?
+/// ```
+///
+/// 括弧の外では従来どおり `R' D': U/R D R'` のように書ける。
+/// `[R D R': [U, R D' R']]` や `(R U R' U')3` のように括弧で入れ子にでき、
+/// 閉じ括弧の直後の数字は繰り返し回数になる。
+pub fn parse_notation(input: &str) -> Result<Notation, ParseError> {
+    let tokens = tokenize(input)?;
+    let mut parser = NotationParser {
+        input,
+        tokens,
+        pos: 0,
+    };
+
+    let notation = parser.notation()?;
+    if parser.peek().is_some() {
+        return Err(parser.error(&["end of input"]));
+    }
+    Ok(notation)
+}
+
+/// 文字列をパースして展開する（再帰的に処理）
+pub fn parse_and_expand(input: &str) -> Result<Sequence, ParseError> {
+    let notation = parse_notation(input)?;
+    Ok(notation.expand())
+}
+
+/// 記法のトークン
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Move(NotationMove),
+    /// 開き括弧（'[' または '('）
+    Open(char),
+    /// 閉じ括弧と、直後に続く繰り返し回数
+    Close(char, Option<usize>),
+    Comma,
+    Colon,
+    Slash,
+}
+
+/// トークンと入力中の位置（バイト単位の範囲）
+struct Spanned {
+    token: Token,
+    start: usize,
+    end: usize,
+}
+
+/// 記号以外の文字が続く部分を 1 手として読む
+fn tokenize(input: &str) -> Result<Vec<Spanned>, ParseError> {
+    let is_symbol = |c: char| "[](),:/".contains(c) || c.is_whitespace();
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '[' | '(' => Token::Open(c),
+            ']' | ')' => {
+                let count_start = end;
+                while let Some(&(i, d)) = chars.peek().filter(|(_, d)| d.is_ascii_digit()) {
+                    end = i + d.len_utf8();
+                    chars.next();
+                }
+                let digits = &input[count_start..end];
+                let count = if digits.is_empty() {
+                    None
+                } else {
+                    Some(
+                        digits
+                            .parse()
+                            .map_err(|_| ParseError::new(count_start, digits, &["repeat count"]))?,
+                    )
+                };
+                Token::Close(c, count)
+            }
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            '/' => Token::Slash,
+            _ => {
+                while let Some(&(i, d)) = chars.peek() {
+                    if is_symbol(d) {
+                        break;
+                    }
+                    end = i + d.len_utf8();
+                    chars.next();
+                }
+                let mov =
+                    NotationMove::from_str(&input[start..end]).map_err(|e| e.shifted(start))?;
+                Token::Move(mov)
+            }
+        };
+        tokens.push(Spanned { token, start, end });
+    }
+
+    Ok(tokens)
+}
+
+/// 再帰下降パーサ
+struct NotationParser<'a> {
+    input: &'a str,
+    tokens: Vec<Spanned>,
+    pos: usize,
+}
+
+impl NotationParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// 現在のトークン（入力の終わりなら空文字列）でのエラー
+    fn error(&self, expected: &[&str]) -> ParseError {
+        match self.tokens.get(self.pos) {
+            Some(t) => ParseError::new(t.start, &self.input[t.start..t.end], expected),
+            None => ParseError::new(self.input.len(), "", expected),
+        }
+    }
 
+    /// notation := slash (':' notation)?
+    fn notation(&mut self) -> Result<Notation, ParseError> {
+        let start = self.pos;
+        let left = self.slash()?;
+
+        if self.peek() != Some(&Token::Colon) {
+            return Ok(left);
+        }
         // 左側が空の場合はエラー
-        if left.is_empty() {
-            return Err("Colon notation requires sequence before ':'".to_string());
+        if self.pos == start {
+            return Err(self.error(&["move", "'['", "'('"]));
         }
+        self.next();
+        let right = self.notation()?;
+
+        Ok(Notation::Colon {
+            seq_a: Box::new(left),
+            seq_b: Box::new(right),
+        })
+    }
 
-        // 左側をパースして展開
-        let seq_a = parse_and_expand(left)?;
-        let seq_b = parse_and_expand(right)?;
+    /// slash := MOVE '/' slash | comma
+    fn slash(&mut self) -> Result<Notation, ParseError> {
+        if let (Some(Token::Move(mov)), Some(Token::Slash)) =
+            (self.peek(), self.tokens.get(self.pos + 1).map(|t| &t.token))
+        {
+            let mov = mov.clone();
+            self.pos += 2;
+            let seq = self.slash()?;
+            return Ok(Notation::Slash {
+                mov,
+                seq: Box::new(seq),
+            });
+        }
 
-        return Ok(Notation::Colon { seq_a, seq_b });
+        self.comma()
     }
 
-    // スラッシュ記法をチェック
-    if let Some(slash_pos) = find_top_level_delimiter(input, '/') {
-        let left = &input[..slash_pos].trim();
-        let right = &input[slash_pos + 1..].trim();
+    /// comma := seq (',' slash)?
+    fn comma(&mut self) -> Result<Notation, ParseError> {
+        let left = self.seq()?;
 
-        // 左側が単一のNotationMoveかチェック
-        if !left.contains(char::is_whitespace) {
-            if let Ok(mov) = NotationMove::from_str(left) {
-                let seq = parse_and_expand(right)?;
-                return Ok(Notation::Slash { mov, seq });
+        match self.peek() {
+            Some(Token::Comma) => {
+                self.next();
+                let right = self.slash()?;
+                Ok(Notation::Comma {
+                    seq_a: Box::new(left),
+                    seq_b: Box::new(right),
+                })
             }
+            // スラッシュの左側は 1 手だけ
+            Some(Token::Slash) => Err(self.error(&["','", "':'", "single move before '/'"])),
+            _ => Ok(left),
         }
     }
 
-    // カンマ記法をチェック
-    if let Some(comma_pos) = find_top_level_delimiter(input, ',') {
-        let left = &input[..comma_pos].trim();
-        let right = &input[comma_pos + 1..].trim();
+    /// seq := item*
+    ///
+    /// 連続する手は 1 つの Plain にまとめ、要素が 1 つならそれ自体を返す
+    fn seq(&mut self) -> Result<Notation, ParseError> {
+        let mut parts: Vec<Notation> = Vec::new();
 
-        let seq_a = parse_and_expand(left)?;
-        let seq_b = parse_and_expand(right)?;
+        loop {
+            match self.peek() {
+                Some(Token::Move(mov)) => {
+                    let mov = mov.clone();
+                    self.next();
+                    match parts.last_mut() {
+                        Some(Notation::Plain(seq)) => seq.push(mov),
+                        _ => parts.push(Notation::Plain(vec![mov])),
+                    }
+                }
+                Some(Token::Open(open)) => {
+                    let open = *open;
+                    self.next();
+                    parts.push(self.group(open)?);
+                }
+                _ => break,
+            }
+        }
 
-        return Ok(Notation::Comma { seq_a, seq_b });
+        Ok(match parts.len() {
+            0 => Notation::Plain(Vec::new()),
+            1 => parts.pop().unwrap(),
+            _ => Notation::Concat(parts),
+        })
     }
 
-    // プレーンなシーケンスとしてパース
-    let seq = parse_sequence(input)?;
-    Ok(Notation::Plain(seq))
-}
+    /// 開き括弧の後から対応する閉じ括弧（と繰り返し回数）まで
+    fn group(&mut self, open: char) -> Result<Notation, ParseError> {
+        let close = if open == '[' { ']' } else { ')' };
+        let inner = self.notation()?;
 
-/// 文字列をパースして展開する（再帰的に処理）
-pub fn parse_and_expand(input: &str) -> Result<Sequence, String> {
-    let notation = parse_notation(input)?;
-    Ok(notation.expand())
-}
-
-/// トップレベルのデリミタを見つける（ネストを考慮しない簡易版）
-///
-/// 注: 本来はコロン、スラッシュ、カンマのネストを正しく扱うべきだが、
-/// 今回の仕様では左から右へのパースで十分と判断
-fn find_top_level_delimiter(input: &str, delimiter: char) -> Option<usize> {
-    input.find(delimiter)
+        match self.peek() {
+            Some(Token::Close(c, count)) if *c == close => {
+                let count = *count;
+                self.next();
+                Ok(match count {
+                    None | Some(1) => inner,
+                    Some(count) => Notation::Repeat {
+                        notation: Box::new(inner),
+                        count,
+                    },
+                })
+            }
+            _ => Err(self.error(&[&format!("'{}'", close)])),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -126,12 +358,16 @@ mod tests {
     use super::super::move_parser::sequence_to_string;
     use super::*;
 
+    fn plain(seq: Sequence) -> Box<Notation> {
+        Box::new(Notation::Plain(seq))
+    }
+
     #[test]
     fn test_comma_notation() {
         // U, R D R' → U R D R' U' R D' R'
         let notation = Notation::Comma {
-            seq_a: vec![NotationMove::U],
-            seq_b: vec![NotationMove::R, NotationMove::D, NotationMove::RPrime],
+            seq_a: plain(vec![NotationMove::U]),
+            seq_b: plain(vec![NotationMove::R, NotationMove::D, NotationMove::RPrime]),
         };
         let expanded = notation.expand();
         assert_eq!(sequence_to_string(&expanded), "U R D R' U' R D' R'");
@@ -142,7 +378,11 @@ mod tests {
         // D/R' U' R → D R' U' R D2 R' U R D
         let notation = Notation::Slash {
             mov: NotationMove::D,
-            seq: vec![NotationMove::RPrime, NotationMove::UPrime, NotationMove::R],
+            seq: plain(vec![
+                NotationMove::RPrime,
+                NotationMove::UPrime,
+                NotationMove::R,
+            ]),
         };
         let expanded = notation.expand();
         assert_eq!(sequence_to_string(&expanded), "D R' U' R D2 R' U R D");
@@ -152,8 +392,8 @@ mod tests {
     fn test_colon_notation() {
         // R' D': (some sequence)
         let notation = Notation::Colon {
-            seq_a: vec![NotationMove::RPrime, NotationMove::DPrime],
-            seq_b: vec![NotationMove::U],
+            seq_a: plain(vec![NotationMove::RPrime, NotationMove::DPrime]),
+            seq_b: plain(vec![NotationMove::U]),
         };
         let expanded = notation.expand();
 
@@ -195,4 +435,77 @@ mod tests {
             "U R U' D R' U' R D' R' U R U R' U'"
         );
     }
+
+    #[test]
+    fn test_bracketed_nested_notation() {
+        // 括弧なしの書き方と同じ結果になる
+        let bracketed = parse_and_expand("[R' D': [U/R D R']]").unwrap();
+        let plain = parse_and_expand("R' D': U/R D R'").unwrap();
+        assert_eq!(bracketed, plain);
+
+        let result = parse_and_expand("[R D R': [U, R D' R']]").unwrap();
+        assert_eq!(
+            sequence_to_string(&result),
+            "R D R' U R D' R' U' R D R' R D' R'"
+        );
+
+        // [A, [B, C]] → A [B, C] A' [B, C]'
+        let result = parse_and_expand("[R, [U, D]]").unwrap();
+        assert_eq!(sequence_to_string(&result), "R U D U' D' R' D U D' U'");
+    }
+
+    #[test]
+    fn test_repeat_and_concat() {
+        let result = parse_and_expand("(R U R' U')3").unwrap();
+        assert_eq!(sequence_to_string(&result), "R U R' U' R U R' U' R U R' U'");
+
+        let result = parse_and_expand("F [R, U]2 F'").unwrap();
+        assert_eq!(sequence_to_string(&result), "F R U R' U' R U R' U' F'");
+
+        // 回数のない括弧はグループ化のみ
+        let result = parse_and_expand("(R U) [R': U]").unwrap();
+        assert_eq!(sequence_to_string(&result), "R U R' U R");
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for input in [
+            "[R D R': [U, R D' R']]",
+            "[U/R D R']",
+            "F [R, U]2 F'",
+            "(R U R' U')3",
+            "R U R'",
+        ] {
+            let notation = parse_notation(input).unwrap();
+            assert_eq!(notation.to_string(), input);
+            assert_eq!(parse_notation(&notation.to_string()).unwrap(), notation);
+        }
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let err = parse_notation("R D R': [U, Q D' R']").unwrap_err();
+        assert_eq!((err.offset, err.token.as_str()), (12, "Q"));
+        assert_eq!(err.expected, vec!["move"]);
+
+        let err = parse_notation("[R, U").unwrap_err();
+        assert_eq!((err.offset, err.token.as_str()), (5, ""));
+        assert_eq!(err.expected, vec!["']'"]);
+
+        let err = parse_notation("(R U]3").unwrap_err();
+        assert_eq!((err.offset, err.token.as_str()), (4, "]3"));
+
+        let err = parse_notation("R U/D").unwrap_err();
+        assert_eq!((err.offset, err.token.as_str()), (3, "/"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_notation("[R, U").is_err());
+        assert!(parse_notation("(R U]").is_err());
+        assert!(parse_notation("R U]").is_err());
+        assert!(parse_notation(": R").is_err());
+        assert!(parse_notation("R U/D").is_err());
+        assert!(parse_notation("[R, Q]").is_err());
+    }
 }
@@ -0,0 +1,294 @@
+use super::move_parser::{NotationMove, Sequence};
+use std::sync::OnceLock;
+
+/// 1 つの軸に属する手の種類の数（面, スライス, 反対の面, 2 層, 反対の 2 層, 持ち替え）
+const KINDS_PER_AXIS: usize = 6;
+
+/// 軸ごとの手の種類
+///
+/// 各種類は 90 度回した時に 3 つの層がそれぞれ何回転するかを、軸の最初の面
+/// （U, R, F）の時計回りを正として表す
+const AXIS_KINDS: [[(NotationMove, [i8; 3]); KINDS_PER_AXIS]; 3] = [
+    [
+        (NotationMove::U, [1, 0, 0]),
+        (NotationMove::E, [0, -1, 0]),
+        (NotationMove::D, [0, 0, -1]),
+        (NotationMove::UWide, [1, 1, 0]),
+        (NotationMove::DWide, [0, -1, -1]),
+        (NotationMove::Y, [1, 1, 1]),
+    ],
+    [
+        (NotationMove::R, [1, 0, 0]),
+        (NotationMove::M, [0, -1, 0]),
+        (NotationMove::L, [0, 0, -1]),
+        (NotationMove::RWide, [1, 1, 0]),
+        (NotationMove::LWide, [0, -1, -1]),
+        (NotationMove::X, [1, 1, 1]),
+    ],
+    [
+        (NotationMove::F, [1, 0, 0]),
+        (NotationMove::S, [0, 1, 0]),
+        (NotationMove::B, [0, 0, -1]),
+        (NotationMove::FWide, [1, 1, 0]),
+        (NotationMove::BWide, [0, -1, -1]),
+        (NotationMove::Z, [1, 1, 1]),
+    ],
+];
+
+/// 面の手（スライス・2 層・持ち替え以外）の種類
+const FACE_KINDS: [usize; 2] = [0, 2];
+/// 持ち替えの種類
+const ROTATION_KIND: usize = 5;
+
+/// 手を (軸, 種類, 90 度回しの回数) に分解する。Noop は None
+pub(crate) fn decompose(mv: &NotationMove) -> Option<(usize, usize, u8)> {
+    AXIS_KINDS.iter().enumerate().find_map(|(axis, kinds)| {
+        kinds.iter().enumerate().find_map(|(kind, (base, _))| {
+            if base == mv {
+                Some((axis, kind, 1))
+            } else if base.doubled() == *mv {
+                Some((axis, kind, 2))
+            } else if base.reversed() == *mv {
+                Some((axis, kind, 3))
+            } else {
+                None
+            }
+        })
+    })
+}
+
+/// 種類の手を `amount` 回（90 度単位）回した NotationMove
+pub(crate) fn kind_move(axis: usize, kind: usize, amount: u8) -> NotationMove {
+    let base = &AXIS_KINDS[axis][kind].0;
+    match amount % 4 {
+        1 => base.clone(),
+        2 => base.doubled(),
+        3 => base.reversed(),
+        _ => NotationMove::Noop,
+    }
+}
+
+/// 出力候補の比較順（手数, 入力にない種類, 面以外の手, 持ち替え）
+type EmitCost = (usize, usize, usize, usize);
+
+/// 3 つの層の回転量（mod 4）を 1 つの添字にする
+fn layers_index(layers: [u8; 3]) -> usize {
+    layers.iter().fold(0, |acc, &l| acc * 4 + l as usize)
+}
+
+/// 軸と層の回転量ごとに、それを実現する手数最小の種類の組み合わせ（各種類の回転量）
+///
+/// 4^6 通りの組み合わせを一度だけ調べて表にしておく。並びは組み合わせの番号順
+fn shortest_combinations(axis: usize, layers: [u8; 3]) -> &'static [[u8; KINDS_PER_AXIS]] {
+    static TABLE: OnceLock<Vec<Vec<Vec<[u8; KINDS_PER_AXIS]>>>> = OnceLock::new();
+
+    let table = TABLE.get_or_init(|| {
+        let count = |a: &[u8; KINDS_PER_AXIS]| a.iter().filter(|&&x| x != 0).count();
+        AXIS_KINDS
+            .iter()
+            .map(|kinds| {
+                let mut table: Vec<Vec<[u8; KINDS_PER_AXIS]>> = vec![Vec::new(); 64];
+                for code in 0..4usize.pow(KINDS_PER_AXIS as u32) {
+                    let amounts: [u8; KINDS_PER_AXIS] =
+                        std::array::from_fn(|k| (code >> (2 * k)) as u8 % 4);
+
+                    let mut layers = [0i8; 3];
+                    for (k, &amount) in amounts.iter().enumerate() {
+                        for (layer, d) in layers.iter_mut().zip(kinds[k].1) {
+                            *layer += d * amount as i8;
+                        }
+                    }
+                    let entry = &mut table[layers_index(layers.map(|l| l.rem_euclid(4) as u8))];
+
+                    match entry.first().map(count) {
+                        Some(best) if best < count(&amounts) => {}
+                        Some(best) if best > count(&amounts) => *entry = vec![amounts],
+                        _ => entry.push(amounts),
+                    }
+                }
+                table
+            })
+            .collect()
+    });
+
+    &table[axis][layers_index(layers)]
+}
+
+/// 同じ軸の手が連続する区間
+struct Run {
+    axis: usize,
+    /// 3 つの層の回転量（mod 4）
+    layers: [u8; 3],
+    /// 入力に現れた種類（出現順）
+    kinds: Vec<usize>,
+}
+
+impl Run {
+    fn add(&mut self, kind: usize, amount: u8) {
+        let direction = AXIS_KINDS[self.axis][kind].1;
+        for (layer, d) in self.layers.iter_mut().zip(direction) {
+            *layer = ((*layer as i8 + d * amount as i8).rem_euclid(4)) as u8;
+        }
+        if !self.kinds.contains(&kind) {
+            self.kinds.push(kind);
+        }
+    }
+
+    fn is_identity(&self) -> bool {
+        self.layers == [0, 0, 0]
+    }
+
+    /// 同じ効果を持つ最短の手順
+    ///
+    /// 手数が同じなら入力に現れた種類を、次に面の手を、最後に持ち替えの少ないものを選ぶ
+    fn emit(&self) -> Sequence {
+        let mut best: Option<([u8; KINDS_PER_AXIS], EmitCost)> = None;
+
+        for &amounts in shortest_combinations(self.axis, self.layers) {
+            let used: Vec<usize> = (0..KINDS_PER_AXIS).filter(|&k| amounts[k] != 0).collect();
+            let cost = (
+                used.len(),
+                used.iter().filter(|k| !self.kinds.contains(k)).count(),
+                used.iter().filter(|k| !FACE_KINDS.contains(k)).count(),
+                used.iter().filter(|&&k| k == ROTATION_KIND).count(),
+            );
+            if best.as_ref().is_none_or(|(_, best_cost)| cost < *best_cost) {
+                best = Some((amounts, cost));
+            }
+        }
+
+        let (amounts, _) = best.expect("every layer combination can be expressed");
+
+        // 入力に現れた順、その後に残りの種類
+        let mut order = self.kinds.clone();
+        order.extend((0..KINDS_PER_AXIS).filter(|k| !self.kinds.contains(k)));
+        order
+            .into_iter()
+            .filter(|&k| amounts[k] != 0)
+            .map(|k| kind_move(self.axis, k, amounts[k]))
+            .collect()
+    }
+}
+
+/// Simplify a sequence without changing its effect on the cube
+///
+/// Consecutive moves on the same axis commute, so each such run (for
+/// example `D U D'` or `R M' L`) is reduced to the turn of each of its
+/// three layers and written back with as few moves as possible. This merges
+/// `U U2` into `U'`, removes `R R'`, and uses slice/wide equivalences such as
+/// `R M'` = `r`. A run that cancels completely lets its neighbours cancel
+/// too, so `R U U' R'` becomes empty. Noop moves are dropped.
+pub fn simplify(seq: &Sequence) -> Sequence {
+    let mut runs: Vec<Run> = Vec::new();
+
+    for mv in seq {
+        let Some((axis, kind, amount)) = decompose(mv) else {
+            continue;
+        };
+
+        match runs.last_mut() {
+            Some(run) if run.axis == axis => run.add(kind, amount),
+            _ => {
+                let mut run = Run {
+                    axis,
+                    layers: [0; 3],
+                    kinds: Vec::new(),
+                };
+                run.add(kind, amount);
+                runs.push(run);
+            }
+        }
+
+        if runs.last().is_some_and(Run::is_identity) {
+            runs.pop();
+        }
+    }
+
+    runs.iter().flat_map(Run::emit).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::operations::apply_notation_move;
+    use crate::cube::State;
+    use crate::parser::{parse_sequence, sequence_to_string};
+
+    fn simplified(s: &str) -> String {
+        sequence_to_string(&simplify(&parse_sequence(s).unwrap()))
+    }
+
+    fn apply(seq: &Sequence) -> State {
+        seq.iter()
+            .fold(State::solved(), |s, m| apply_notation_move(&s, m))
+    }
+
+    #[test]
+    fn test_cancel_and_merge() {
+        assert_eq!(simplified("R R'"), "");
+        assert_eq!(simplified("U U2"), "U'");
+        assert_eq!(simplified("R2 R2 F"), "F");
+        assert_eq!(simplified("R U U' R'"), "");
+        assert_eq!(simplified("R U R' U' U2"), "R U R' U");
+    }
+
+    #[test]
+    fn test_commuting_opposite_faces() {
+        assert_eq!(simplified("D U D'"), "U");
+        assert_eq!(simplified("U D U'"), "D");
+        assert_eq!(simplified("R L R"), "R2 L");
+        // 打ち消さない限り並びは入力のまま
+        assert_eq!(simplified("R L'"), "R L'");
+        assert_eq!(simplified("L' R"), "L' R");
+    }
+
+    #[test]
+    fn test_slice_and_wide_equivalences() {
+        assert_eq!(simplified("R M'"), "r");
+        assert_eq!(simplified("r R'"), "M'");
+        assert_eq!(simplified("L M"), "l");
+        assert_eq!(simplified("U E'"), "u");
+        assert_eq!(simplified("F S"), "f");
+        assert_eq!(simplified("r' R"), "M");
+        assert_eq!(simplified("x R'"), "l'");
+    }
+
+    #[test]
+    fn test_noop_is_dropped() {
+        let seq = vec![NotationMove::R, NotationMove::Noop, NotationMove::RPrime];
+        assert!(simplify(&seq).is_empty());
+    }
+
+    #[test]
+    fn test_kind_directions_match_cube() {
+        // 2 層回しと持ち替えが、面とスライスの組み合わせと一致することを確認
+        for (axis, kinds) in AXIS_KINDS.iter().enumerate() {
+            for (kind, direction) in kinds.iter().skip(3) {
+                let layers: Sequence = (0..3)
+                    .filter(|&layer| direction[layer] != 0)
+                    .map(|layer| {
+                        let sign = kinds[layer].1[layer];
+                        kind_move(axis, layer, (direction[layer] * sign).rem_euclid(4) as u8)
+                    })
+                    .collect();
+                assert_eq!(apply(&vec![kind.clone()]), apply(&layers), "{:?}", kind);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simplify_preserves_state() {
+        for s in [
+            "R U R' U' D U D' R2 R L' M x y' r R' S f B b' E d u",
+            "M' U M U2 M' U M",
+            "R U2 R' U' R U' R' L' U2 L U L' U L",
+            "x' R2 D2 R' U' R D2 R' U R' x",
+        ] {
+            let seq = parse_sequence(s).unwrap();
+            let result = simplify(&seq);
+            assert_eq!(apply(&result), apply(&seq), "{}", s);
+            assert!(result.len() <= seq.len());
+            assert_eq!(simplify(&result), result, "simplify is idempotent");
+        }
+    }
+}
@@ -1,10 +1,10 @@
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
-use crate::cube::{Move, RubiksCube, SolutionSearcher, State};
-use crate::parser::{parse_sequence, sequence_to_string, NotationMove};
+use crate::cube::{RubiksCube, SolutionSearcher, State, StateComponent, StateValidation};
+use crate::parser::{parse_sequence, sequence_to_string, MoveCount, NotationMove};
 use crate::workflow::BldWorkflow;
-use crate::inspection::{CornerOperation, EdgeOperation};
+use crate::inspection::{CornerOperation, EdgeOperation, LetterScheme, MoveSequence};
 
 #[wasm_bindgen]
 pub fn greet(name: &str) -> String {
@@ -31,6 +31,7 @@ pub struct StateData {
     pub co: [u8; 8],
     pub ep: [u8; 12],
     pub eo: [u8; 12],
+    pub centers: [u8; 6],
 }
 
 #[derive(Serialize, Deserialize)]
@@ -52,14 +53,35 @@ pub struct BldSolutionData {
     pub corner_operations: Vec<String>,
     pub edge_operations: Vec<String>,
     pub all_operations: Vec<String>,
+    /// 記憶する文字の組（例: "edges: AB CD E; corners: FG H"）
+    pub letter_pairs: String,
     pub move_sequences: Vec<MoveSequenceData>,
     pub formatted_solution: String,
+    /// 全ての手順をそのまま結合した手数
+    pub move_count: MoveCount,
+    /// 手順の境界で打ち消し合う手をまとめた後の手数
+    pub simplified_move_count: MoveCount,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct MoveSequenceData {
     pub description: String,
     pub sequence: String,
+    /// 手順の手数
+    pub move_count: MoveCount,
+    /// 手順内で打ち消し合う手をまとめた後の手数
+    pub simplified_move_count: MoveCount,
+}
+
+impl From<&MoveSequence> for MoveSequenceData {
+    fn from(seq: &MoveSequence) -> Self {
+        MoveSequenceData {
+            description: seq.description.clone(),
+            sequence: seq.to_string(),
+            move_count: seq.move_count(),
+            simplified_move_count: seq.simplified_move_count(),
+        }
+    }
 }
 
 // 構造化データを返す新しいバージョン
@@ -75,6 +97,141 @@ pub struct BldSolutionDataV2 {
     pub corner_operations: Vec<CornerOperation>,
     pub edge_operations: Vec<EdgeOperation>,
     pub move_sequences: Vec<MoveSequenceData>,
+    /// 全ての手順をそのまま結合した手数
+    pub move_count: MoveCount,
+    /// 手順の境界で打ち消し合う手をまとめた後の手数
+    pub simplified_move_count: MoveCount,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StateValidationResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub valid: bool,
+    pub issues: Vec<StateIssueData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StateIssueData {
+    pub component: StateComponent,
+    pub positions: Vec<usize>,
+    pub message: String,
+}
+
+impl From<&StateValidation> for StateValidationResult {
+    fn from(validation: &StateValidation) -> Self {
+        StateValidationResult {
+            success: true,
+            error: None,
+            valid: validation.is_valid(),
+            issues: validation
+                .issues
+                .iter()
+                .map(|issue| StateIssueData {
+                    component: issue.component(),
+                    positions: issue.positions(),
+                    message: issue.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// State が合法かどうかを検証し、問題点を返す
+/// フロントエンドで不正な入力欄をハイライトするために使う
+#[wasm_bindgen]
+pub fn validate_state(cp: Vec<u8>, co: Vec<u8>, ep: Vec<u8>, eo: Vec<u8>) -> JsValue {
+    if cp.len() != 8 || co.len() != 8 || ep.len() != 12 || eo.len() != 12 {
+        let error_result = StateValidationResult {
+            success: false,
+            error: Some(
+                "Invalid state array lengths. Expected: cp(8), co(8), ep(12), eo(12)".to_string(),
+            ),
+            valid: false,
+            issues: Vec::new(),
+        };
+        return serde_wasm_bindgen::to_value(&error_result).unwrap();
+    }
+
+    let state = State::new(
+        cp.try_into().unwrap(),
+        co.try_into().unwrap(),
+        ep.try_into().unwrap(),
+        eo.try_into().unwrap(),
+    );
+    let result = StateValidationResult::from(&state.validate());
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FaceletResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub facelets: Option<String>,
+}
+
+/// 54 文字の facelet 文字列（URFDLB 順）から State を作る
+#[wasm_bindgen]
+pub fn state_from_facelets(facelets: &str) -> JsValue {
+    let result = match State::from_facelet_string(facelets) {
+        Ok(state) => ScrambleResult {
+            success: true,
+            error: None,
+            state: Some(StateData {
+                cp: state.cp,
+                co: state.co,
+                ep: state.ep,
+                eo: state.eo,
+                centers: state.centers,
+            }),
+        },
+        Err(err) => ScrambleResult {
+            success: false,
+            error: Some(err.to_string()),
+            state: None,
+        },
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// State を 54 文字の facelet 文字列（URFDLB 順）に変換する
+#[wasm_bindgen]
+pub fn state_to_facelets(cp: Vec<u8>, co: Vec<u8>, ep: Vec<u8>, eo: Vec<u8>) -> JsValue {
+    if cp.len() != 8 || co.len() != 8 || ep.len() != 12 || eo.len() != 12 {
+        let error_result = FaceletResult {
+            success: false,
+            error: Some(
+                "Invalid state array lengths. Expected: cp(8), co(8), ep(12), eo(12)".to_string(),
+            ),
+            facelets: None,
+        };
+        return serde_wasm_bindgen::to_value(&error_result).unwrap();
+    }
+
+    let state = State::new(
+        cp.try_into().unwrap(),
+        co.try_into().unwrap(),
+        ep.try_into().unwrap(),
+        eo.try_into().unwrap(),
+    );
+
+    // 範囲外の値があると facelet に変換できない
+    if let Err(err) = state.validate().into_result() {
+        let error_result = FaceletResult {
+            success: false,
+            error: Some(err),
+            facelets: None,
+        };
+        return serde_wasm_bindgen::to_value(&error_result).unwrap();
+    }
+
+    let result = FaceletResult {
+        success: true,
+        error: None,
+        facelets: Some(state.to_facelet_string()),
+    };
+    serde_wasm_bindgen::to_value(&result).unwrap()
 }
 
 #[wasm_bindgen]
@@ -87,7 +244,7 @@ pub fn parse_scramble(input: &str) -> JsValue {
         },
         Err(err) => ParsedScramble {
             success: false,
-            error: Some(err),
+            error: Some(err.to_string()),
             moves: None,
         },
     };
@@ -98,26 +255,9 @@ pub fn parse_scramble(input: &str) -> JsValue {
 #[wasm_bindgen]
 pub fn apply_scramble_to_state(scramble: &str) -> JsValue {
     let cube = RubiksCube::new();
-    let mut state = State::solved();
-
-    match parse_sequence(scramble) {
-        Ok(sequence) => {
-            // Apply each move to the state
-            for notation_move in sequence {
-                let move_str = notation_move.to_string();
-                match cube.apply_move(&state, &move_str) {
-                    Some(new_state) => state = new_state,
-                    None => {
-                        let error_result = ScrambleResult {
-                            success: false,
-                            error: Some(format!("Failed to apply move: {}", move_str)),
-                            state: None,
-                        };
-                        return serde_wasm_bindgen::to_value(&error_result).unwrap();
-                    }
-                }
-            }
 
+    match cube.scramble_to_state(scramble) {
+        Ok(state) => {
             let success_result = ScrambleResult {
                 success: true,
                 error: None,
@@ -126,6 +266,7 @@ pub fn apply_scramble_to_state(scramble: &str) -> JsValue {
                     co: state.co,
                     ep: state.ep,
                     eo: state.eo,
+                    centers: state.centers,
                 }),
             };
             serde_wasm_bindgen::to_value(&success_result).unwrap()
@@ -133,7 +274,7 @@ pub fn apply_scramble_to_state(scramble: &str) -> JsValue {
         Err(err) => {
             let error_result = ScrambleResult {
                 success: false,
-                error: Some(err),
+                error: Some(err.to_string()),
                 state: None,
             };
             serde_wasm_bindgen::to_value(&error_result).unwrap()
@@ -212,12 +353,17 @@ pub fn solve_bld_with_default_moveset_v2(
     };
 
     // Create state from arrays
-    let state = State {
-        cp: cp_array,
-        co: co_array,
-        ep: ep_array,
-        eo: eo_array,
-    };
+    let state = State::new(cp_array, co_array, ep_array, eo_array);
+
+    // Reject states that cannot occur on a real cube
+    if let Err(err) = state.validate().into_result() {
+        let error_result = BldSolutionResultV2 {
+            success: false,
+            error: Some(err),
+            solution: None,
+        };
+        return serde_wasm_bindgen::to_value(&error_result).unwrap();
+    }
 
     // Create BldWorkflow with embedded movesets
     let workflow = match BldWorkflow::new(
@@ -245,10 +391,7 @@ pub fn solve_bld_with_default_moveset_v2(
                 .move_sequences
                 .sequences()
                 .iter()
-                .map(|seq| MoveSequenceData {
-                    description: seq.description.clone(),
-                    sequence: seq.to_string(),
-                })
+                .map(MoveSequenceData::from)
                 .collect();
 
             let success_result = BldSolutionResultV2 {
@@ -258,6 +401,8 @@ pub fn solve_bld_with_default_moveset_v2(
                     corner_operations: solution.corner_operations.clone(),
                     edge_operations: solution.edge_operations.clone(),
                     move_sequences: move_seqs,
+                    move_count: solution.move_sequences.move_count(),
+                    simplified_move_count: solution.move_sequences.simplified_move_count(),
                 }),
             };
             serde_wasm_bindgen::to_value(&success_result).unwrap()
@@ -273,6 +418,9 @@ pub fn solve_bld_with_default_moveset_v2(
     }
 }
 
+/// 手順表の JSON を渡して解く
+///
+/// `letter_scheme_json` を省略すると文字の組は Speffz で表示する
 #[wasm_bindgen]
 pub fn solve_bld(
     cp: Vec<u8>,
@@ -284,6 +432,7 @@ pub fn solve_bld(
     ufr_twist_json: &str,
     uf_expanded_json: &str,
     uf_flip_json: &str,
+    letter_scheme_json: Option<String>,
 ) -> JsValue {
     // Validate array lengths
     if cp.len() != 8 || co.len() != 8 || ep.len() != 12 || eo.len() != 12 {
@@ -347,12 +496,17 @@ pub fn solve_bld(
     };
 
     // Create state from arrays
-    let state = State {
-        cp: cp_array,
-        co: co_array,
-        ep: ep_array,
-        eo: eo_array,
-    };
+    let state = State::new(cp_array, co_array, ep_array, eo_array);
+
+    // Reject states that cannot occur on a real cube
+    if let Err(err) = state.validate().into_result() {
+        let error_result = BldSolutionResult {
+            success: false,
+            error: Some(err),
+            solution: None,
+        };
+        return serde_wasm_bindgen::to_value(&error_result).unwrap();
+    }
 
     // Create BldWorkflow
     let workflow = match BldWorkflow::new(
@@ -373,6 +527,19 @@ pub fn solve_bld(
         }
     };
 
+    let scheme = match letter_scheme_json.as_deref().map(LetterScheme::from_json) {
+        None => LetterScheme::speffz(),
+        Some(Ok(scheme)) => scheme,
+        Some(Err(err)) => {
+            let error_result = BldSolutionResult {
+                success: false,
+                error: Some(format!("Failed to load letter scheme: {}", err)),
+                solution: None,
+            };
+            return serde_wasm_bindgen::to_value(&error_result).unwrap();
+        }
+    };
+
     // Solve using BldWorkflow
     match workflow.solve(&state) {
         Ok(solution) => {
@@ -399,13 +566,10 @@ pub fn solve_bld(
                 .move_sequences
                 .sequences()
                 .iter()
-                .map(|seq| MoveSequenceData {
-                    description: seq.description.clone(),
-                    sequence: seq.to_string(),
-                })
+                .map(MoveSequenceData::from)
                 .collect();
 
-            let formatted = BldWorkflow::format_solution(&solution);
+            let formatted = BldWorkflow::format_solution_with_scheme(&solution, &scheme);
 
             let success_result = BldSolutionResult {
                 success: true,
@@ -414,8 +578,11 @@ pub fn solve_bld(
                     corner_operations: corner_ops,
                     edge_operations: edge_ops,
                     all_operations: all_ops,
+                    letter_pairs: solution.all_operations.letter_pairs(&scheme),
                     move_sequences: move_seqs,
                     formatted_solution: formatted,
+                    move_count: solution.move_sequences.move_count(),
+                    simplified_move_count: solution.move_sequences.simplified_move_count(),
                 }),
             };
             serde_wasm_bindgen::to_value(&success_result).unwrap()
@@ -437,6 +604,40 @@ pub fn solve_bld_with_default_moveset(
     co: Vec<u8>,
     ep: Vec<u8>,
     eo: Vec<u8>,
+) -> JsValue {
+    solve_default_moveset_with_scheme(cp, co, ep, eo, &LetterScheme::speffz())
+}
+
+/// `solve_bld_with_default_moveset` の文字の組を `letter_scheme_json` の文字で表示する
+///
+/// `letter_scheme_json` はステッカーと文字の JSON（例: `{"UBL": "A", "UB": "A", ...}`）
+#[wasm_bindgen]
+pub fn solve_bld_with_letter_scheme(
+    cp: Vec<u8>,
+    co: Vec<u8>,
+    ep: Vec<u8>,
+    eo: Vec<u8>,
+    letter_scheme_json: &str,
+) -> JsValue {
+    match LetterScheme::from_json(letter_scheme_json) {
+        Ok(scheme) => solve_default_moveset_with_scheme(cp, co, ep, eo, &scheme),
+        Err(err) => {
+            let error_result = BldSolutionResult {
+                success: false,
+                error: Some(format!("Failed to load letter scheme: {}", err)),
+                solution: None,
+            };
+            serde_wasm_bindgen::to_value(&error_result).unwrap()
+        }
+    }
+}
+
+fn solve_default_moveset_with_scheme(
+    cp: Vec<u8>,
+    co: Vec<u8>,
+    ep: Vec<u8>,
+    eo: Vec<u8>,
+    scheme: &LetterScheme,
 ) -> JsValue {
     // Validate array lengths
     if cp.len() != 8 || co.len() != 8 || ep.len() != 12 || eo.len() != 12 {
@@ -500,12 +701,17 @@ pub fn solve_bld_with_default_moveset(
     };
 
     // Create state from arrays
-    let state = State {
-        cp: cp_array,
-        co: co_array,
-        ep: ep_array,
-        eo: eo_array,
-    };
+    let state = State::new(cp_array, co_array, ep_array, eo_array);
+
+    // Reject states that cannot occur on a real cube
+    if let Err(err) = state.validate().into_result() {
+        let error_result = BldSolutionResult {
+            success: false,
+            error: Some(err),
+            solution: None,
+        };
+        return serde_wasm_bindgen::to_value(&error_result).unwrap();
+    }
 
     // Create BldWorkflow with embedded movesets
     let workflow = match BldWorkflow::new(
@@ -552,13 +758,10 @@ pub fn solve_bld_with_default_moveset(
                 .move_sequences
                 .sequences()
                 .iter()
-                .map(|seq| MoveSequenceData {
-                    description: seq.description.clone(),
-                    sequence: seq.to_string(),
-                })
+                .map(MoveSequenceData::from)
                 .collect();
 
-            let formatted = BldWorkflow::format_solution(&solution);
+            let formatted = BldWorkflow::format_solution_with_scheme(&solution, scheme);
 
             let success_result = BldSolutionResult {
                 success: true,
@@ -567,8 +770,11 @@ pub fn solve_bld_with_default_moveset(
                     corner_operations: corner_ops,
                     edge_operations: edge_ops,
                     all_operations: all_ops,
+                    letter_pairs: solution.all_operations.letter_pairs(scheme),
                     move_sequences: move_seqs,
                     formatted_solution: formatted,
+                    move_count: solution.move_sequences.move_count(),
+                    simplified_move_count: solution.move_sequences.simplified_move_count(),
                 }),
             };
             serde_wasm_bindgen::to_value(&success_result).unwrap()
@@ -1,6 +1,10 @@
-use crate::cube::State;
-use crate::inspection::{CornerInspection, CornerOperation, EdgeInspection, EdgeOperation};
-use crate::inspection::{MoveSequenceCollection, OperationsToTurns};
+use super::parity_strategy::{ParityStrategy, SwapInspectionParity};
+use crate::cube::{PackedState, State};
+use crate::inspection::{BldMethod, BldMethods, CornerOperation, EdgeOperation, LetterScheme};
+use crate::inspection::{MoveSequenceCollection, OperationsToTurns, SolverConfig, TableError};
+use crate::parser::sequence_to_string;
+use std::path::Path;
+use std::rc::Rc;
 
 /// BLD (Blindfolded) solving workflow
 ///
@@ -8,6 +12,10 @@ use crate::inspection::{MoveSequenceCollection, OperationsToTurns};
 #[derive(Clone)]
 pub struct BldWorkflow {
     operations_converter: OperationsToTurns,
+    parity_strategy: Rc<dyn ParityStrategy>,
+    /// None なら手順表による 3-style
+    corner_method: Option<Rc<dyn BldMethod<CornerOperation>>>,
+    edge_method: Option<Rc<dyn BldMethod<EdgeOperation>>>,
 }
 
 /// Corner と Edge の操作を含む完全な解法
@@ -21,6 +29,8 @@ pub struct BldSolution {
     pub all_operations: AllOperations,
     /// Move Sequence Collection (Edge → Corner の順)
     pub move_sequences: MoveSequenceCollection,
+    /// 各 MoveSequence の境界で打ち消し合った手数（i 番目は i 番目と i + 1 番目の境界）
+    pub boundary_cancellations: Vec<usize>,
 }
 
 impl BldSolution {
@@ -50,6 +60,8 @@ impl std::fmt::Display for Operation {
 #[derive(Debug, Clone)]
 pub struct AllOperations {
     operations: Vec<Operation>,
+    /// バッファを知るための設定（文字の組でバッファのねじれ・反転を省く）
+    config: SolverConfig,
 }
 
 impl AllOperations {
@@ -57,9 +69,16 @@ impl AllOperations {
     pub fn new() -> Self {
         Self {
             operations: Vec::new(),
+            config: SolverConfig::default(),
         }
     }
 
+    /// バッファを `config` のものにする（既定は UFR / UF）
+    pub fn with_config(mut self, config: SolverConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     /// Edge 操作を追加
     pub fn push_edge(&mut self, op: EdgeOperation) {
         self.operations.push(Operation::Edge(op));
@@ -84,6 +103,54 @@ impl AllOperations {
     pub fn is_empty(&self) -> bool {
         self.operations.is_empty()
     }
+
+    /// 記憶する文字の組（例: "edges: AB CD E; flip: F; corners: FG H; twist: I"）
+    ///
+    /// 交換は2つずつ組にし、反転・ねじれは1文字ずつ並べる。操作のない種類は省く。
+    /// バッファのねじれ・反転は他のピースと一緒に揃うので記憶しない
+    pub fn letter_pairs(&self, scheme: &LetterScheme) -> String {
+        let mut edges = Vec::new();
+        let mut flips = Vec::new();
+        let mut corners = Vec::new();
+        let mut twists = Vec::new();
+        for op in &self.operations {
+            match op {
+                Operation::Edge(op @ EdgeOperation::Swap(_)) => edges.push(scheme.edge_target(op)),
+                Operation::Edge(op @ EdgeOperation::Flip(flip)) => {
+                    if flip.target != self.config.edge_buffer {
+                        flips.push(scheme.edge_target(op))
+                    }
+                }
+                Operation::Corner(op @ CornerOperation::Swap(_)) => {
+                    corners.push(scheme.corner_target(op))
+                }
+                Operation::Corner(op @ CornerOperation::Twist(twist)) => {
+                    if twist.target != self.config.corner_buffer {
+                        twists.push(scheme.corner_target(op))
+                    }
+                }
+            }
+        }
+
+        let pairs = |letters: &[&str]| {
+            letters
+                .chunks(2)
+                .map(|pair| pair.concat())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        [
+            ("edges", pairs(&edges)),
+            ("flip", flips.join(" ")),
+            ("corners", pairs(&corners)),
+            ("twist", twists.join(" ")),
+        ]
+        .into_iter()
+        .filter(|(_, letters)| !letters.is_empty())
+        .map(|(kind, letters)| format!("{}: {}", kind, letters))
+        .collect::<Vec<_>>()
+        .join("; ")
+    }
 }
 
 impl std::fmt::Display for AllOperations {
@@ -119,7 +186,7 @@ impl BldWorkflow {
         ufr_twist_json: &str,
         uf_expanded_json: &str,
         uf_flip_json: &str,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, TableError> {
         let operations_converter = OperationsToTurns::new(
             ufr_expanded_json,
             ufr_parity_json,
@@ -130,9 +197,141 @@ impl BldWorkflow {
 
         Ok(Self {
             operations_converter,
+            parity_strategy: Rc::new(SwapInspectionParity),
+            corner_method: None,
+            edge_method: None,
         })
     }
 
+    /// 3-style の CSV シートから BldWorkflow を初期化
+    ///
+    /// 記法は読み込み時に展開されるので、シートを編集したらそのまま読み直せばよい
+    ///
+    /// # Arguments
+    /// * `ufr_csv` - Corner 2-swap 用の 3-style シート（例: `resources/ufr.csv`）
+    /// * `ufr_parity_csv` - Corner parity 用の「ステッカー,手順」シート
+    /// * `ufr_twist_csv` - Corner twist 用の「ステッカー,手順」シート
+    /// * `uf_csv` - Edge 2-swap 用の 3-style シート（例: `resources/uf.csv`）
+    /// * `uf_flip_csv` - Edge flip 用の「ステッカー,手順」シート
+    pub fn from_csv_sheets<P: AsRef<Path>>(
+        ufr_csv: P,
+        ufr_parity_csv: P,
+        ufr_twist_csv: P,
+        uf_csv: P,
+        uf_flip_csv: P,
+    ) -> Result<Self, TableError> {
+        let operations_converter = OperationsToTurns::from_csv_sheets(
+            ufr_csv,
+            ufr_parity_csv,
+            ufr_twist_csv,
+            uf_csv,
+            uf_flip_csv,
+        )?;
+
+        Ok(Self {
+            operations_converter,
+            parity_strategy: Rc::new(SwapInspectionParity),
+            corner_method: None,
+            edge_method: None,
+        })
+    }
+
+    /// バッファを設定（デフォルトは UFR / UF）
+    ///
+    /// 手順表はここで設定したバッファ用のものを読み込むこと
+    pub fn with_config(mut self, config: SolverConfig) -> Self {
+        self.operations_converter = self.operations_converter.with_config(config);
+        self
+    }
+
+    /// 使用するバッファ
+    pub fn config(&self) -> &SolverConfig {
+        self.operations_converter.config()
+    }
+
+    /// パリティのある状態の解き方を設定（デフォルトは `SwapInspectionParity`）
+    pub fn with_parity_strategy(mut self, parity_strategy: impl ParityStrategy + 'static) -> Self {
+        self.parity_strategy = Rc::new(parity_strategy);
+        self
+    }
+
+    /// コーナーを手順にする解き方を設定（デフォルトは手順表による 3-style）
+    ///
+    /// `with_config` のコーナーのバッファとパリティのエッジは解き方に合わせること
+    pub fn with_corner_method(
+        mut self,
+        corner_method: impl BldMethod<CornerOperation> + 'static,
+    ) -> Self {
+        self.corner_method = Some(Rc::new(corner_method));
+        self
+    }
+
+    /// エッジを手順にする解き方を設定（デフォルトは手順表による 3-style）
+    ///
+    /// `with_config` のエッジのバッファは解き方に合わせること
+    pub fn with_edge_method(
+        mut self,
+        edge_method: impl BldMethod<EdgeOperation> + 'static,
+    ) -> Self {
+        self.edge_method = Some(Rc::new(edge_method));
+        self
+    }
+
+    /// `EdgeParity::Table` で使うエッジのパリティ手順表を追加
+    pub fn with_edge_parity_table(mut self, uf_parity_json: &str) -> Result<Self, TableError> {
+        self.operations_converter = self
+            .operations_converter
+            .with_edge_parity_table(uf_parity_json)?;
+        Ok(self)
+    }
+
+    /// `config.pair_orientations` で使う、2つのコーナーのねじれを揃える手順表を追加
+    pub fn with_twist_pair_table(mut self, ufr_twist_pairs_json: &str) -> Result<Self, TableError> {
+        self.operations_converter = self
+            .operations_converter
+            .with_twist_pair_table(ufr_twist_pairs_json)?;
+        Ok(self)
+    }
+
+    /// `config.pair_orientations` で使う、2つのエッジの反転を揃える手順表を追加
+    pub fn with_flip_pair_table(mut self, uf_flip_pairs_json: &str) -> Result<Self, TableError> {
+        self.operations_converter = self
+            .operations_converter
+            .with_flip_pair_table(uf_flip_pairs_json)?;
+        Ok(self)
+    }
+
+    /// フローティングバッファ `buffer` からのコーナーの手順表を追加
+    ///
+    /// `config.floating_corner_buffers` の各バッファに必要
+    pub fn with_corner_buffer_sheet(
+        mut self,
+        buffer: usize,
+        expanded_json: &str,
+        parity_json: &str,
+    ) -> Result<Self, TableError> {
+        self.operations_converter = self.operations_converter.with_corner_buffer_sheet(
+            buffer,
+            expanded_json,
+            parity_json,
+        )?;
+        Ok(self)
+    }
+
+    /// フローティングバッファ `buffer` からのエッジの手順表を追加
+    ///
+    /// `config.floating_edge_buffers` の各バッファに必要
+    pub fn with_edge_buffer_sheet(
+        mut self,
+        buffer: usize,
+        expanded_json: &str,
+    ) -> Result<Self, TableError> {
+        self.operations_converter = self
+            .operations_converter
+            .with_edge_buffer_sheet(buffer, expanded_json)?;
+        Ok(self)
+    }
+
     /// 完全な BLD 解法を生成
     ///
     /// # Arguments
@@ -142,29 +341,36 @@ impl BldWorkflow {
     /// Corner と Edge の操作列、および Move Sequence Collection を含む BldSolution
     ///
     /// # ワークフロー
+    /// 0. State が合法かを検証（不正な場合は Err）し、センターがずれていれば持ち替える
+    ///    （解法は持ち替えた後のキューブに対するもの）
     /// 1. Corner workflow を実行し、Corner 用の操作列を取得
     /// 2. Corner の Swap 操作数が奇数の場合、Edge workflow で交換分析モードを使用
+    ///    （`EdgeParity::SwapParityEdges` の場合のみ）
     /// 3. Edge workflow を実行し、Edge 用の操作列を取得
+    ///
+    ///    1-3 と 5 は `with_parity_strategy` で設定した `ParityStrategy` が決める
+    ///    （上はデフォルトの `SwapInspectionParity` の場合）
     /// 4. Edge → Corner の順で操作列を結合
-    /// 5. 操作列から Move Sequence Collection を生成
+    /// 5. 操作列から Move Sequence Collection を生成（`with_corner_method` / `with_edge_method`）
+    /// 6. 手順の境界で打ち消し合う手数を数える
+    /// 7. 手順が State を揃えるかを確認（揃わない場合は Err）
     pub fn solve(&self, state: &State) -> Result<BldSolution, String> {
-        // 1. Corner workflow を実行
-        let corner_operations =
-            CornerInspection::solve_corner_permutation_with_orientation(state);
-
-        // 2. 交換分析の有無を判断
-        let corner_swap_count = corner_operations
-            .iter()
-            .filter(|op| matches!(op, CornerOperation::Swap(_)))
-            .count();
-        let use_swap_inspection = corner_swap_count % 2 == 1;
+        // 0. 不正な State では Inspection が停止しないことがあるため先に検証
+        state.validate().into_result()?;
+        // Inspection はセンターを見ないので、r や x で終わるスクランブルは持ち替えてから解く
+        let state = &state.reoriented().ok_or_else(|| {
+            format!(
+                "The centers {:?} are not a whole-cube rotation, so the cube cannot be reoriented",
+                state.centers
+            )
+        })?;
 
-        // 3. Edge workflow を実行
-        let edge_operations =
-            EdgeInspection::solve_edge_permutation_with_orientation(state, use_swap_inspection);
+        // 1-3. パリティの解き方に従って Corner と Edge の操作列を計算
+        let converter = &self.operations_converter;
+        let (corner_operations, edge_operations) = self.parity_strategy.inspect(state, converter);
 
         // 4. 操作列を結合 (Edge → Corner)
-        let mut all_operations = AllOperations::new();
+        let mut all_operations = AllOperations::new().with_config(converter.config().clone());
         
         // Edge の操作を全て追加
         for op in &edge_operations {
@@ -177,26 +383,51 @@ impl BldWorkflow {
         }
 
         // 5. Move Sequence Collection を生成 (Edge → Corner)
-        let mut move_sequences = MoveSequenceCollection::new();
-        
-        // Edge operations を move sequences に変換して追加
-        let edge_sequences = self.operations_converter.convert_edge_operations(&edge_operations)?;
-        move_sequences.extend(edge_sequences);
-        
-        // Corner operations を move sequences に変換して追加
-        let corner_sequences = self.operations_converter.convert(&corner_operations)?;
-        move_sequences.extend(corner_sequences);
+        let methods = BldMethods {
+            corners: self.corner_method.as_deref().unwrap_or(converter),
+            edges: self.edge_method.as_deref().unwrap_or(converter),
+        };
+        let move_sequences = self.parity_strategy.convert(
+            state,
+            &corner_operations,
+            &edge_operations,
+            converter.config(),
+            &methods,
+        )?;
+
+        // 6. 手順の境界での打ち消しを数える
+        let boundary_cancellations = move_sequences.boundary_cancellations();
+
+        // 7. 手順表の抜けや間違いで揃わない解法を返さない
+        let mut result = PackedState::from(state);
+        result.apply_sequence(&move_sequences.flatten_moves());
+        if !result.to_state().is_solved() {
+            return Err(format!(
+                "The move sequences do not solve the state (check the alg tables):\n{}",
+                move_sequences
+            ));
+        }
 
         Ok(BldSolution {
             corner_operations,
             edge_operations,
             all_operations,
             move_sequences,
+            boundary_cancellations,
         })
     }
 
     /// 解法を人間が読みやすい形式でフォーマット
     pub fn format_solution(solution: &BldSolution) -> String {
+        Self::format_solution_impl(solution, None)
+    }
+
+    /// `format_solution` に `scheme` の文字の組（Letter Pairs）を加えてフォーマット
+    pub fn format_solution_with_scheme(solution: &BldSolution, scheme: &LetterScheme) -> String {
+        Self::format_solution_impl(solution, Some(scheme))
+    }
+
+    fn format_solution_impl(solution: &BldSolution, scheme: Option<&LetterScheme>) -> String {
         let mut result = String::new();
 
         result.push_str("=== BLD Solution ===\n\n");
@@ -232,6 +463,20 @@ impl BldWorkflow {
         }
         result.push('\n');
 
+        // 記憶する文字の組
+        if let Some(scheme) = scheme {
+            result.push_str("Letter Pairs:\n");
+            if solution.all_operations.is_empty() {
+                result.push_str("  (none)\n");
+            } else {
+                result.push_str(&format!(
+                    "  {}\n",
+                    solution.all_operations.letter_pairs(scheme)
+                ));
+            }
+            result.push('\n');
+        }
+
         // Move Sequences (各MoveSequenceごとに改行して表示)
         result.push_str("Move Sequences:\n");
         if solution.move_sequences.is_empty() {
@@ -246,50 +491,61 @@ impl BldWorkflow {
             }
         }
 
+        // 手数（そのまま / 打ち消し後）
+        if !solution.move_sequences.is_empty() {
+            result.push('\n');
+            result.push_str("Move Count:\n");
+            result.push_str(&format!("  Raw: {}\n", solution.move_sequences.move_count()));
+            result.push_str(&format!(
+                "  After cancellation: {}\n",
+                solution.move_sequences.simplified_move_count()
+            ));
+        }
+
+        // 境界での打ち消しをまとめた実際に回す手順
+        let cancelled: usize = solution.boundary_cancellations.iter().sum();
+        if cancelled > 0 {
+            result.push('\n');
+            result.push_str(&format!("Cancellations: {} moves\n", cancelled));
+            for (i, &count) in solution.boundary_cancellations.iter().enumerate() {
+                if count > 0 {
+                    result.push_str(&format!(
+                        "  Between sequence {} and {}: {}\n",
+                        i + 1,
+                        i + 2,
+                        count
+                    ));
+                }
+            }
+            result.push_str(&format!(
+                "Simplified Moves:\n  {}\n",
+                sequence_to_string(&solution.move_sequences.simplified_moves())
+            ));
+        }
+
         result
     }
 }
 
+// テスト用のBldWorkflowを作成するヘルパー関数
+//
+// 解法が状態を揃えるかを確かめるので、同梱の手順表を使う
+#[cfg(test)]
+pub(crate) fn create_test_workflow() -> BldWorkflow {
+    BldWorkflow::new(
+        include_str!("../../resources/ufr_expanded.json"),
+        include_str!("../../resources/ufr_parity.json"),
+        include_str!("../../resources/ufr_twist.json"),
+        include_str!("../../resources/uf_expanded.json"),
+        include_str!("../../resources/uf_flip.json"),
+    )
+    .expect("Failed to create test workflow")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    
-    // テスト用のBldWorkflowを作成するヘルパー関数
-    fn create_test_workflow() -> BldWorkflow {
-        let ufr_expanded = r#"{
-            "RDB": { "RDF": "R U R' U R U2 R'" },
-            "RDF": { "UFL": "U R U' R' U R U2 R'" },
-            "UBR": { "UBL": "L' U' L U' L' U2 L" },
-            "BUR": { "BUL": "U L' U L U' L' U2 L" }
-        }"#;
-        
-        let ufr_parity = r#"{
-            "RDB": "R U R' U R U2 R'",
-            "UBR": "R U R' U R U2 R'",
-            "RUB": "U R U' R' U R U2 R'",
-            "BUR": "R U2 R' U' R U' R'",
-            "UBL": "L' U' L U' L' U2 L",
-            "BUL": "U' L' U L U' L' U2 L",
-            "LUB": "L' U2 L U L' U L"
-        }"#;
-        
-        let ufr_twist = r#"{
-            "FUL": "R' D R D' R' D R U' R' D' R D R' D' R U"
-        }"#;
-        
-        let uf_expanded = r#"{
-            "FR": { "DL": "R U R' F R F'" },
-            "RU": { "BL": "U R U' R' U R U R'" }
-        }"#;
-        
-        let uf_flip = r#"{
-            "UB": "R U R' U R U2 R'",
-            "UR": "R U R' U R U2 R' U"
-        }"#;
-        
-        BldWorkflow::new(&ufr_expanded, &ufr_parity, &ufr_twist, &uf_expanded, &uf_flip)
-            .expect("Failed to create test workflow")
-    }
+    use crate::inspection::EdgeParity;
 
     #[test]
     fn test_bld_workflow_simple() {
@@ -308,17 +564,19 @@ mod tests {
         
         println!("{}", BldWorkflow::format_solution(&solution));
         
-        // Corner は 1 Swap 操作 → 奇数なので Edge は交換分析モード
-        assert_eq!(solution.corner_operations.len(), 1);
-        
-        // Edge も 1 Swap 操作
-        assert_eq!(solution.edge_operations.len(), 1);
-        
-        // 全操作列は Edge + Corner = 2
-        assert_eq!(solution.all_operations.len(), 2);
-        
-        // MoveSequences が空でないことを確認
-        assert!(!solution.move_sequences.is_empty());
+        // UBL と UBR の交換はバッファ経由の 3 Swap 操作 → 奇数なので Edge は交換分析モード
+        assert_eq!(solution.corner_operations.len(), 3);
+
+        // 全操作列は Edge + Corner
+        assert_eq!(
+            solution.all_operations.len(),
+            solution.corner_operations.len() + solution.edge_operations.len()
+        );
+
+        // 手順で State が揃う
+        let mut result = PackedState::from(&state);
+        result.apply_sequence(&solution.move_sequences.flatten_moves());
+        assert!(result.to_state().is_solved());
     }
 
     #[test]
@@ -340,7 +598,7 @@ mod tests {
 
     #[test]
     fn test_bld_workflow_corner_only() {
-        // Corner のみが崩れている状態
+        // Corner のみが 2 点交換された状態は実在しない（偶奇が一致しない）
         let state = State::new(
             [1, 0, 2, 3, 4, 5, 6, 7],
             [0, 0, 0, 0, 0, 0, 0, 0],
@@ -348,25 +606,15 @@ mod tests {
             [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         );
 
-        println!("\n=== BLD Workflow Corner Only Test ===");
-        
         let workflow = create_test_workflow();
-        let solution = workflow.solve(&state).expect("Failed to solve");
-        
-        println!("{}", BldWorkflow::format_solution(&solution));
-        
-        // Corner は 1 Swap 操作
-        assert_eq!(solution.corner_operations.len(), 1);
-        
-        // Edge は交換分析モード（奇数）で、ep は全て正しい位置
-        // しかし交換分析モードでは ep[5]=5, ep[6]=6 ではなく ep[5]=6, ep[6]=5 が終了状態
-        // 初期状態では ep[5]=5, ep[6]=6 なので、調整が必要
-        assert!(!solution.edge_operations.is_empty());
+        let err = workflow.solve(&state).unwrap_err();
+
+        assert!(err.contains("parity"), "{}", err);
     }
 
     #[test]
     fn test_bld_workflow_edge_only() {
-        // Edge のみが崩れている状態
+        // Edge のみが 2 点交換された状態も実在しない
         let state = State::new(
             [0, 1, 2, 3, 4, 5, 6, 7],
             [0, 0, 0, 0, 0, 0, 0, 0],
@@ -374,18 +622,24 @@ mod tests {
             [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         );
 
-        println!("\n=== BLD Workflow Edge Only Test ===");
-        
         let workflow = create_test_workflow();
-        let solution = workflow.solve(&state).expect("Failed to solve");
-        
-        println!("{}", BldWorkflow::format_solution(&solution));
-        
-        // Corner は操作なし
-        assert!(solution.corner_operations.is_empty());
-        
-        // Edge は 1 Swap 操作（通常モード、偶数 = 0）
-        assert_eq!(solution.edge_operations.len(), 1);
+        let err = workflow.solve(&state).unwrap_err();
+
+        assert!(err.contains("parity"), "{}", err);
+    }
+
+    #[test]
+    fn test_bld_workflow_rejects_twisted_corner() {
+        // 1 箇所だけねじれたコーナー
+        let state = State::new(
+            [0, 1, 2, 3, 4, 5, 6, 7],
+            [0, 0, 1, 0, 0, 0, 0, 0],
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+
+        let workflow = create_test_workflow();
+        assert!(workflow.solve(&state).is_err());
     }
 
     #[test]
@@ -490,6 +744,156 @@ mod tests {
         println!("{}", BldWorkflow::format_solution(&solution));
     }
 
+    #[test]
+    fn test_bld_workflow_edge_parity_table() {
+        // UF ↔ X と UF ↔ UR の組の手順は、UF ↔ X にパリティのエッジの交換を加えたもの
+        let uf_expanded: serde_json::Value =
+            serde_json::from_str(include_str!("../../resources/uf_expanded.json")).unwrap();
+        let uf_parity: serde_json::Map<String, serde_json::Value> = uf_expanded
+            .as_object()
+            .unwrap()
+            .iter()
+            .filter_map(|(target, inner)| Some((target.clone(), inner.get("UR")?.clone())))
+            .collect();
+
+        let config = SolverConfig::default().with_edge_parity(EdgeParity::Table);
+        let workflow = create_test_workflow()
+            .with_config(config)
+            .with_edge_parity_table(&serde_json::Value::Object(uf_parity).to_string())
+            .unwrap();
+
+        // UR 自身のステッカーだけは組にできない
+        let report = workflow.operations_converter.verify();
+        assert!(report.wrong.is_empty(), "{}", report);
+        assert_eq!(report.missing.len(), 1, "{}", report);
+        assert_eq!(report.missing[0].key, "RU");
+
+        // コーナーもエッジも 2 点交換（パリティ）
+        let state = State::new(
+            [1, 0, 2, 3, 4, 5, 6, 7],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [1, 0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+        let solution = workflow.solve(&state).expect("Failed to solve");
+
+        // エッジは交換分析せず、残った交換をパリティ手順表で解く
+        let edge_swaps = solution
+            .edge_operations
+            .iter()
+            .filter(|op| matches!(op, EdgeOperation::Swap(_)))
+            .count();
+        assert_eq!(edge_swaps % 2, 1);
+        assert!(solution
+            .move_sequences
+            .sequences()
+            .iter()
+            .any(|seq| seq.description.starts_with("Parity: ") && !seq.description.contains('→')));
+
+        // パリティのエッジの反転は、パリティ手順表の手順で入れ替わった後の位置で揃える
+        for eo in [
+            [0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0],
+            [0, 0, 0, 0, 1, 0, 1, 0, 0, 0, 0, 0],
+        ] {
+            let state = State::new(
+                [1, 0, 2, 3, 4, 5, 6, 7],
+                [0, 0, 0, 0, 0, 0, 0, 0],
+                [1, 0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+                eo,
+            );
+            workflow.solve(&state).expect("Failed to solve");
+        }
+        for scramble in SCRAMBLES {
+            workflow.solve(&scrambled(scramble)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_bld_workflow_orientation_pairs() {
+        // 1つずつの手順を2つ続けた組の手順（バッファのねじれ・反転は打ち消し合う）
+        let twist: serde_json::Value =
+            serde_json::from_str(include_str!("../../resources/ufr_twist.json")).unwrap();
+        let flip: serde_json::Value =
+            serde_json::from_str(include_str!("../../resources/uf_flip.json")).unwrap();
+        let join = |singles: &serde_json::Value, first: &str, second: &str| {
+            format!(
+                "{} {}",
+                singles[first].as_str().unwrap(),
+                singles[second].as_str().unwrap()
+            )
+        };
+        let twist_pairs = serde_json::json!({ "LUB": { "LUF": join(&twist, "LUB", "LUF") } });
+        let flip_pairs = serde_json::json!({ "UR": { "UB": join(&flip, "UR", "UB") } });
+
+        let config = SolverConfig::default().with_pair_orientations(true);
+        let workflow = create_test_workflow()
+            .with_config(config)
+            .with_twist_pair_table(&twist_pairs.to_string())
+            .unwrap()
+            .with_flip_pair_table(&flip_pairs.to_string())
+            .unwrap();
+
+        // UBL が反時計回り、UFL が時計回り、UB と UR が反転
+        let state = State::new(
+            [0, 1, 2, 3, 4, 5, 6, 7],
+            [1, 0, 0, 2, 0, 0, 0, 0],
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0],
+        );
+        let solution = workflow.solve(&state).expect("Failed to solve");
+
+        let descriptions: Vec<&str> = solution
+            .move_sequences
+            .sequences()
+            .iter()
+            .map(|seq| seq.description.as_str())
+            .collect();
+        assert_eq!(descriptions, vec!["Flip: UB + UR", "Twist: LUB + LUF"]);
+    }
+
+    #[test]
+    fn test_bld_workflow_rejects_unsolved_result() {
+        let mut ufr_parity: serde_json::Value =
+            serde_json::from_str(include_str!("../../resources/ufr_parity.json")).unwrap();
+        ufr_parity["UBR"] = serde_json::Value::String("R U R' U'".to_string());
+        let workflow = BldWorkflow::new(
+            include_str!("../../resources/ufr_expanded.json"),
+            &ufr_parity.to_string(),
+            include_str!("../../resources/ufr_twist.json"),
+            include_str!("../../resources/uf_expanded.json"),
+            include_str!("../../resources/uf_flip.json"),
+        )
+        .unwrap();
+
+        let state = State::new(
+            [1, 0, 2, 3, 4, 5, 6, 7],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [1, 0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+        let err = workflow.solve(&state).unwrap_err();
+        assert!(err.contains("do not solve"), "{}", err);
+    }
+
+    #[test]
+    fn test_bld_workflow_reorients_centers() {
+        let workflow = create_test_workflow();
+        let expected = workflow.solve(&scrambled(SCRAMBLES[0])).unwrap();
+
+        // 持ち替えで終わるスクランブルも、持ち替えた後のキューブとして解く
+        let rotated = workflow
+            .solve(&scrambled(&format!("{} x y'", SCRAMBLES[0])))
+            .unwrap();
+        assert_eq!(rotated.move_sequences, expected.move_sequences);
+
+        let wide = scrambled(&format!("{} r u'", SCRAMBLES[0]));
+        let solution = workflow.solve(&wide).unwrap();
+        let mut result = PackedState::from(&wide.reoriented().unwrap());
+        result.apply_sequence(&solution.move_sequences.flatten_moves());
+        assert!(result.to_state().is_solved());
+    }
+
     #[test]
     fn test_move_sequences_display() {
         // MoveSequences の表示形式を確認
@@ -514,4 +918,135 @@ mod tests {
         // フォーマット済みの出力を表示
         println!("\n{}", BldWorkflow::format_solution(&solution));
     }
+
+    #[test]
+    fn test_boundary_cancellations() {
+        use crate::cube::operations::apply_notation_move;
+
+        let state = State::new(
+            [1, 0, 2, 3, 4, 5, 6, 7],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [1, 0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+
+        let workflow = create_test_workflow();
+        let solution = workflow.solve(&state).expect("Failed to solve");
+
+        let sequences = &solution.move_sequences;
+        assert_eq!(solution.boundary_cancellations.len(), sequences.len() - 1);
+
+        // 簡約後の手順も同じ状態に到達し、消えた手数だけ短い
+        let apply = |moves: &[crate::parser::NotationMove]| {
+            moves
+                .iter()
+                .fold(state.clone(), |s, m| apply_notation_move(&s, m))
+        };
+        let simplified = sequences.simplified_moves();
+        assert_eq!(apply(&simplified), apply(&sequences.flatten_moves()));
+        assert!(
+            simplified.len() + solution.boundary_cancellations.iter().sum::<usize>()
+                <= sequences.flatten_moves().len()
+        );
+    }
+
+    /// スクランブル後の状態
+    fn scrambled(scramble: &str) -> State {
+        let mut packed = PackedState::solved();
+        packed.apply_sequence(&crate::parser::parse_sequence(scramble).unwrap());
+        packed.to_state()
+    }
+
+    // 偶置換と奇置換のスクランブル
+    const SCRAMBLES: [&str; 2] = [
+        "R U2 F' L D2 B R' U F2 D' L2 B' U R2 F D2",
+        "D2 F' R U' L2 B D R2 F U' B2 L' D F2 R' U",
+    ];
+
+    #[test]
+    fn test_bld_workflow_old_pochmann() {
+        use crate::inspection::OldPochmann;
+
+        let workflow = create_test_workflow()
+            .with_config(OldPochmann::solver_config())
+            .with_corner_method(OldPochmann::new().unwrap())
+            .with_edge_method(OldPochmann::new().unwrap());
+        for scramble in SCRAMBLES {
+            // 揃わない手順は solve が Err にする
+            let solution = workflow.solve(&scrambled(scramble)).unwrap();
+            assert!(!solution.move_sequences.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_bld_workflow_m2_r2() {
+        use crate::inspection::{M2Edges, R2Corners};
+
+        let workflow = create_test_workflow()
+            .with_config(SolverConfig::new(1, 10, [5, 7]).unwrap())
+            .with_corner_method(R2Corners::new().unwrap())
+            .with_edge_method(M2Edges::new().unwrap());
+        for scramble in SCRAMBLES {
+            workflow.solve(&scrambled(scramble)).unwrap();
+        }
+
+        // コーナーは 3-style、エッジは M2
+        let workflow = create_test_workflow()
+            .with_config(SolverConfig::new(2, 10, [6, 5]).unwrap())
+            .with_edge_method(M2Edges::new().unwrap());
+        for scramble in SCRAMBLES {
+            workflow.solve(&scrambled(scramble)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_letter_pairs() {
+        use crate::inspection::{
+            CornerSwapOperation, CornerTwistOperation, EdgeFlipOperation, EdgeSwapOperation,
+        };
+
+        let mut operations = AllOperations::new();
+        operations.push_edge(EdgeOperation::Swap(EdgeSwapOperation::new(6, 4, 0)));
+        operations.push_edge(EdgeOperation::Swap(EdgeSwapOperation::new(6, 9, 1)));
+        operations.push_edge(EdgeOperation::Swap(EdgeSwapOperation::new(6, 7, 0)));
+        operations.push_edge(EdgeOperation::Flip(EdgeFlipOperation::new(5)));
+        operations.push_corner(CornerOperation::Swap(CornerSwapOperation::new(2, 5, 2)));
+        operations.push_corner(CornerOperation::Swap(CornerSwapOperation::new(2, 0, 1)));
+        operations.push_corner(CornerOperation::Swap(CornerSwapOperation::new(2, 3, 2)));
+        operations.push_corner(CornerOperation::Twist(CornerTwistOperation::new(6, 2)));
+        assert_eq!(
+            operations.letter_pairs(&LetterScheme::speffz()),
+            "edges: AO D; flip: B; corners: OR I; twist: P"
+        );
+
+        let kana =
+            LetterScheme::from_json(include_str!("../../resources/kana_scheme.json")).unwrap();
+        assert_eq!(
+            operations.letter_pairs(&kana),
+            "edges: あそ え; flip: い; corners: そつ け; twist: た"
+        );
+
+        // バッファ（UFR / UF）のねじれ・反転は文字にしない
+        let mut operations = AllOperations::new();
+        operations.push_edge(EdgeOperation::Flip(EdgeFlipOperation::new(5)));
+        operations.push_edge(EdgeOperation::Flip(EdgeFlipOperation::new(6)));
+        operations.push_corner(CornerOperation::Twist(CornerTwistOperation::new(3, 1)));
+        operations.push_corner(CornerOperation::Twist(CornerTwistOperation::new(2, 2)));
+        assert_eq!(
+            operations.letter_pairs(&LetterScheme::speffz()),
+            "flip: B; twist: I"
+        );
+        let operations = operations.with_config(SolverConfig::new(3, 5, [4, 7]).unwrap());
+        assert_eq!(
+            operations.letter_pairs(&LetterScheme::speffz()),
+            "flip: C; twist: J"
+        );
+
+        let workflow = create_test_workflow();
+        let solution = workflow.solve(&scrambled(SCRAMBLES[0])).unwrap();
+        let formatted =
+            BldWorkflow::format_solution_with_scheme(&solution, &LetterScheme::speffz());
+        assert!(formatted.contains("Letter Pairs:\n  edges: "));
+        assert!(!BldWorkflow::format_solution(&solution).contains("Letter Pairs:"));
+    }
 }
@@ -2,6 +2,8 @@ use crate::cube::State;
 use crate::explorer::ModifiedMoveSequenceCollection;
 use crate::workflow::{BldWorkflow, BldSolution, MixedNearbySearchWorkflow, NearbySequenceSearchWorkflow};
 use crate::explorer::ModifiedMixedSequence;
+use crate::inspection::LetterScheme;
+use crate::parser::{MoveCount, Sequence};
 
 /// Combined Nearby Search の結果
 #[derive(Debug)]
@@ -60,6 +62,23 @@ impl CombinedSearchResult {
 
     /// 結果を詳細に表示
     pub fn display_detailed(&self, max_variants_per_type: usize) -> String {
+        self.display_detailed_impl(max_variants_per_type, None)
+    }
+
+    /// `display_detailed` に元の解法の `scheme` の文字の組を加えて表示
+    pub fn display_detailed_with_scheme(
+        &self,
+        max_variants_per_type: usize,
+        scheme: &LetterScheme,
+    ) -> String {
+        self.display_detailed_impl(max_variants_per_type, Some(scheme))
+    }
+
+    fn display_detailed_impl(
+        &self,
+        max_variants_per_type: usize,
+        scheme: Option<&LetterScheme>,
+    ) -> String {
         let mut result = String::new();
 
         if !self.solution_found {
@@ -105,6 +124,17 @@ impl CombinedSearchResult {
             }
             result.push_str("\n");
 
+            // Letter pairs
+            if let Some(scheme) = scheme {
+                if !solution.all_operations.is_empty() {
+                    result.push_str("Letter Pairs:\n");
+                    result.push_str(&format!(
+                        "  {}\n\n",
+                        solution.all_operations.letter_pairs(scheme)
+                    ));
+                }
+            }
+
             // Move Sequences
             result.push_str("Move Sequences:\n");
             if solution.move_sequences.is_empty() {
@@ -116,6 +146,11 @@ impl CombinedSearchResult {
                     }
                     result.push_str(&format!("    {}\n", seq));
                 }
+                result.push_str(&format!(
+                    "Move Count: {} (after cancellation: {})\n",
+                    solution.move_sequences.move_count(),
+                    solution.move_sequences.simplified_move_count()
+                ));
             }
             result.push_str("\n");
         }
@@ -167,7 +202,14 @@ impl CombinedSearchResult {
             {
                 result.push_str(&format!("\nMove Variant {}:\n", i + 1));
                 result.push_str(&format!("{}\n", modified_collection));
-                
+
+                let moves: Sequence = modified_collection.get_collection().concat();
+                result.push_str(&format!(
+                    "  Move Count: {} (after cancellation: {})\n",
+                    MoveCount::of(&moves),
+                    MoveCount::of_simplified(&moves)
+                ));
+
                 let final_state = modified_collection.apply_to_state(&self.initial_state);
                 result.push_str("  Final state verification:\n");
                 result.push_str(&format!("    cp: {:?}\n", final_state.cp));
@@ -239,7 +281,9 @@ impl CombinedNearbySearchWorkflow {
     ) -> Result<CombinedSearchResult, String> {
         // スクランブル文字列から初期状態を生成
         let cube = crate::cube::operations::RubiksCube::new();
-        let initial_state = cube.scramble_to_state(scramble);
+        let initial_state = cube
+            .scramble_to_state(scramble)
+            .map_err(|e| format!("Invalid scramble: {}", e))?;
 
         self.search_internal(&initial_state, target_state, Some(scramble.to_string()))
     }
@@ -304,34 +348,12 @@ mod tests {
     use super::*;
 
     fn create_test_workflow() -> CombinedNearbySearchWorkflow {
-        let ufr_expanded = r#"{
-            "RDB": { "RDF": "R U R' U R U2 R'" },
-            "RDF": { "UFL": "U R U' R' U R U2 R'" }
-        }"#;
-
-        let ufr_parity = r#"{
-            "RDB": "R U R' U R U2 R'",
-            "UBR": "R U R' U R U2 R'"
-        }"#;
-
-        let ufr_twist = r#"{
-            "FUL": "R' D R D' R' D R U' R' D' R D R' D' R U"
-        }"#;
-
-        let uf_expanded = r#"{
-            "FR": { "DL": "R U R' F R F'" }
-        }"#;
-
-        let uf_flip = r#"{
-            "UB": "R U R' U R U2 R'"
-        }"#;
-
         CombinedNearbySearchWorkflow::from_json(
-            &ufr_expanded,
-            &ufr_parity,
-            &ufr_twist,
-            &uf_expanded,
-            &uf_flip,
+            include_str!("../../resources/ufr_expanded.json"),
+            include_str!("../../resources/ufr_parity.json"),
+            include_str!("../../resources/ufr_twist.json"),
+            include_str!("../../resources/uf_expanded.json"),
+            include_str!("../../resources/uf_flip.json"),
         )
         .expect("Failed to create test workflow")
     }
@@ -438,4 +460,34 @@ mod tests {
         let summary = result.summary();
         assert!(summary.contains("Total alternatives found: 0"));
     }
+
+    #[test]
+    fn test_display_detailed_with_scheme() {
+        let workflow = crate::workflow::bld_workflow::create_test_workflow();
+        // UFR → DFR → DFL の3点交換
+        let state = State::new(
+            [0, 1, 7, 3, 4, 5, 2, 6],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+        let solution = workflow.solve(&state).unwrap();
+        let letters = solution
+            .all_operations
+            .letter_pairs(&LetterScheme::speffz());
+        let result = CombinedSearchResult {
+            solution_found: true,
+            original_solution: Some(solution),
+            operation_variants: Vec::new(),
+            move_variants: Vec::new(),
+            initial_state: state,
+            target_state: State::solved(),
+            scramble: None,
+        };
+
+        let detailed = result.display_detailed_with_scheme(3, &LetterScheme::speffz());
+        assert!(letters.starts_with("corners: "));
+        assert!(detailed.contains(&format!("Letter Pairs:\n  {}\n", letters)));
+        assert!(!result.display_detailed(3).contains("Letter Pairs:"));
+    }
 }
@@ -70,11 +70,12 @@ impl MixedNearbySearchWorkflow {
         let solution = self.get_correct_solution(initial_state)?;
         let mixed_operations = self.solution_to_mixed_operations(&solution);
 
-        // 2. 近傍探索を実行
+        // 2. 近傍探索を実行（交換の代替案は同じバッファのもの）
+        let config = self.bld_workflow.config();
         let generators: Vec<Box<dyn AlternativeGenerator<MixedOperation>>> = vec![
-            Box::new(CornerSwapAlternativeGenerator),
+            Box::new(CornerSwapAlternativeGenerator::new(config.clone())),
             Box::new(CornerTwistAlternativeGenerator),
-            Box::new(EdgeSwapAlternativeGenerator),
+            Box::new(EdgeSwapAlternativeGenerator::new(config.clone())),
             Box::new(EdgeFlipAlternativeGenerator),
         ];
         let search =
@@ -99,11 +100,12 @@ impl MixedNearbySearchWorkflow {
         let solution = self.get_correct_solution(initial_state)?;
         let mixed_operations = self.solution_to_mixed_operations(&solution);
 
-        // 2. 近傍探索を実行
+        // 2. 近傍探索を実行（交換の代替案は同じバッファのもの）
+        let config = self.bld_workflow.config();
         let generators: Vec<Box<dyn AlternativeGenerator<MixedOperation>>> = vec![
-            Box::new(CornerSwapAlternativeGenerator),
+            Box::new(CornerSwapAlternativeGenerator::new(config.clone())),
             Box::new(CornerTwistAlternativeGenerator),
-            Box::new(EdgeSwapAlternativeGenerator),
+            Box::new(EdgeSwapAlternativeGenerator::new(config.clone())),
             Box::new(EdgeFlipAlternativeGenerator),
         ];
         let search =
@@ -124,43 +126,12 @@ mod tests {
     use super::*;
 
     fn create_test_workflow() -> MixedNearbySearchWorkflow {
-        let ufr_expanded = r#"{
-            "RDB": { "RDF": "R U R' U R U2 R'" },
-            "RDF": { "UFL": "U R U' R' U R U2 R'" },
-            "UBR": { "UBL": "L' U' L U' L' U2 L" },
-            "BUR": { "BUL": "U L' U L U' L' U2 L" }
-        }"#;
-
-        let ufr_parity = r#"{
-            "RDB": "R U R' U R U2 R'",
-            "UBR": "R U R' U R U2 R'",
-            "RUB": "U R U' R' U R U2 R'",
-            "BUR": "R U2 R' U' R U' R'",
-            "UBL": "L' U' L U' L' U2 L",
-            "BUL": "U' L' U L U' L' U2 L",
-            "LUB": "L' U2 L U L' U L"
-        }"#;
-
-        let ufr_twist = r#"{
-            "FUL": "R' D R D' R' D R U' R' D' R D R' D' R U"
-        }"#;
-
-        let uf_expanded = r#"{
-            "FR": { "DL": "R U R' F R F'" },
-            "RU": { "BL": "U R U' R' U R U R'" }
-        }"#;
-
-        let uf_flip = r#"{
-            "UB": "R U R' U R U2 R'",
-            "UR": "R U R' U R U2 R' U"
-        }"#;
-
         let bld_workflow = BldWorkflow::new(
-            &ufr_expanded,
-            &ufr_parity,
-            &ufr_twist,
-            &uf_expanded,
-            &uf_flip,
+            include_str!("../../resources/ufr_expanded.json"),
+            include_str!("../../resources/ufr_parity.json"),
+            include_str!("../../resources/ufr_twist.json"),
+            include_str!("../../resources/uf_expanded.json"),
+            include_str!("../../resources/uf_flip.json"),
         )
         .expect("Failed to create test workflow");
 
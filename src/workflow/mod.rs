@@ -2,8 +2,13 @@ pub mod bld_workflow;
 pub mod mixed_nearby_search_workflow;
 pub mod nearby_sequence_search_workflow;
 pub mod combined_nearby_search_workflow;
+pub mod parity_strategy;
 
 pub use bld_workflow::{BldWorkflow, BldSolution};
 pub use mixed_nearby_search_workflow::MixedNearbySearchWorkflow;
 pub use nearby_sequence_search_workflow::{AlternativeResult, NearbySequenceSearchWorkflow};
 pub use combined_nearby_search_workflow::{CombinedNearbySearchWorkflow, CombinedSearchResult};
+pub use parity_strategy::{
+    EdgeMemoFirst, ParityAtEnd, ParityInspection, ParityStrategy, PseudoSwapParity,
+    SwapInspectionParity,
+};
@@ -1,4 +1,4 @@
-use crate::cube::State;
+use crate::cube::{PackedState, State};
 use crate::explorer::{
     CollectionModifier, ModifiedMoveSequenceCollection, NearbySequenceSearch,
     SameGroupAlternativeGenerator,
@@ -24,6 +24,8 @@ impl NearbySequenceSearchWorkflow {
         after_state: &State,
     ) -> Vec<ModifiedMoveSequenceCollection> {
         let mut results = Vec::new();
+        let before = PackedState::from(before_state);
+        let after = PackedState::from(after_state);
 
         // 各Sequenceについて探索
         for (seq_index, sequence) in self.sequences.iter().enumerate() {
@@ -39,8 +41,9 @@ impl NearbySequenceSearchWorkflow {
                 modified_collection.add_modifier(CollectionModifier::new(seq_index, modified_seq));
 
                 // Collection全体を適用して、Before → After を満たすかチェック
-                let final_state = modified_collection.apply_to_state(before_state);
-                if &final_state == after_state {
+                let mut final_state = before;
+                modified_collection.apply_to_packed(&mut final_state);
+                if final_state == after {
                     results.push(modified_collection);
                 }
             }
@@ -56,6 +59,8 @@ impl NearbySequenceSearchWorkflow {
         after_state: &State,
     ) -> Vec<AlternativeResult> {
         let mut results = Vec::new();
+        let before = PackedState::from(before_state);
+        let after = PackedState::from(after_state);
 
         for (seq_index, sequence) in self.sequences.iter().enumerate() {
             let generator = Box::new(SameGroupAlternativeGenerator::new());
@@ -68,11 +73,12 @@ impl NearbySequenceSearchWorkflow {
                     ModifiedMoveSequenceCollection::new(self.sequences.clone());
                 modified_collection.add_modifier(CollectionModifier::new(seq_index, modified_seq));
 
-                let final_state = modified_collection.apply_to_state(before_state);
-                if &final_state == after_state {
+                let mut final_state = before;
+                modified_collection.apply_to_packed(&mut final_state);
+                if final_state == after {
                     results.push(AlternativeResult {
                         modified_collection,
-                        final_state,
+                        final_state: final_state.to_state(),
                     });
                 }
             }
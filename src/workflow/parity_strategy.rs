@@ -0,0 +1,438 @@
+use crate::cube::state::{CORNER_NAMES, EDGE_NAMES};
+use crate::cube::validation::permutation_is_odd;
+use crate::cube::{PackedState, State};
+use crate::inspection::{
+    BldMethods, CornerInspection, CornerOperation, CornerSwapOperation, EdgeInspection,
+    EdgeOperation, EdgeParity, MoveSequence, MoveSequenceCollection, OperationsToTurns,
+    SolverConfig, EDGE_TARGET_STICKERS,
+};
+use crate::parser::{parse_sequence, Sequence};
+use std::collections::HashMap;
+
+/// Corner と Edge の操作列
+pub type ParityInspection = (Vec<CornerOperation>, Vec<EdgeOperation>);
+
+/// 奇置換（パリティ）のある状態の解き方
+///
+/// Inspection で操作列をどう作るかと、それを手順にどう変換するかの両方を決める。
+/// `BldWorkflow::with_parity_strategy` で設定する（デフォルトは `SwapInspectionParity`）
+pub trait ParityStrategy {
+    /// Corner と Edge の操作列を計算
+    fn inspect(&self, state: &State, converter: &OperationsToTurns) -> ParityInspection;
+
+    /// 操作列を Edge → Corner の順に `methods` で手順に変換
+    fn convert(
+        &self,
+        state: &State,
+        corner_operations: &[CornerOperation],
+        edge_operations: &[EdgeOperation],
+        config: &SolverConfig,
+        methods: &BldMethods,
+    ) -> Result<MoveSequenceCollection, String>;
+}
+
+/// 手順の長さを見ながら Corner の操作列を計算
+fn solve_corners(state: &State, converter: &OperationsToTurns) -> Vec<CornerOperation> {
+    CornerInspection::solve_corner_permutation_with_pair_cost(
+        state,
+        converter.config(),
+        &|swap1, swap2| converter.corner_pair_cost(swap1, swap2),
+    )
+}
+
+/// 手順の長さを見ながら Edge の操作列を計算
+fn solve_edges(
+    state: &State,
+    use_swap_inspection: bool,
+    converter: &OperationsToTurns,
+) -> Vec<EdgeOperation> {
+    EdgeInspection::solve_edge_permutation_with_pair_cost(
+        state,
+        use_swap_inspection,
+        converter.config(),
+        &|swap1, swap2| converter.edge_pair_cost(swap1, swap2),
+    )
+}
+
+/// Edge → Corner の順に手順に変換
+fn convert_in_order(
+    corner_operations: &[CornerOperation],
+    edge_operations: &[EdgeOperation],
+    methods: &BldMethods,
+) -> Result<MoveSequenceCollection, String> {
+    let mut sequences = methods.edges.convert(edge_operations)?;
+    sequences.extend(methods.corners.convert(corner_operations)?);
+    Ok(sequences)
+}
+
+/// コーナー（したがってエッジも）が奇置換か
+fn has_parity(state: &State) -> bool {
+    permutation_is_odd(&state.cp)
+}
+
+/// UF ↔ UR の交換分析（デフォルト）
+///
+/// Corner の交換が奇数なら、エッジは `config.parity_edges` を入れ替えて分析し、
+/// 残った Corner の交換を `ufr_parity` の手順（パリティのエッジの交換を含む）で解く。
+/// `config.edge_parity` が `EdgeParity::SwapParityEdges` 以外の場合は交換分析をせず、
+/// 残った Edge の交換はその設定に従って変換する
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SwapInspectionParity;
+
+impl ParityStrategy for SwapInspectionParity {
+    fn inspect(&self, state: &State, converter: &OperationsToTurns) -> ParityInspection {
+        let corner_operations = solve_corners(state, converter);
+
+        let corner_swap_count = corner_operations
+            .iter()
+            .filter(|op| matches!(op, CornerOperation::Swap(_)))
+            .count();
+        let use_swap_inspection = corner_swap_count % 2 == 1
+            && converter.config().edge_parity == EdgeParity::SwapParityEdges;
+
+        let edge_operations = solve_edges(state, use_swap_inspection, converter);
+        (corner_operations, edge_operations)
+    }
+
+    fn convert(
+        &self,
+        _state: &State,
+        corner_operations: &[CornerOperation],
+        edge_operations: &[EdgeOperation],
+        _config: &SolverConfig,
+        methods: &BldMethods,
+    ) -> Result<MoveSequenceCollection, String> {
+        convert_in_order(corner_operations, edge_operations, methods)
+    }
+}
+
+/// UFR-UBR などの疑似交換
+///
+/// パリティがある場合、コーナーは `corners` の2つを入れ替えた状態として分析し、
+/// 残った Edge の交換を、その交換と `corners` の交換を同時に行う手順で解く
+#[derive(Debug, Clone)]
+pub struct PseudoSwapParity {
+    corners: [usize; 2],
+    /// Edge のターゲットのステッカー → 手順
+    algs: HashMap<String, Sequence>,
+}
+
+impl PseudoSwapParity {
+    /// `parity_json` は `{"FR": "..."}` の形で、キーは Edge のターゲットのステッカー
+    /// （`uf_expanded.json` と同じ `EDGE_TARGET_STICKERS` の名前）
+    ///
+    /// 各手順は、エッジのバッファとターゲットの交換に加えて `corners` の2つを
+    /// 向きを変えずに交換するもの
+    pub fn new(corners: [usize; 2], parity_json: &str) -> Result<Self, String> {
+        let [corner1, corner2] = corners;
+        if corner1 >= 8 || corner2 >= 8 || corner1 == corner2 {
+            return Err(format!(
+                "Pseudo swap needs two different corners: {:?}",
+                corners
+            ));
+        }
+
+        let algs: HashMap<String, String> = serde_json::from_str(parity_json)
+            .map_err(|e| format!("Failed to parse pseudo swap algs: {}", e))?;
+        let algs = algs
+            .into_iter()
+            .map(|(key, alg)| {
+                let moves = parse_sequence(&alg)
+                    .map_err(|e| format!("Failed to parse pseudo swap alg for {}: {}", key, e))?;
+                Ok((key, moves))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self { corners, algs })
+    }
+}
+
+impl ParityStrategy for PseudoSwapParity {
+    fn inspect(&self, state: &State, converter: &OperationsToTurns) -> ParityInspection {
+        let edge_operations = solve_edges(state, false, converter);
+
+        // Edge のパリティ手順が先に corners を交換する
+        let corner_state = if has_parity(state) {
+            CornerSwapOperation::new(self.corners[0], self.corners[1], 0).apply(state)
+        } else {
+            state.clone()
+        };
+        let corner_operations = solve_corners(&corner_state, converter);
+
+        (corner_operations, edge_operations)
+    }
+
+    fn convert(
+        &self,
+        state: &State,
+        corner_operations: &[CornerOperation],
+        edge_operations: &[EdgeOperation],
+        config: &SolverConfig,
+        methods: &BldMethods,
+    ) -> Result<MoveSequenceCollection, String> {
+        // 奇数個の Edge の交換の最後は組にならない
+        let lone = edge_operations
+            .iter()
+            .rposition(|op| matches!(op, EdgeOperation::Swap(_)))
+            .filter(|_| has_parity(state));
+        let Some(i) = lone else {
+            return convert_in_order(corner_operations, edge_operations, methods);
+        };
+        let EdgeOperation::Swap(swap) = &edge_operations[i] else {
+            unreachable!("rposition found a swap");
+        };
+
+        if swap.target1 != config.edge_buffer {
+            return Err(format!(
+                "No pseudo swap alg for edge buffer {}",
+                EDGE_NAMES[swap.target1]
+            ));
+        }
+        let target_sticker = EDGE_TARGET_STICKERS[swap.target2][swap.orientation as usize];
+        let moves = self.algs.get(target_sticker).ok_or_else(|| {
+            format!(
+                "Pseudo swap alg not found for target_sticker: {}",
+                target_sticker
+            )
+        })?;
+        let description = format!(
+            "Parity: {} ({}-{})",
+            target_sticker, CORNER_NAMES[self.corners[0]], CORNER_NAMES[self.corners[1]]
+        );
+
+        let mut sequences = methods.edges.convert(&edge_operations[..i])?;
+        sequences.push(MoveSequence::new(moves.clone(), description));
+        sequences.extend(methods.edges.convert(&edge_operations[i + 1..])?);
+        sequences.extend(methods.corners.convert(corner_operations)?);
+        Ok(sequences)
+    }
+}
+
+/// コーナーとエッジを1組ずつ交換するパリティ手順
+#[derive(Debug, Clone)]
+struct ParityAlg {
+    moves: Sequence,
+    /// 完成状態に手順を適用した状態
+    effect: State,
+}
+
+impl ParityAlg {
+    fn new(alg: &str) -> Result<Self, String> {
+        let moves =
+            parse_sequence(alg).map_err(|e| format!("Failed to parse parity alg: {}", e))?;
+
+        let mut effect = PackedState::solved();
+        effect.apply_sequence(&moves);
+        let effect = effect.to_state();
+
+        if !permutation_is_odd(&effect.cp) || !effect.centers_solved() {
+            return Err(format!(
+                "Parity alg must be an odd permutation that keeps the centers: {}",
+                alg
+            ));
+        }
+        Ok(Self { moves, effect })
+    }
+
+    fn sequence(&self) -> MoveSequence {
+        MoveSequence::new(self.moves.clone(), "Parity".to_string())
+    }
+}
+
+/// 最後にパリティ手順を回す
+///
+/// パリティがある場合、パリティ手順 → スクランブルの順に回した状態を分析するので、
+/// Corner も Edge も交換は全て組になる
+#[derive(Debug, Clone)]
+pub struct ParityAtEnd {
+    alg: ParityAlg,
+}
+
+impl ParityAtEnd {
+    /// `alg` は T perm のように、コーナーとエッジを奇置換にする手順（センターは動かさない）
+    pub fn new(alg: &str) -> Result<Self, String> {
+        Ok(Self {
+            alg: ParityAlg::new(alg)?,
+        })
+    }
+}
+
+impl ParityStrategy for ParityAtEnd {
+    fn inspect(&self, state: &State, converter: &OperationsToTurns) -> ParityInspection {
+        let state = if has_parity(state) {
+            self.alg.effect.apply_move(state)
+        } else {
+            state.clone()
+        };
+        (
+            solve_corners(&state, converter),
+            solve_edges(&state, false, converter),
+        )
+    }
+
+    fn convert(
+        &self,
+        state: &State,
+        corner_operations: &[CornerOperation],
+        edge_operations: &[EdgeOperation],
+        _config: &SolverConfig,
+        methods: &BldMethods,
+    ) -> Result<MoveSequenceCollection, String> {
+        let mut sequences = convert_in_order(corner_operations, edge_operations, methods)?;
+        if has_parity(state) {
+            sequences.push(self.alg.sequence());
+        }
+        Ok(sequences)
+    }
+}
+
+/// Edge の記憶の最初にパリティを解く
+///
+/// パリティがある場合、まずパリティ手順を回し、その後の状態を分析する
+#[derive(Debug, Clone)]
+pub struct EdgeMemoFirst {
+    alg: ParityAlg,
+}
+
+impl EdgeMemoFirst {
+    /// `alg` は T perm のように、コーナーとエッジを奇置換にする手順（センターは動かさない）
+    pub fn new(alg: &str) -> Result<Self, String> {
+        Ok(Self {
+            alg: ParityAlg::new(alg)?,
+        })
+    }
+}
+
+impl ParityStrategy for EdgeMemoFirst {
+    fn inspect(&self, state: &State, converter: &OperationsToTurns) -> ParityInspection {
+        let state = if has_parity(state) {
+            state.apply_move(&self.alg.effect)
+        } else {
+            state.clone()
+        };
+        (
+            solve_corners(&state, converter),
+            solve_edges(&state, false, converter),
+        )
+    }
+
+    fn convert(
+        &self,
+        state: &State,
+        corner_operations: &[CornerOperation],
+        edge_operations: &[EdgeOperation],
+        _config: &SolverConfig,
+        methods: &BldMethods,
+    ) -> Result<MoveSequenceCollection, String> {
+        let mut sequences = MoveSequenceCollection::new();
+        if has_parity(state) {
+            sequences.push(self.alg.sequence());
+        }
+        sequences.extend(convert_in_order(
+            corner_operations,
+            edge_operations,
+            methods,
+        )?);
+        Ok(sequences)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow::bld_workflow::create_test_workflow;
+
+    /// UFR ↔ UBR と UF ↔ UR を交換する手順
+    fn t_perm() -> String {
+        let ufr_parity: serde_json::Value =
+            serde_json::from_str(include_str!("../../resources/ufr_parity.json")).unwrap();
+        ufr_parity["UBR"].as_str().unwrap().to_string()
+    }
+
+    /// コーナーもエッジも 2 点交換（パリティ）
+    fn parity_state() -> State {
+        State::new(
+            [1, 0, 2, 3, 4, 5, 6, 7],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [1, 0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        )
+    }
+
+    fn corner_swap_count(operations: &[CornerOperation]) -> usize {
+        operations
+            .iter()
+            .filter(|op| matches!(op, CornerOperation::Swap(_)))
+            .count()
+    }
+
+    #[test]
+    fn test_swap_inspection_parity() {
+        let workflow = create_test_workflow().with_parity_strategy(SwapInspectionParity);
+        let solution = workflow.solve(&parity_state()).expect("Failed to solve");
+        assert_eq!(corner_swap_count(&solution.corner_operations) % 2, 1);
+    }
+
+    #[test]
+    fn test_pseudo_swap_parity() {
+        // UF ↔ X と UF ↔ UR の組の後に T perm を回すと、UF ↔ X と UFR ↔ UBR の交換になる
+        let t_perm = t_perm();
+        let uf_expanded: serde_json::Value =
+            serde_json::from_str(include_str!("../../resources/uf_expanded.json")).unwrap();
+        let mut algs: HashMap<String, String> = uf_expanded
+            .as_object()
+            .unwrap()
+            .iter()
+            .filter_map(|(target, inner)| {
+                let pair = inner.get("UR")?.as_str()?;
+                Some((target.clone(), format!("{} {}", pair, t_perm)))
+            })
+            .collect();
+        algs.insert("UR".to_string(), t_perm);
+        let strategy =
+            PseudoSwapParity::new([2, 1], &serde_json::to_string(&algs).unwrap()).unwrap();
+
+        let workflow = create_test_workflow().with_parity_strategy(strategy);
+        let solution = workflow.solve(&parity_state()).expect("Failed to solve");
+
+        assert_eq!(corner_swap_count(&solution.corner_operations) % 2, 0);
+        assert!(solution
+            .move_sequences
+            .sequences()
+            .iter()
+            .any(|seq| seq.description.ends_with("(UFR-UBR)")));
+    }
+
+    #[test]
+    fn test_pseudo_swap_parity_rejects_same_corners() {
+        assert!(PseudoSwapParity::new([2, 2], "{}").is_err());
+    }
+
+    #[test]
+    fn test_parity_at_end() {
+        let strategy = ParityAtEnd::new(&t_perm()).unwrap();
+        let workflow = create_test_workflow().with_parity_strategy(strategy);
+        let solution = workflow.solve(&parity_state()).expect("Failed to solve");
+
+        assert_eq!(corner_swap_count(&solution.corner_operations) % 2, 0);
+        let sequences = solution.move_sequences.sequences();
+        assert_eq!(sequences.last().unwrap().description, "Parity");
+    }
+
+    #[test]
+    fn test_edge_memo_first() {
+        let strategy = EdgeMemoFirst::new(&t_perm()).unwrap();
+        let workflow = create_test_workflow().with_parity_strategy(strategy);
+        let solution = workflow.solve(&parity_state()).expect("Failed to solve");
+
+        assert_eq!(corner_swap_count(&solution.corner_operations) % 2, 0);
+        let sequences = solution.move_sequences.sequences();
+        assert_eq!(sequences.first().unwrap().description, "Parity");
+    }
+
+    #[test]
+    fn test_parity_alg_must_be_odd() {
+        assert!(ParityAtEnd::new("R2").is_err());
+        assert!(EdgeMemoFirst::new("M2").is_err());
+    }
+}